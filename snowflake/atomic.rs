@@ -0,0 +1,142 @@
+/*!
+无锁雪花算法生成器
+
+`SnowflakeIdWorker` 需要 `&mut self`，多线程共享时必须加锁。`AtomicIdWorker` 把
+"上次发放的时间戳+序列号" 保存在一个 `AtomicU64` 中，每次生成用CAS把它推进到
+`max(当前毫秒的第一个序列号, 上次 + 1)`:
+
+- 同一毫秒内序列号加1；序列号用尽时进位到下一毫秒 (相当于借用未来时间戳)
+- 时间戳最多领先系统时钟 `max_backward_ms` 毫秒，超过后自旋等待时钟追上
+- 系统时钟回拨不超过 `max_backward_ms` 时继续在上次的基础上递增，超过时返回 `ClockBackward` 错误
+
+发放的ID严格递增，CAS成功的线程独占该ID，因此不会重复。
+`sequence_exhausted_policy` 和 `sequence_start` 配置对该生成器不生效: 序列号用尽时总是进位借用，
+领先上限由 `max_backward_ms` 决定而不是 `Borrow(N)` 的N (领先的时间戳与回拨一样，
+都要在时钟追上前保持递增，因此共用同一个容忍范围)。
+
+使用 `--cfg loom` 编译时原子变量换成loom的实现，用于模型检查 (见 `tests/loom.rs`)。
+*/
+
+use std::sync::Arc;
+
+#[cfg(loom)]
+use loom::{
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+};
+#[cfg(not(loom))]
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+};
+
+use crate::generator::IdGenerator;
+use crate::{Clock, DecodedId, SnowflakeConfig, SnowflakeError, SystemClock, TWEPOCH_MS};
+
+/// 无锁雪花算法生成器
+#[derive(Debug)]
+pub struct AtomicIdWorker {
+    /// 配置参数
+    config: SnowflakeConfig,
+    /// 当前Worker ID
+    worker_id: u8,
+    /// 上次发放的 (时间戳 << 序列号位数) | 序列号，尚未发放时为0
+    state: AtomicU64,
+    /// 时钟 (默认为系统时钟)
+    clock: Arc<dyn Clock>,
+}
+
+impl AtomicIdWorker {
+    /// 创建使用指定Worker ID的无锁生成器
+    ///
+    /// 返回:
+    /// - Err(SnowflakeError::ConfigError): Worker ID超出 `worker_id_bits` 能表示的范围，或位布局无效 (见 `SnowflakeConfig::check_layout`)
+    pub fn new(config: Option<SnowflakeConfig>, worker_id: u8) -> Result<Self, SnowflakeError> {
+        Self::with_clock(config, worker_id, Arc::new(SystemClock))
+    }
+
+    /// 使用指定时钟创建无锁生成器 (测试中可以注入模拟时钟)
+    pub fn with_clock(
+        config: Option<SnowflakeConfig>,
+        worker_id: u8,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self, SnowflakeError> {
+        let config = config.unwrap_or_default();
+        config.check_layout()?;
+        if config.worker_id_bits < 8 && worker_id >> config.worker_id_bits != 0 {
+            return Err(SnowflakeError::ConfigError(format!(
+                "Worker ID {}超出{}位能表示的范围",
+                worker_id, config.worker_id_bits
+            )));
+        }
+        Ok(Self {
+            config,
+            worker_id,
+            state: AtomicU64::new(0),
+            clock,
+        })
+    }
+
+    /// 生成下一个唯一ID
+    pub fn next_id(&self) -> Result<u64, SnowflakeError> {
+        let sequence_bits = self.config.sequence_bits;
+        let max_ahead_ms = self.config.max_backward_ms;
+        loop {
+            let last = self.state.load(Ordering::Acquire);
+            let now = self.time_gen()?;
+            let last_timestamp = last >> sequence_bits;
+
+            // 时钟回拨超出容忍范围
+            if now + max_ahead_ms < last_timestamp {
+                return Err(SnowflakeError::ClockBackward(format!(
+                    "时钟回拨过大: {}ms，超出容忍范围{}ms",
+                    last_timestamp - now,
+                    max_ahead_ms
+                )));
+            }
+
+            let next = if now > last_timestamp { now << sequence_bits } else { last + 1 };
+            // 序列号用尽进位后领先系统时钟过多，等待时钟追上
+            if next >> sequence_bits > now + max_ahead_ms {
+                thread::yield_now();
+                continue;
+            }
+
+            if self
+                .state
+                .compare_exchange_weak(last, next, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                let timestamp = next >> sequence_bits;
+                let sequence = next & ((1u64 << sequence_bits) - 1);
+                return Ok(timestamp << (self.config.worker_id_bits + sequence_bits)
+                    | (self.worker_id as u64) << sequence_bits
+                    | sequence);
+            }
+        }
+    }
+
+    /// 获取Worker ID
+    pub fn get_worker_id(&self) -> u8 {
+        self.worker_id
+    }
+
+    /// 解析该生成器生成的ID
+    pub fn decode(&self, id: u64) -> DecodedId {
+        self.config.decode(id)
+    }
+
+    /// 当前时间相对基准时间的毫秒数
+    fn time_gen(&self) -> Result<u64, SnowflakeError> {
+        let now_ms = self.clock.now_ms()?;
+        now_ms
+            .checked_sub(TWEPOCH_MS)
+            .ok_or_else(|| SnowflakeError::ConfigError("基准时间设置在未来".to_string()))
+    }
+}
+
+impl IdGenerator for AtomicIdWorker {
+    fn next_id(&self) -> Result<u64, SnowflakeError> {
+        AtomicIdWorker::next_id(self)
+    }
+}
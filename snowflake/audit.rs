@@ -0,0 +1,758 @@
+/*!
+ID审计: 检查多个节点导出的ID是否全局唯一
+
+事故复盘时需要证明各节点生成的ID没有重复。审计器读取每个节点的ID导出文件
+(文本或二进制)，以流式方式处理，不需要把所有ID放在内存里:
+
+1. 读取阶段: 逐条解析ID，按节点统计实际使用的Worker ID、单调性和未来时间的ID；
+   同时把 (ID, 节点) 放入固定大小的缓冲区，缓冲区满时排序后写入临时文件 (一个有序段)
+2. 合并阶段: 多路归并所有有序段，得到全局有序的ID流，相邻相等即为重复ID；
+   同一 (毫秒, Worker ID) 的ID在有序流中相邻，据此统计每毫秒的序列号是否用满
+
+检查项:
+- 重复ID: 出现多次的ID及其来源节点
+- Worker ID: 每个节点的导出中实际出现的Worker ID
+- 未来ID: 时间戳晚于审计时间 (加上容忍度) 的ID，通常是时钟跳变造成的
+- 单调性: 同一节点、同一Worker ID的ID按导出顺序应严格递增，记录回退次数
+- 序列号饱和: 同一毫秒、同一Worker ID生成的ID数达到每毫秒上限
+
+导出格式:
+- 文本: 每行一个十进制ID，忽略空行和 # 开头的注释行；行内有多列时取第一列 (空白或逗号分隔)
+- 二进制: 连续的8字节大端序u64
+*/
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tracing::{debug, info, warn};
+
+use crate::clock::{Clock, SystemClock};
+use crate::{SnowflakeConfig, SnowflakeError};
+
+/// 一次最多合并的有序段数 (超过时分多轮合并，避免同时打开过多文件)
+const MERGE_FAN_IN: usize = 64;
+
+/// 有序段文件中每条记录的字节数: 8字节ID + 4字节节点序号
+const RUN_RECORD_BYTES: usize = 12;
+
+/// 重复ID样本中最多记录的来源节点数
+const MAX_SAMPLE_NODES: usize = 16;
+
+/// ID导出文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// 每行一个十进制ID
+    Text,
+    /// 连续的8字节大端序u64
+    Binary,
+}
+
+impl DumpFormat {
+    /// 按扩展名推断格式: .bin 为二进制，其他为文本
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("bin") => DumpFormat::Binary,
+            _ => DumpFormat::Text,
+        }
+    }
+}
+
+/// 审计配置
+#[derive(Debug, Clone)]
+pub struct AuditConfig {
+    /// ID布局 (只使用 worker_id_bits 和 sequence_bits)
+    pub layout: SnowflakeConfig,
+
+    /// 内存中最多缓存的ID数，超过后排序写入临时文件
+    pub chunk_size: usize,
+
+    /// 临时文件目录
+    pub temp_dir: PathBuf,
+
+    /// 审计时间 (Unix毫秒时间戳)，None表示使用当前系统时间
+    pub now_ms: Option<u64>,
+
+    /// 时间戳晚于审计时间超过该值的ID视为未来ID (毫秒)
+    pub future_tolerance_ms: u64,
+
+    /// 每类问题最多保留的样本数
+    pub max_samples: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            layout: SnowflakeConfig::default(),
+            chunk_size: 1_000_000,     // 约12MB内存
+            temp_dir: env::temp_dir(),
+            now_ms: None,
+            future_tolerance_ms: 1000, // 容忍1秒的时钟偏差
+            max_samples: 10,
+        }
+    }
+}
+
+// ============================================================================
+// 审计结果
+// ============================================================================
+
+/// 重复ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateId {
+    pub id: u64,
+    /// 出现次数
+    pub occurrences: u64,
+    /// 来源节点 (去重，最多16个)
+    pub nodes: Vec<String>,
+}
+
+/// 节点实际使用的一个Worker ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerUsage {
+    pub worker_id: u64,
+    /// 该Worker ID的ID数量
+    pub ids: u64,
+    /// 最早的ID时间 (Unix毫秒时间戳)
+    pub first_ms: u64,
+    /// 最晚的ID时间 (Unix毫秒时间戳)
+    pub last_ms: u64,
+}
+
+/// 单个节点的统计
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeReport {
+    pub name: String,
+    /// 有效ID数量
+    pub ids: u64,
+    /// 无法解析的记录数
+    pub invalid_records: u64,
+    /// 实际使用的Worker ID (按Worker ID排序)
+    pub workers: Vec<WorkerUsage>,
+}
+
+/// 时间戳晚于审计时间的ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FutureId {
+    pub node: String,
+    pub id: u64,
+    /// ID中的时间 (Unix毫秒时间戳)
+    pub timestamp_ms: u64,
+}
+
+/// 同一节点、同一Worker ID的ID序列中出现回退
+/// ID序列被回退分成 regressions + 1 段单调递增的区间
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonMonotonicRuns {
+    pub node: String,
+    pub worker_id: u64,
+    /// 回退次数 (后一个ID不大于前一个ID)
+    pub regressions: u64,
+    /// 最大回退幅度 (毫秒)
+    pub max_backward_ms: u64,
+    /// 第一次回退: (前一个ID, 回退后的ID)
+    pub first: (u64, u64),
+}
+
+/// 序列号用满的一毫秒
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaturatedTick {
+    pub worker_id: u64,
+    /// Unix毫秒时间戳
+    pub timestamp_ms: u64,
+    /// 该毫秒内的不同ID数
+    pub ids: u64,
+}
+
+/// 审计报告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditReport {
+    /// 有效ID总数
+    pub total_ids: u64,
+    /// 不同ID的数量
+    pub distinct_ids: u64,
+    /// 各节点统计 (按添加顺序)
+    pub nodes: Vec<NodeReport>,
+
+    /// 出现多次的ID数量
+    pub duplicate_ids: u64,
+    /// 多出的重复次数 (total_ids - distinct_ids)
+    pub duplicate_occurrences: u64,
+    /// 重复ID样本
+    pub duplicates: Vec<DuplicateId>,
+
+    /// 未来ID数量
+    pub future_ids: u64,
+    /// 未来ID样本
+    pub future_samples: Vec<FutureId>,
+
+    /// 出现回退的 (节点, Worker ID)
+    pub non_monotonic: Vec<NonMonotonicRuns>,
+
+    /// 每毫秒ID数上限 (2^sequence_bits)
+    pub ids_per_ms_limit: u64,
+    /// 同一Worker ID在一毫秒内的最大ID数
+    pub max_ids_per_ms: u64,
+    /// 序列号用满的 (毫秒, Worker ID) 数量
+    pub saturated_ticks: u64,
+    /// 序列号用满的样本
+    pub saturated_samples: Vec<SaturatedTick>,
+}
+
+impl AuditReport {
+    /// 没有重复ID和未来ID
+    /// 回退和序列号饱和不一定是错误，需要结合业务判断
+    pub fn is_clean(&self) -> bool {
+        self.duplicate_ids == 0 && self.future_ids == 0
+    }
+}
+
+// ============================================================================
+// 读取阶段
+// ============================================================================
+
+/// 单个Worker ID在一个节点中的状态
+#[derive(Debug)]
+struct WorkerState {
+    ids: u64,
+    first_ms: u64,
+    last_ms: u64,
+    last_id: u64,
+    regressions: u64,
+    max_backward_ms: u64,
+    first_regression: Option<(u64, u64)>,
+}
+
+/// 单个节点的状态
+#[derive(Debug)]
+struct NodeState {
+    name: String,
+    ids: u64,
+    invalid_records: u64,
+    workers: BTreeMap<u64, WorkerState>,
+}
+
+/// ID审计器
+///
+/// 依次调用 `add_dump` / `add_reader` 读取各节点的导出，最后调用 `finish` 得到报告。
+/// 同一节点名可以添加多个导出，按添加顺序视为同一个序列。
+#[derive(Debug)]
+pub struct Auditor {
+    config: AuditConfig,
+    now_ms: u64,
+    nodes: Vec<NodeState>,
+    node_index: HashMap<String, u32>,
+    /// 尚未写入临时文件的 (ID, 节点序号)
+    buffer: Vec<(u64, u32)>,
+    /// 临时目录 (第一次写入有序段时创建)
+    work_dir: Option<PathBuf>,
+    /// 有序段文件
+    runs: Vec<PathBuf>,
+    /// 已创建的有序段文件数 (用于文件命名)
+    runs_created: usize,
+    future_ids: u64,
+    future_samples: Vec<FutureId>,
+}
+
+impl Auditor {
+    /// 创建审计器
+    ///
+    /// 参数:
+    /// - config: 可选的配置参数，如果为None则使用默认配置
+    pub fn new(config: Option<AuditConfig>) -> Result<Self, SnowflakeError> {
+        let config = config.unwrap_or_default();
+        if config.chunk_size == 0 {
+            return Err(SnowflakeError::ConfigError("审计缓冲区大小必须大于0".to_string()));
+        }
+        config.layout.check_layout()?;
+        let now_ms = match config.now_ms {
+            Some(ms) => ms,
+            None => SystemClock.now_ms()?,
+        };
+
+        Ok(Self {
+            buffer: Vec::with_capacity(config.chunk_size.min(1 << 20)),
+            config,
+            now_ms,
+            nodes: Vec::new(),
+            node_index: HashMap::new(),
+            work_dir: None,
+            runs: Vec::new(),
+            runs_created: 0,
+            future_ids: 0,
+            future_samples: Vec::new(),
+        })
+    }
+
+    /// 读取一个导出文件
+    pub fn add_dump(&mut self, node: &str, path: impl AsRef<Path>, format: DumpFormat) -> Result<(), SnowflakeError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| io_error(path, e))?;
+        info!(node, path = %path.display(), ?format, "读取ID导出");
+        // 读取失败时带上文件路径，错误类型不变
+        self.add_reader(node, file, format).map_err(|e| match e {
+            SnowflakeError::ConfigError(msg) => SnowflakeError::ConfigError(format!("{}: {}", path.display(), msg)),
+            SnowflakeError::IoError(msg) => SnowflakeError::IoError(format!("{}: {}", path.display(), msg)),
+            other => other,
+        })
+    }
+
+    /// 从任意数据源读取一个节点的ID
+    pub fn add_reader(&mut self, node: &str, reader: impl Read, format: DumpFormat) -> Result<(), SnowflakeError> {
+        let node_idx = self.node(node);
+        let mut reader = BufReader::new(reader);
+
+        match format {
+            DumpFormat::Text => {
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    if reader.read_line(&mut line).map_err(read_error)? == 0 {
+                        break;
+                    }
+                    let trimmed = line.trim();
+                    if trimmed.is_empty() || trimmed.starts_with('#') {
+                        continue;
+                    }
+                    let field = trimmed
+                        .split(|c: char| c.is_whitespace() || c == ',')
+                        .next()
+                        .unwrap_or_default();
+                    match field.parse::<u64>() {
+                        Ok(id) => self.record(node_idx, id)?,
+                        Err(_) => self.invalid(node_idx, trimmed),
+                    }
+                }
+            }
+            DumpFormat::Binary => {
+                let mut buf = [0u8; 8];
+                loop {
+                    let filled = read_full(&mut reader, &mut buf).map_err(read_error)?;
+                    if filled == 8 {
+                        self.record(node_idx, u64::from_be_bytes(buf))?;
+                    } else {
+                        if filled > 0 {
+                            self.invalid(node_idx, &format!("末尾不完整的{}字节", filled));
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 节点序号 (不存在时创建)
+    fn node(&mut self, name: &str) -> u32 {
+        if let Some(&idx) = self.node_index.get(name) {
+            return idx;
+        }
+        let idx = self.nodes.len() as u32;
+        self.nodes.push(NodeState {
+            name: name.to_string(),
+            ids: 0,
+            invalid_records: 0,
+            workers: BTreeMap::new(),
+        });
+        self.node_index.insert(name.to_string(), idx);
+        idx
+    }
+
+    /// 记录一条无法解析的记录
+    fn invalid(&mut self, node_idx: u32, record: &str) {
+        let node = &mut self.nodes[node_idx as usize];
+        if node.invalid_records == 0 {
+            warn!(node = %node.name, record, "无法解析的ID记录");
+        }
+        node.invalid_records += 1;
+    }
+
+    /// 处理一个ID
+    fn record(&mut self, node_idx: u32, id: u64) -> Result<(), SnowflakeError> {
+        let decoded = self.config.layout.decode(id);
+        let node = &mut self.nodes[node_idx as usize];
+        node.ids += 1;
+
+        let worker = node.workers.entry(decoded.worker_id).or_insert(WorkerState {
+            ids: 0,
+            first_ms: decoded.timestamp_ms,
+            last_ms: decoded.timestamp_ms,
+            last_id: id,
+            regressions: 0,
+            max_backward_ms: 0,
+            first_regression: None,
+        });
+        if worker.ids > 0 && id <= worker.last_id {
+            let last_ms = self.config.layout.decode(worker.last_id).timestamp_ms;
+            worker.regressions += 1;
+            worker.max_backward_ms = worker.max_backward_ms.max(last_ms - decoded.timestamp_ms);
+            worker.first_regression.get_or_insert((worker.last_id, id));
+        }
+        worker.ids += 1;
+        worker.first_ms = worker.first_ms.min(decoded.timestamp_ms);
+        worker.last_ms = worker.last_ms.max(decoded.timestamp_ms);
+        worker.last_id = id;
+
+        if decoded.timestamp_ms > self.now_ms.saturating_add(self.config.future_tolerance_ms) {
+            self.future_ids += 1;
+            if self.future_samples.len() < self.config.max_samples {
+                self.future_samples.push(FutureId {
+                    node: node.name.clone(),
+                    id,
+                    timestamp_ms: decoded.timestamp_ms,
+                });
+            }
+        }
+
+        self.buffer.push((id, node_idx));
+        if self.buffer.len() >= self.config.chunk_size {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// 把缓冲区排序后写入一个有序段文件
+    fn spill(&mut self) -> Result<(), SnowflakeError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.buffer.sort_unstable();
+
+        let path = self.next_run_path()?;
+        let file = File::create(&path).map_err(|e| io_error(&path, e))?;
+        let mut writer = BufWriter::new(file);
+        for &(id, node) in &self.buffer {
+            write_record(&mut writer, id, node).map_err(|e| io_error(&path, e))?;
+        }
+        writer.flush().map_err(|e| io_error(&path, e))?;
+
+        debug!(path = %path.display(), records = self.buffer.len(), "写入有序段");
+        self.buffer.clear();
+        self.runs.push(path);
+        Ok(())
+    }
+
+    /// 新的有序段文件路径 (第一次调用时创建临时目录)
+    fn next_run_path(&mut self) -> Result<PathBuf, SnowflakeError> {
+        let work_dir = match &self.work_dir {
+            Some(dir) => dir.clone(),
+            None => {
+                let nanos = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or_default();
+                let dir = self
+                    .config
+                    .temp_dir
+                    .join(format!("snowflake-audit-{}-{}", process::id(), nanos));
+                fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+                self.work_dir = Some(dir.clone());
+                dir
+            }
+        };
+        self.runs_created += 1;
+        Ok(work_dir.join(format!("run-{}.bin", self.runs_created)))
+    }
+
+    // ========================================================================
+    // 合并阶段
+    // ========================================================================
+
+    /// 合并所有ID，生成审计报告
+    pub fn finish(mut self) -> Result<AuditReport, SnowflakeError> {
+        let mut analysis = Analysis::new(&self.config);
+
+        if self.runs.is_empty() {
+            // 所有ID都在内存中，不需要临时文件
+            self.buffer.sort_unstable();
+            for &(id, node) in &self.buffer {
+                analysis.push(id, node, &self.nodes);
+            }
+        } else {
+            self.spill()?;
+            // 有序段过多时先分批合并，直到可以一次合并完
+            while self.runs.len() > MERGE_FAN_IN {
+                let batch: Vec<PathBuf> = self.runs.drain(..MERGE_FAN_IN).collect();
+                let path = self.next_run_path()?;
+                let file = File::create(&path).map_err(|e| io_error(&path, e))?;
+                let mut writer = BufWriter::new(file);
+                let mut merger = Merger::open(&batch)?;
+                while let Some((id, node)) = merger.next_record()? {
+                    write_record(&mut writer, id, node).map_err(|e| io_error(&path, e))?;
+                }
+                writer.flush().map_err(|e| io_error(&path, e))?;
+                for run in &batch {
+                    let _ = fs::remove_file(run);
+                }
+                self.runs.push(path);
+            }
+
+            let mut merger = Merger::open(&self.runs)?;
+            while let Some((id, node)) = merger.next_record()? {
+                analysis.push(id, node, &self.nodes);
+            }
+        }
+        analysis.finish(&self.nodes);
+
+        let report = self.build_report(analysis);
+        info!(
+            total = report.total_ids,
+            distinct = report.distinct_ids,
+            duplicates = report.duplicate_ids,
+            future = report.future_ids,
+            non_monotonic = report.non_monotonic.len(),
+            saturated = report.saturated_ticks,
+            "ID审计完成"
+        );
+        Ok(report)
+    }
+
+    fn build_report(&mut self, analysis: Analysis) -> AuditReport {
+        let mut non_monotonic = Vec::new();
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                for (&worker_id, worker) in &node.workers {
+                    if let Some(first) = worker.first_regression {
+                        non_monotonic.push(NonMonotonicRuns {
+                            node: node.name.clone(),
+                            worker_id,
+                            regressions: worker.regressions,
+                            max_backward_ms: worker.max_backward_ms,
+                            first,
+                        });
+                    }
+                }
+                NodeReport {
+                    name: node.name.clone(),
+                    ids: node.ids,
+                    invalid_records: node.invalid_records,
+                    workers: node
+                        .workers
+                        .iter()
+                        .map(|(&worker_id, worker)| WorkerUsage {
+                            worker_id,
+                            ids: worker.ids,
+                            first_ms: worker.first_ms,
+                            last_ms: worker.last_ms,
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        AuditReport {
+            total_ids: analysis.total_ids,
+            distinct_ids: analysis.distinct_ids,
+            nodes,
+            duplicate_ids: analysis.duplicate_ids,
+            duplicate_occurrences: analysis.total_ids - analysis.distinct_ids,
+            duplicates: analysis.duplicates,
+            future_ids: self.future_ids,
+            future_samples: std::mem::take(&mut self.future_samples),
+            non_monotonic,
+            ids_per_ms_limit: analysis.ids_per_ms_limit,
+            max_ids_per_ms: analysis.max_ids_per_ms,
+            saturated_ticks: analysis.saturated_ticks,
+            saturated_samples: analysis.saturated_samples,
+        }
+    }
+}
+
+impl Drop for Auditor {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.work_dir {
+            if let Err(e) = fs::remove_dir_all(dir) {
+                warn!(path = %dir.display(), error = %e, "无法删除审计临时目录");
+            }
+        }
+    }
+}
+
+/// 对全局有序的ID流做重复和饱和统计
+#[derive(Debug)]
+struct Analysis {
+    layout: SnowflakeConfig,
+    max_samples: usize,
+    total_ids: u64,
+    distinct_ids: u64,
+
+    /// 当前ID及其出现次数、来源节点
+    current: Option<(u64, u64, Vec<u32>)>,
+    duplicate_ids: u64,
+    duplicates: Vec<DuplicateId>,
+
+    /// 当前 (时间戳, Worker ID) 即 ID >> sequence_bits，及其中的不同ID数
+    tick: Option<(u64, u64)>,
+    ids_per_ms_limit: u64,
+    max_ids_per_ms: u64,
+    saturated_ticks: u64,
+    saturated_samples: Vec<SaturatedTick>,
+}
+
+impl Analysis {
+    fn new(config: &AuditConfig) -> Self {
+        Self {
+            layout: config.layout.clone(),
+            max_samples: config.max_samples,
+            total_ids: 0,
+            distinct_ids: 0,
+            current: None,
+            duplicate_ids: 0,
+            duplicates: Vec::new(),
+            tick: None,
+            ids_per_ms_limit: 1u64 << config.layout.sequence_bits,
+            max_ids_per_ms: 0,
+            saturated_ticks: 0,
+            saturated_samples: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, id: u64, node: u32, nodes: &[NodeState]) {
+        self.total_ids += 1;
+        if let Some((current, occurrences, sources)) = &mut self.current {
+            if *current == id {
+                *occurrences += 1;
+                if !sources.contains(&node) && sources.len() < MAX_SAMPLE_NODES {
+                    sources.push(node);
+                }
+                return;
+            }
+        }
+        self.flush_duplicate(nodes);
+        self.current = Some((id, 1, vec![node]));
+        self.distinct_ids += 1;
+
+        let tick = id >> self.layout.sequence_bits;
+        match &mut self.tick {
+            Some((current, count)) if *current == tick => *count += 1,
+            _ => {
+                self.flush_tick();
+                self.tick = Some((tick, 1));
+            }
+        }
+    }
+
+    fn finish(&mut self, nodes: &[NodeState]) {
+        self.flush_duplicate(nodes);
+        self.flush_tick();
+    }
+
+    fn flush_duplicate(&mut self, nodes: &[NodeState]) {
+        if let Some((id, occurrences, sources)) = self.current.take() {
+            if occurrences > 1 {
+                self.duplicate_ids += 1;
+                if self.duplicates.len() < self.max_samples {
+                    self.duplicates.push(DuplicateId {
+                        id,
+                        occurrences,
+                        nodes: sources.iter().map(|&idx| nodes[idx as usize].name.clone()).collect(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn flush_tick(&mut self) {
+        if let Some((tick, count)) = self.tick.take() {
+            self.max_ids_per_ms = self.max_ids_per_ms.max(count);
+            if count >= self.ids_per_ms_limit {
+                self.saturated_ticks += 1;
+                if self.saturated_samples.len() < self.max_samples {
+                    let decoded = self.layout.decode(tick << self.layout.sequence_bits);
+                    self.saturated_samples.push(SaturatedTick {
+                        worker_id: decoded.worker_id,
+                        timestamp_ms: decoded.timestamp_ms,
+                        ids: count,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// 多路归并有序段文件
+struct Merger {
+    readers: Vec<BufReader<File>>,
+    paths: Vec<PathBuf>,
+    /// (ID, 节点序号, 段序号) 的最小堆
+    heap: BinaryHeap<Reverse<(u64, u32, usize)>>,
+}
+
+impl Merger {
+    fn open(runs: &[PathBuf]) -> Result<Self, SnowflakeError> {
+        let mut merger = Self {
+            readers: Vec::with_capacity(runs.len()),
+            paths: runs.to_vec(),
+            heap: BinaryHeap::with_capacity(runs.len()),
+        };
+        for (idx, path) in runs.iter().enumerate() {
+            let file = File::open(path).map_err(|e| io_error(path, e))?;
+            merger.readers.push(BufReader::new(file));
+            merger.advance(idx)?;
+        }
+        Ok(merger)
+    }
+
+    /// 读取第idx段的下一条记录放入堆中
+    fn advance(&mut self, idx: usize) -> Result<(), SnowflakeError> {
+        let mut buf = [0u8; RUN_RECORD_BYTES];
+        let filled = read_full(&mut self.readers[idx], &mut buf).map_err(|e| io_error(&self.paths[idx], e))?;
+        match filled {
+            0 => Ok(()),
+            RUN_RECORD_BYTES => {
+                let id = u64::from_be_bytes(buf[..8].try_into().expect("8字节"));
+                let node = u32::from_be_bytes(buf[8..].try_into().expect("4字节"));
+                self.heap.push(Reverse((id, node, idx)));
+                Ok(())
+            }
+            _ => Err(SnowflakeError::ConfigError(format!(
+                "有序段 {} 不完整",
+                self.paths[idx].display()
+            ))),
+        }
+    }
+
+    fn next_record(&mut self) -> Result<Option<(u64, u32)>, SnowflakeError> {
+        match self.heap.pop() {
+            Some(Reverse((id, node, idx))) => {
+                self.advance(idx)?;
+                Ok(Some((id, node)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+fn write_record(writer: &mut impl Write, id: u64, node: u32) -> io::Result<()> {
+    writer.write_all(&id.to_be_bytes())?;
+    writer.write_all(&node.to_be_bytes())
+}
+
+/// 尽量读满缓冲区，返回读到的字节数 (小于缓冲区长度表示到达末尾)
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+fn read_error(e: io::Error) -> SnowflakeError {
+    SnowflakeError::IoError(format!("读取ID导出失败: {}", e))
+}
+
+fn io_error(path: &Path, e: io::Error) -> SnowflakeError {
+    SnowflakeError::IoError(format!("{} 读写失败: {}", path.display(), e))
+}
@@ -0,0 +1,205 @@
+//! 生成器基准测试
+//!
+//! 运行: cargo bench --bench generators
+//! 只运行一组: cargo bench --bench generators -- contended
+
+use std::env;
+use std::fs;
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier, Mutex, Once, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use snowflake::encoding::Encoding;
+use snowflake::id128::{UlidGenerator, UuidV7Generator};
+use snowflake::segment::{SegmentAllocator, SegmentConfig};
+use snowflake::{
+    decode_id, get_next_id, init_global_worker, AtomicIdWorker, GlobalIdGenerator, IdGenerator, SnowflakeConfig,
+    SnowflakeId, SnowflakeIdWorker,
+};
+
+/// 所有雪花生成器 (包括全局生成器) 都使用16位序列号 (每毫秒65536个)
+///
+/// 默认的12位序列号每秒最多4096000个ID，单线程就能达到上限，
+/// 测到的是序列号容量而不是锁的开销。
+fn wide_layout() -> SnowflakeConfig {
+    SnowflakeConfig {
+        sequence_bits: 16,
+        ..Default::default()
+    }
+}
+
+/// 本次运行的临时目录
+/// 目录名包含进程号和启动时间，同时运行的多个基准测试不会共用号段日志
+fn bench_dir() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let dir = env::temp_dir().join(format!("snowflake-bench-{}-{}", process::id(), nanos));
+        fs::create_dir_all(&dir).expect("无法创建临时目录");
+        dir
+    })
+}
+
+/// 按 `wide_layout` 创建全局Worker
+/// 先切换到临时目录，避免当前目录下的snowflake.toml覆盖位布局
+fn init_global() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        env::set_current_dir(bench_dir()).expect("无法切换到临时目录");
+        let created = init_global_worker(wide_layout()).expect("无法创建全局Worker");
+        assert!(created, "全局Worker已经存在");
+        // 12位序列号时第15位属于Worker ID，解析出的序列号不会是1 << 15
+        assert_eq!(decode_id(1 << 15).sequence, 1 << 15, "配置文件覆盖了基准测试的位布局");
+    });
+}
+
+/// 参与对比的生成器 (都可以在线程间共享)
+fn generators() -> Vec<(&'static str, Arc<dyn IdGenerator>)> {
+    init_global();
+
+    // 每组基准测试使用新的号段日志
+    static JOURNALS: AtomicUsize = AtomicUsize::new(0);
+    let journal = bench_dir().join(format!("segments-{}.journal", JOURNALS.fetch_add(1, Ordering::Relaxed)));
+    let segments = SegmentAllocator::open(&journal, Some(SegmentConfig { step: 100_000, ..Default::default() }))
+        .expect("无法打开号段日志");
+
+    vec![
+        ("global", Arc::new(GlobalIdGenerator)),
+        ("mutex", Arc::new(Mutex::new(SnowflakeIdWorker::new(Some(wide_layout())).unwrap()))),
+        ("atomic", Arc::new(AtomicIdWorker::new(Some(wide_layout()), 1).unwrap())),
+        ("segment", Arc::new(segments.generator("bench").unwrap())),
+    ]
+}
+
+fn single_thread(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_thread");
+    group.throughput(Throughput::Elements(1));
+
+    for (name, generator) in generators() {
+        group.bench_function(name, |b| b.iter(|| generator.next_id().unwrap()));
+    }
+
+    // 独占的生成器，不经过锁
+    let mut worker = SnowflakeIdWorker::new(Some(wide_layout())).unwrap();
+    group.bench_function("worker_unlocked", |b| b.iter(|| worker.next_id().unwrap()));
+
+    let uuid_v7 = UuidV7Generator::new(None);
+    group.bench_function("uuid_v7", |b| b.iter(|| uuid_v7.next_uuid().unwrap()));
+    let ulid = UlidGenerator::new(None);
+    group.bench_function("ulid", |b| b.iter(|| ulid.next_ulid().unwrap()));
+
+    group.finish();
+}
+
+/// 多个线程同时生成: 每次迭代每个线程生成一个ID，计时包含等待锁的时间
+fn contended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contended");
+    group.measurement_time(Duration::from_secs(5));
+
+    for (name, generator) in generators() {
+        for threads in [2, 4, 8] {
+            group.throughput(Throughput::Elements(threads as u64));
+            group.bench_with_input(BenchmarkId::new(name, threads), &threads, |b, &threads| {
+                b.iter_custom(|iters| {
+                    let barrier = Barrier::new(threads + 1);
+                    thread::scope(|scope| {
+                        for _ in 0..threads {
+                            scope.spawn(|| {
+                                barrier.wait();
+                                for _ in 0..iters {
+                                    black_box(generator.next_id().unwrap());
+                                }
+                            });
+                        }
+                        barrier.wait();
+                        let start = Instant::now();
+                        // scope结束时等待所有线程完成
+                        start
+                    })
+                    .elapsed()
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// 批量预留: 一次加锁生成多个ID
+fn batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch");
+
+    for (name, generator) in generators() {
+        for count in [16, 256, 4096] {
+            group.throughput(Throughput::Elements(count as u64));
+            group.bench_with_input(BenchmarkId::new(name, count), &count, |b, &count| {
+                b.iter(|| generator.next_ids(count).unwrap())
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn encoding(c: &mut Criterion) {
+    init_global();
+    let mut group = c.benchmark_group("encoding");
+    let id = get_next_id().unwrap();
+
+    for (name, encoding) in [
+        ("base62", Encoding::Base62),
+        ("base32", Encoding::Base32),
+        ("hex", Encoding::Hex),
+        ("base32_check", Encoding::Base32Check),
+    ] {
+        group.bench_function(BenchmarkId::new("encode", name), |b| b.iter(|| encoding.encode(black_box(id))));
+        let text = encoding.encode(id);
+        group.bench_function(BenchmarkId::new("decode", name), |b| {
+            b.iter(|| encoding.decode(black_box(&text)).unwrap())
+        });
+    }
+    group.bench_function(BenchmarkId::new("encode", "decimal"), |b| b.iter(|| black_box(id).to_string()));
+    let text = id.to_string();
+    group.bench_function(BenchmarkId::new("decode", "decimal"), |b| {
+        b.iter(|| black_box(&text).parse::<u64>().unwrap())
+    });
+
+    group.finish();
+}
+
+/// 解析ID的各组成部分
+fn decomposition(c: &mut Criterion) {
+    init_global();
+    let mut group = c.benchmark_group("decomposition");
+    let config = wide_layout();
+    let ids: Vec<u64> = (0..1024).map(|_| get_next_id().unwrap()).collect();
+
+    group.throughput(Throughput::Elements(ids.len() as u64));
+    group.bench_function("layout_decode", |b| {
+        b.iter(|| ids.iter().map(|&id| config.decode(black_box(id)).timestamp_ms).sum::<u64>())
+    });
+    group.bench_function("snowflake_id_accessors", |b| {
+        b.iter(|| {
+            ids.iter()
+                .map(|&id| {
+                    let id = SnowflakeId::<()>::from(black_box(id));
+                    id.timestamp_ms() ^ id.worker_id() ^ id.sequence()
+                })
+                .sum::<u64>()
+        })
+    });
+    // 使用全局Worker的布局，每次都要加全局锁
+    group.bench_function("decode_id_global", |b| {
+        b.iter(|| ids.iter().map(|&id| decode_id(black_box(id)).timestamp_ms).sum::<u64>())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, single_thread, contended, batch, encoding, decomposition);
+criterion_main!(benches);
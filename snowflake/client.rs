@@ -0,0 +1,284 @@
+/*!
+预取ID的远程发号客户端
+
+从中心发号服务 (二进制协议，见 `proto`) 批量获取ID并缓存在本地:
+1. 本地缓冲: `next_id()` 直接从缓冲区取ID，不需要每次访问网络
+2. 异步补充: 缓冲区低于低水位时由后台线程补充，调用方不等待
+3. 多服务端容灾: 当前服务端不可用时按顺序切换到下一个服务端
+
+`next_id()` 的签名与进程内生成器一致，调用方可以在两者之间透明切换。
+*/
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use tracing::{debug, warn};
+
+use crate::proto::BinaryClient;
+use crate::SnowflakeError;
+
+/// 预取客户端配置
+#[derive(Debug, Clone)]
+pub struct PrefetchConfig {
+    /// 发号服务地址列表 (按优先级排序，例如 "10.0.0.1:8081")
+    pub endpoints: Vec<String>,
+
+    /// 每次从服务端获取的ID数量
+    pub batch_size: u32,
+
+    /// 低水位: 缓冲区剩余ID数低于该值时触发后台补充 (为0时缓冲区取空才补充)
+    pub low_water_mark: usize,
+
+    /// 网络超时 (连接、读、写)，也是缓冲区为空时 `next_id()` 的最长等待时间
+    pub timeout: Duration,
+
+    /// 所有服务端都不可用时，后台线程重试前的等待时间
+    pub retry_interval: Duration,
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),                      // 必须由调用方指定
+            batch_size: 1000,                           // 每批1000个ID
+            low_water_mark: 200,                        // 剩余不足200个时补充
+            timeout: Duration::from_secs(1),            // 1秒网络超时
+            retry_interval: Duration::from_millis(100), // 全部失败后100毫秒重试
+        }
+    }
+}
+
+impl PrefetchConfig {
+    /// 缓冲区剩余ID数低于该值时需要补充
+    /// 低水位为0时按1处理，否则缓冲区取空后后台线程也不会补充
+    fn refill_below(&self) -> usize {
+        self.low_water_mark.max(1)
+    }
+}
+
+/// 客户端与后台补充线程共享的状态
+#[derive(Debug, Default)]
+struct State {
+    /// 预取的ID
+    buffer: VecDeque<u64>,
+    /// 最近一次补充失败的错误 (补充成功后清除)
+    last_error: Option<SnowflakeError>,
+    /// 补充失败的次数，等待中的调用方据此判断是否出现了新的失败
+    failures: u64,
+    /// 客户端已关闭
+    shutdown: bool,
+}
+
+/// 共享状态 + 两个条件变量
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    /// 通知后台线程需要补充
+    refill_needed: Condvar,
+    /// 通知调用方补充完成 (或失败)
+    refilled: Condvar,
+}
+
+/// 预取ID的远程发号客户端 (线程安全，可在多个线程间共享)
+#[derive(Debug)]
+pub struct PrefetchClient {
+    shared: Arc<Shared>,
+    config: PrefetchConfig,
+    /// 后台补充线程
+    refiller: Option<JoinHandle<()>>,
+}
+
+impl PrefetchClient {
+    /// 创建客户端并立即开始后台预取
+    ///
+    /// 返回:
+    /// - Ok(PrefetchClient): 创建成功 (服务端是否可用在第一次取ID时才能确定)
+    /// - Err(SnowflakeError::ConfigError): 配置不合法
+    pub fn new(config: PrefetchConfig) -> Result<Self, SnowflakeError> {
+        if config.endpoints.is_empty() {
+            return Err(SnowflakeError::ConfigError("至少需要一个发号服务地址".to_string()));
+        }
+        if config.batch_size == 0 {
+            return Err(SnowflakeError::ConfigError("batch_size必须大于0".to_string()));
+        }
+        if config.low_water_mark >= config.batch_size as usize {
+            return Err(SnowflakeError::ConfigError(format!(
+                "low_water_mark({})必须小于batch_size({})",
+                config.low_water_mark, config.batch_size
+            )));
+        }
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            refill_needed: Condvar::new(),
+            refilled: Condvar::new(),
+        });
+
+        let refiller = Refiller {
+            shared: Arc::clone(&shared),
+            config: config.clone(),
+            conn: None,
+            current: 0,
+        };
+        let handle = thread::Builder::new()
+            .name("snowflake-prefetch".to_string())
+            .spawn(move || refiller.run())
+            .map_err(|e| SnowflakeError::ConfigError(format!("无法启动预取线程: {}", e)))?;
+
+        Ok(Self {
+            shared,
+            config,
+            refiller: Some(handle),
+        })
+    }
+
+    /// 获取下一个ID
+    ///
+    /// 缓冲区有ID时立即返回；缓冲区为空时等待后台补充，
+    /// 补充失败或等待超过 `timeout` 时返回错误
+    pub fn next_id(&self) -> Result<u64, SnowflakeError> {
+        let mut state = self.shared.state.lock().unwrap();
+        let failures = state.failures;
+        let deadline = Instant::now() + self.config.timeout;
+
+        loop {
+            if let Some(id) = state.buffer.pop_front() {
+                if state.buffer.len() < self.config.refill_below() {
+                    self.shared.refill_needed.notify_one();
+                }
+                return Ok(id);
+            }
+
+            // 缓冲区为空，且在等待期间出现了新的补充失败
+            if state.failures != failures {
+                if let Some(e) = &state.last_error {
+                    return Err(e.clone());
+                }
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(SnowflakeError::NetworkError(format!(
+                    "等待预取ID超时({:?})",
+                    self.config.timeout
+                )));
+            }
+
+            self.shared.refill_needed.notify_one();
+            state = self.shared.refilled.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+
+    /// 当前缓冲区中剩余的ID数
+    pub fn buffered(&self) -> usize {
+        self.shared.state.lock().unwrap().buffer.len()
+    }
+}
+
+impl Drop for PrefetchClient {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.refill_needed.notify_all();
+        if let Some(handle) = self.refiller.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 后台补充线程
+struct Refiller {
+    shared: Arc<Shared>,
+    config: PrefetchConfig,
+    /// 当前服务端的连接 (出错后丢弃，下次重连)
+    conn: Option<BinaryClient>,
+    /// 当前使用的服务端下标
+    current: usize,
+}
+
+impl Refiller {
+    /// 补充循环: 缓冲区低于低水位时补充，直到客户端关闭
+    fn run(mut self) {
+        loop {
+            {
+                let mut state = self.shared.state.lock().unwrap();
+                while !state.shutdown && state.buffer.len() >= self.config.refill_below() {
+                    state = self.shared.refill_needed.wait(state).unwrap();
+                }
+                if state.shutdown {
+                    return;
+                }
+            }
+
+            // 网络请求不持有锁，调用方可以继续消费缓冲区中剩余的ID
+            let result = self.fetch();
+
+            let mut state = self.shared.state.lock().unwrap();
+            match result {
+                Ok(ids) => {
+                    state.buffer.extend(ids);
+                    state.last_error = None;
+                    self.shared.refilled.notify_all();
+                }
+                Err(e) => {
+                    state.last_error = Some(e);
+                    state.failures += 1;
+                    self.shared.refilled.notify_all();
+                    // 所有服务端都不可用，等待一段时间再重试
+                    // 期间忽略补充通知，避免调用方频繁触发重试；关闭时立即退出
+                    let deadline = Instant::now() + self.config.retry_interval;
+                    while !state.shutdown {
+                        let now = Instant::now();
+                        if now >= deadline {
+                            break;
+                        }
+                        state = self.shared.refill_needed.wait_timeout(state, deadline - now).unwrap().0;
+                    }
+                    if state.shutdown {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 从服务端获取一批ID
+    /// 当前服务端失败时依次尝试其余服务端，全部失败时返回最后一个错误
+    fn fetch(&mut self) -> Result<Vec<u64>, SnowflakeError> {
+        let mut last_err = None;
+
+        for _ in 0..self.config.endpoints.len() {
+            let endpoint = &self.config.endpoints[self.current];
+
+            if self.conn.is_none() {
+                match BinaryClient::connect_timeout(endpoint.as_str(), self.config.timeout) {
+                    Ok(conn) => {
+                        debug!(endpoint = %endpoint, "已连接发号服务");
+                        self.conn = Some(conn);
+                    }
+                    Err(e) => {
+                        warn!(endpoint = %endpoint, error = %e, "连接发号服务失败，切换到下一个服务端");
+                        last_err = Some(e);
+                        self.current = (self.current + 1) % self.config.endpoints.len();
+                        continue;
+                    }
+                }
+            }
+
+            let conn = self.conn.as_mut().expect("连接已建立");
+            match conn.next_ids(self.config.batch_size) {
+                Ok(ids) => return Ok(ids),
+                Err(e) => {
+                    // 网络错误和服务端暂时性错误 (时钟回拨等) 都切换服务端
+                    warn!(endpoint = %endpoint, error = %e, "获取ID失败，切换到下一个服务端");
+                    last_err = Some(e);
+                    self.conn = None;
+                    self.current = (self.current + 1) % self.config.endpoints.len();
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| SnowflakeError::NetworkError("没有可用的发号服务".to_string())))
+    }
+}
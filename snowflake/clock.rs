@@ -0,0 +1,75 @@
+/*!
+时钟抽象
+
+所有基于时间的生成器 (雪花ID、UUIDv7、ULID) 都通过 `Clock` 读取时间，
+并共用同一套时钟回拨处理逻辑:
+- 回拨幅度在容忍范围内: 等待系统时钟追上上次的时间戳
+- 超出容忍范围: 返回 `SnowflakeError::ClockBackward`
+
+测试中可以注入自定义时钟来模拟时钟回拨、跳变等情况。
+*/
+
+use std::fmt;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::{error, warn};
+
+use crate::SnowflakeError;
+
+/// 时钟
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// 当前时间 (Unix毫秒时间戳)
+    fn now_ms(&self) -> Result<u64, SnowflakeError>;
+
+    /// 休眠指定时长 (等待时钟回拨恢复、Sleep策略等待下一毫秒时调用)
+    /// 模拟时钟可以覆盖该方法，直接推进时间而不真正休眠
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// 系统时钟
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> Result<u64, SnowflakeError> {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .map_err(|e| SnowflakeError::ClockBackward(format!("系统时钟错误: {}", e)))
+    }
+}
+
+/// 处理时钟回拨
+///
+/// now_ms小于last_ms时，回拨幅度在max_backward_ms以内则休眠等待时钟追上，
+/// 否则返回ClockBackward错误。每检测到一次回拨调用一次on_backward(回拨毫秒数, 是否拒绝)。
+///
+/// 返回不小于last_ms的当前时间
+pub(crate) fn wait_out_backward(
+    clock: &dyn Clock,
+    mut now_ms: u64,
+    last_ms: u64,
+    max_backward_ms: u64,
+    mut on_backward: impl FnMut(u64, bool),
+) -> Result<u64, SnowflakeError> {
+    // 等待期间时钟可能再次回拨，直到追上为止
+    while now_ms < last_ms {
+        let diff = last_ms - now_ms;
+        let rejected = diff > max_backward_ms;
+        on_backward(diff, rejected);
+        if rejected {
+            error!(diff_ms = diff, max_backward_ms, "时钟回拨超出容忍范围");
+            return Err(SnowflakeError::ClockBackward(format!(
+                "时钟回拨过大: {}ms，超出容忍范围{}ms",
+                diff, max_backward_ms
+            )));
+        }
+        warn!(diff_ms = diff, max_backward_ms, "检测到时钟回拨，等待中");
+        clock.sleep(Duration::from_millis(diff + 1));
+        now_ms = clock.now_ms()?;
+    }
+    Ok(now_ms)
+}
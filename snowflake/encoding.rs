@@ -0,0 +1,281 @@
+/*!
+ID的字符串编码
+
+十进制的雪花ID长达19位，放在URL里既长又容易让人看出发号量。这里提供三种更紧凑的编码:
+- base62:          11个字符，字母表 `0-9A-Za-z`
+- Crockford base32: 13个字符，字母表 `0-9A-Z` 去掉 I L O U，解码时不区分大小写
+- hex:             16个字符，小写十六进制
+- base32 + 校验位:  14个字符，Crockford base32后追加一个Luhn mod 32校验字符，用于人工录入
+
+所有编码都是定长的 (高位补字母表的第一个字符)，字母表按ASCII顺序排列，
+因此编码后字符串的字典序与ID的数值顺序一致，可以直接用于排序或范围查询。
+*/
+
+use std::fmt;
+
+/// base62字母表 (ASCII顺序: 数字 < 大写字母 < 小写字母)
+const BASE62_ALPHABET: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Crockford base32字母表 (去掉容易混淆的 I L O U)
+const BASE32_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// 十六进制字母表
+const HEX_ALPHABET: &[u8; 16] = b"0123456789abcdef";
+
+/// base62编码长度: 62^11 > 2^64
+pub const BASE62_WIDTH: usize = 11;
+
+/// Crockford base32编码长度: 13 * 5 = 65位
+pub const BASE32_WIDTH: usize = 13;
+
+/// 十六进制编码长度
+pub const HEX_WIDTH: usize = 16;
+
+/// 带校验位的Crockford base32编码长度
+pub const BASE32_CHECK_WIDTH: usize = BASE32_WIDTH + 1;
+
+/// 解码错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// 长度与编码的固定长度不一致
+    InvalidLength { expected: usize, actual: usize },
+    /// 字符不在字母表中 (position为字符下标，从0开始)
+    InvalidChar { ch: char, position: usize },
+    /// 数值超过u64范围
+    Overflow,
+    /// 校验位不匹配 (通常是录入时输错或调换了字符)
+    CheckDigitMismatch { expected: char, actual: char },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidLength { expected, actual } => {
+                write!(f, "长度错误: 需要{}个字符，实际{}个", expected, actual)
+            }
+            DecodeError::InvalidChar { ch, position } => {
+                write!(f, "第{}个字符 {:?} 不合法", position + 1, ch)
+            }
+            DecodeError::Overflow => write!(f, "数值超过u64范围"),
+            DecodeError::CheckDigitMismatch { expected, actual } => {
+                write!(f, "校验位错误: 应为 {:?}，实际为 {:?}，请检查是否输错", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// ID编码方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// base62 (11个字符)
+    Base62,
+    /// Crockford base32 (13个字符)
+    Base32,
+    /// 小写十六进制 (16个字符)
+    Hex,
+    /// Crockford base32 + 校验位 (14个字符)
+    Base32Check,
+}
+
+impl Encoding {
+    /// 编码后的固定长度
+    pub fn width(self) -> usize {
+        match self {
+            Encoding::Base62 => BASE62_WIDTH,
+            Encoding::Base32 => BASE32_WIDTH,
+            Encoding::Hex => HEX_WIDTH,
+            Encoding::Base32Check => BASE32_CHECK_WIDTH,
+        }
+    }
+
+    /// 编码ID
+    pub fn encode(self, id: u64) -> String {
+        match self {
+            Encoding::Base62 => encode_base62(id),
+            Encoding::Base32 => encode_base32(id),
+            Encoding::Hex => encode_hex(id),
+            Encoding::Base32Check => encode_base32_check(id),
+        }
+    }
+
+    /// 解码ID
+    pub fn decode(self, s: &str) -> Result<u64, DecodeError> {
+        match self {
+            Encoding::Base62 => decode_base62(s),
+            Encoding::Base32 => decode_base32(s),
+            Encoding::Hex => decode_hex(s),
+            Encoding::Base32Check => decode_base32_check(s),
+        }
+    }
+}
+
+// ============================================================================
+// 编码
+// ============================================================================
+
+/// 按字母表定长编码 (从低位到高位填充，高位补第一个字符)
+fn encode_fixed(mut id: u64, alphabet: &[u8], width: usize) -> String {
+    let base = alphabet.len() as u64;
+    let mut buf = vec![alphabet[0]; width];
+    for slot in buf.iter_mut().rev() {
+        *slot = alphabet[(id % base) as usize];
+        id /= base;
+    }
+    // 字母表都是ASCII字符
+    String::from_utf8(buf).expect("字母表为ASCII")
+}
+
+/// base62编码
+pub fn encode_base62(id: u64) -> String {
+    encode_fixed(id, BASE62_ALPHABET, BASE62_WIDTH)
+}
+
+/// Crockford base32编码 (大写)
+pub fn encode_base32(id: u64) -> String {
+    encode_fixed(id, BASE32_ALPHABET, BASE32_WIDTH)
+}
+
+/// 十六进制编码 (小写)
+pub fn encode_hex(id: u64) -> String {
+    encode_fixed(id, HEX_ALPHABET, HEX_WIDTH)
+}
+
+// ============================================================================
+// 解码
+// ============================================================================
+
+/// 按字母表定长解码
+/// digit把字符映射为数值，不在字母表中时返回None
+fn decode_fixed(
+    s: &str,
+    base: u64,
+    width: usize,
+    digit: impl Fn(char) -> Option<u64>,
+) -> Result<u64, DecodeError> {
+    let actual = s.chars().count();
+    if actual != width {
+        return Err(DecodeError::InvalidLength { expected: width, actual });
+    }
+
+    let mut value: u64 = 0;
+    for (position, ch) in s.chars().enumerate() {
+        let d = digit(ch).ok_or(DecodeError::InvalidChar { ch, position })?;
+        value = value
+            .checked_mul(base)
+            .and_then(|v| v.checked_add(d))
+            .ok_or(DecodeError::Overflow)?;
+    }
+    Ok(value)
+}
+
+/// base62解码 (区分大小写)
+pub fn decode_base62(s: &str) -> Result<u64, DecodeError> {
+    decode_fixed(s, 62, BASE62_WIDTH, base62_digit)
+}
+
+/// base62字符对应的数值
+pub(crate) fn base62_digit(ch: char) -> Option<u64> {
+    match ch {
+        '0'..='9' => Some(ch as u64 - '0' as u64),
+        'A'..='Z' => Some(ch as u64 - 'A' as u64 + 10),
+        'a'..='z' => Some(ch as u64 - 'a' as u64 + 36),
+        _ => None,
+    }
+}
+
+/// 数值对应的base62字符 (digit必须小于62)
+pub(crate) fn base62_char(digit: u8) -> char {
+    BASE62_ALPHABET[digit as usize] as char
+}
+
+/// Crockford base32字符对应的数值
+/// 不区分大小写，按Crockford规范把 I/L 视为1、O 视为0；U 不在字母表中
+pub(crate) fn base32_digit(ch: char) -> Option<u64> {
+    let ch = ch.to_ascii_uppercase();
+    let ch = match ch {
+        'I' | 'L' => '1',
+        'O' => '0',
+        _ => ch,
+    };
+    BASE32_ALPHABET
+        .iter()
+        .position(|&c| c as char == ch)
+        .map(|d| d as u64)
+}
+
+/// 数值对应的Crockford base32字符 (digit必须小于32)
+pub(crate) fn base32_char(digit: u8) -> char {
+    BASE32_ALPHABET[digit as usize] as char
+}
+
+/// Crockford base32解码
+pub fn decode_base32(s: &str) -> Result<u64, DecodeError> {
+    decode_fixed(s, 32, BASE32_WIDTH, base32_digit)
+}
+
+/// 十六进制解码 (不区分大小写)
+pub fn decode_hex(s: &str) -> Result<u64, DecodeError> {
+    decode_fixed(s, 16, HEX_WIDTH, |ch| ch.to_digit(16).map(u64::from))
+}
+
+// ============================================================================
+// 校验位
+// ============================================================================
+
+/// Luhn mod 32校验位 (数值)
+///
+/// 从右往左每隔一位乘2，乘积按32进制拆成两位相加。
+/// 能发现所有单字符错误和绝大多数相邻字符调换。
+fn luhn32_check_digit(digits: &[u64]) -> u64 {
+    let sum: u64 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            let addend = if i % 2 == 0 { d * 2 } else { d };
+            addend / 32 + addend % 32
+        })
+        .sum();
+    (32 - sum % 32) % 32
+}
+
+/// 带校验位的Crockford base32编码
+pub fn encode_base32_check(id: u64) -> String {
+    let mut encoded = encode_base32(id);
+    let digits: Vec<u64> = encoded.chars().filter_map(base32_digit).collect();
+    encoded.push(BASE32_ALPHABET[luhn32_check_digit(&digits) as usize] as char);
+    encoded
+}
+
+/// 带校验位的Crockford base32解码
+/// 与 `decode_base32` 一样不区分大小写；校验位不匹配时返回 `DecodeError::CheckDigitMismatch`
+pub fn decode_base32_check(s: &str) -> Result<u64, DecodeError> {
+    let actual = s.chars().count();
+    if actual != BASE32_CHECK_WIDTH {
+        return Err(DecodeError::InvalidLength { expected: BASE32_CHECK_WIDTH, actual });
+    }
+
+    let mut digits = Vec::with_capacity(BASE32_CHECK_WIDTH);
+    for (position, ch) in s.chars().enumerate() {
+        digits.push(base32_digit(ch).ok_or(DecodeError::InvalidChar { ch, position })?);
+    }
+
+    // 先校验再计算数值，输错的ID报告为校验位错误而不是溢出
+    let (check, payload) = digits.split_last().expect("长度已校验");
+    let expected = luhn32_check_digit(payload);
+    if *check != expected {
+        return Err(DecodeError::CheckDigitMismatch {
+            expected: BASE32_ALPHABET[expected as usize] as char,
+            actual: s.chars().last().expect("长度已校验").to_ascii_uppercase(),
+        });
+    }
+
+    payload.iter().try_fold(0u64, |value, &d| {
+        value
+            .checked_mul(32)
+            .and_then(|v| v.checked_add(d))
+            .ok_or(DecodeError::Overflow)
+    })
+}
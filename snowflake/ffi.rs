@@ -0,0 +1,359 @@
+/*!
+C语言接口 (FFI)
+
+编译为 `cdylib`/`staticlib` 后，C/C++/Go等语言可以通过 `include/snowflake.h` 调用全局生成器。
+头文件由cbindgen根据本文件生成 (配置见 `cbindgen.toml`)，修改接口后运行
+`SNOWFLAKE_UPDATE_HEADER=1 cargo test --test ffi` 重新生成。
+
+- 所有函数返回 `SnowflakeStatus`，结果通过输出指针返回；失败时可以用
+  `snowflake_last_error` 取得当前线程最近一次失败的错误信息
+- `snowflake_init` 可选: 不调用时第一次生成ID按默认配置创建全局Worker
+- Rust代码中的panic不会跨越FFI边界，统一返回 `SNOWFLAKE_STATUS_PANIC`
+- 所有函数都可以在多个线程中同时调用
+*/
+
+use std::cell::RefCell;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::{
+    get_next_id, get_next_ids, get_worker_info, global_config, init_global_worker, SequenceExhaustedPolicy,
+    SequenceStart, SnowflakeConfig, SnowflakeError,
+};
+
+/// 序列号用尽时自旋等待下一毫秒
+pub const SNOWFLAKE_POLICY_SPIN: u32 = 0;
+/// 序列号用尽时休眠等待下一毫秒
+pub const SNOWFLAKE_POLICY_SLEEP: u32 = 1;
+/// 序列号用尽时返回 `SNOWFLAKE_STATUS_SEQUENCE_EXHAUSTED`
+pub const SNOWFLAKE_POLICY_ERROR: u32 = 2;
+/// 序列号用尽时借用未来毫秒 (最多领先 `borrow_ahead_ms`)
+pub const SNOWFLAKE_POLICY_BORROW: u32 = 3;
+
+/// 每毫秒序列号从0开始
+pub const SNOWFLAKE_SEQUENCE_START_ZERO: u32 = 0;
+/// 每毫秒序列号从随机值开始
+pub const SNOWFLAKE_SEQUENCE_START_RANDOM: u32 = 1;
+/// 每毫秒序列号起始值轮转
+pub const SNOWFLAKE_SEQUENCE_START_ROTATING: u32 = 2;
+
+/// 返回码
+/// 1-6和10与 `SnowflakeError` 的各个变体一一对应
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnowflakeStatus {
+    /// 成功
+    Ok = 0,
+    /// 时钟回拨超出容忍范围 (`SnowflakeError::ClockBackward`)
+    ClockBackward = 1,
+    /// 配置错误 (`SnowflakeError::ConfigError`)
+    ConfigError = 2,
+    /// 网络错误 (`SnowflakeError::NetworkError`)
+    NetworkError = 3,
+    /// 序列号用尽 (`SnowflakeError::SequenceExhausted`)
+    SequenceExhausted = 4,
+    /// 协议错误 (`SnowflakeError::ProtocolError`)
+    ProtocolError = 5,
+    /// 时间超出范围 (`SnowflakeError::TimeOutOfRange`)
+    TimeOutOfRange = 6,
+    /// 参数无效 (空指针、未知的策略编号等)
+    InvalidArgument = 7,
+    /// 全局Worker已经存在，`snowflake_init` 的配置未生效
+    AlreadyInitialized = 8,
+    /// Rust代码发生panic (内部错误)
+    Panic = 9,
+    /// IO错误 (`SnowflakeError::IoError`)
+    IoError = 10,
+}
+
+impl From<&SnowflakeError> for SnowflakeStatus {
+    fn from(err: &SnowflakeError) -> Self {
+        match err {
+            SnowflakeError::ClockBackward(_) => SnowflakeStatus::ClockBackward,
+            SnowflakeError::ConfigError(_) => SnowflakeStatus::ConfigError,
+            SnowflakeError::NetworkError(_) => SnowflakeStatus::NetworkError,
+            SnowflakeError::SequenceExhausted(_) => SnowflakeStatus::SequenceExhausted,
+            SnowflakeError::ProtocolError(_) => SnowflakeStatus::ProtocolError,
+            SnowflakeError::TimeOutOfRange(_) => SnowflakeStatus::TimeOutOfRange,
+            SnowflakeError::IoError(_) => SnowflakeStatus::IoError,
+        }
+    }
+}
+
+/// 全局生成器配置 (对应 `SnowflakeConfig`)
+/// 先用 `snowflake_config_default` 填充默认值，再修改需要的字段
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnowflakeInitConfig {
+    /// Worker ID位数 (1-8)
+    pub worker_id_bits: u8,
+    /// 序列号位数 (至少1位，与Worker ID位数之和小于63)
+    pub sequence_bits: u8,
+    /// 时钟回拨容忍度 (毫秒)
+    pub max_backward_ms: u64,
+    /// 序列号用尽策略 (`SNOWFLAKE_POLICY_*`)
+    pub exhausted_policy: u32,
+    /// Borrow策略最多领先系统时钟的毫秒数 (其他策略忽略)
+    pub borrow_ahead_ms: u64,
+    /// 每毫秒序列号的起始值 (`SNOWFLAKE_SEQUENCE_START_*`)
+    pub sequence_start: u32,
+}
+
+impl From<&SnowflakeConfig> for SnowflakeInitConfig {
+    fn from(config: &SnowflakeConfig) -> Self {
+        let (exhausted_policy, borrow_ahead_ms) = match config.sequence_exhausted_policy {
+            SequenceExhaustedPolicy::Spin => (SNOWFLAKE_POLICY_SPIN, 0),
+            SequenceExhaustedPolicy::Sleep => (SNOWFLAKE_POLICY_SLEEP, 0),
+            SequenceExhaustedPolicy::Error => (SNOWFLAKE_POLICY_ERROR, 0),
+            SequenceExhaustedPolicy::Borrow(ms) => (SNOWFLAKE_POLICY_BORROW, ms),
+        };
+        let sequence_start = match config.sequence_start {
+            SequenceStart::Zero => SNOWFLAKE_SEQUENCE_START_ZERO,
+            SequenceStart::Random => SNOWFLAKE_SEQUENCE_START_RANDOM,
+            SequenceStart::Rotating => SNOWFLAKE_SEQUENCE_START_ROTATING,
+        };
+        Self {
+            worker_id_bits: config.worker_id_bits,
+            sequence_bits: config.sequence_bits,
+            max_backward_ms: config.max_backward_ms,
+            exhausted_policy,
+            borrow_ahead_ms,
+            sequence_start,
+        }
+    }
+}
+
+/// 把C配置转换为 `SnowflakeConfig`，检查策略编号和位布局
+fn parse_init_config(config: &SnowflakeInitConfig) -> Result<SnowflakeConfig, Failure> {
+    let sequence_exhausted_policy = match config.exhausted_policy {
+        SNOWFLAKE_POLICY_SPIN => SequenceExhaustedPolicy::Spin,
+        SNOWFLAKE_POLICY_SLEEP => SequenceExhaustedPolicy::Sleep,
+        SNOWFLAKE_POLICY_ERROR => SequenceExhaustedPolicy::Error,
+        SNOWFLAKE_POLICY_BORROW => SequenceExhaustedPolicy::Borrow(config.borrow_ahead_ms),
+        other => return Err(Failure::invalid(format!("未知的序列号用尽策略: {}", other))),
+    };
+    let sequence_start = match config.sequence_start {
+        SNOWFLAKE_SEQUENCE_START_ZERO => SequenceStart::Zero,
+        SNOWFLAKE_SEQUENCE_START_RANDOM => SequenceStart::Random,
+        SNOWFLAKE_SEQUENCE_START_ROTATING => SequenceStart::Rotating,
+        other => return Err(Failure::invalid(format!("未知的序列号起始值方式: {}", other))),
+    };
+    let config = SnowflakeConfig {
+        worker_id_bits: config.worker_id_bits,
+        sequence_bits: config.sequence_bits,
+        max_backward_ms: config.max_backward_ms,
+        sequence_exhausted_policy,
+        sequence_start,
+    };
+    config.check_layout()?;
+    Ok(config)
+}
+
+/// 解析后的ID各组成部分 (对应 `DecodedId`)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnowflakeDecodedId {
+    /// 原始ID
+    pub id: u64,
+    /// 生成时间 (Unix毫秒时间戳)
+    pub timestamp_ms: u64,
+    /// 生成ID的Worker ID
+    pub worker_id: u64,
+    /// 同一毫秒内的序列号
+    pub sequence: u64,
+}
+
+// ============================================================================
+// 错误处理
+// ============================================================================
+
+/// 失败的返回码和错误信息
+#[derive(Debug)]
+struct Failure {
+    status: SnowflakeStatus,
+    message: String,
+}
+
+impl Failure {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self {
+            status: SnowflakeStatus::InvalidArgument,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<SnowflakeError> for Failure {
+    fn from(err: SnowflakeError) -> Self {
+        Self {
+            status: SnowflakeStatus::from(&err),
+            message: err.to_string(),
+        }
+    }
+}
+
+thread_local! {
+    /// 当前线程最近一次失败的错误信息
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// 执行一次FFI调用: 捕获panic，失败时记录错误信息并转换为返回码
+fn ffi_call(f: impl FnOnce() -> Result<(), Failure>) -> SnowflakeStatus {
+    let failure = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return SnowflakeStatus::Ok,
+        Ok(Err(failure)) => failure,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "未知panic".to_string());
+            Failure {
+                status: SnowflakeStatus::Panic,
+                message: format!("内部错误: {}", message),
+            }
+        }
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = failure.message);
+    failure.status
+}
+
+/// 检查输出指针不为空
+fn non_null<T>(ptr: *mut T, name: &str) -> Result<*mut T, Failure> {
+    if ptr.is_null() {
+        Err(Failure::invalid(format!("{}不能为空指针", name)))
+    } else {
+        Ok(ptr)
+    }
+}
+
+// ============================================================================
+// 导出函数
+// ============================================================================
+
+/// 用默认配置填充 `config`
+///
+/// # Safety
+/// `config` 必须为空指针或指向可写的 `SnowflakeInitConfig`
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_config_default(config: *mut SnowflakeInitConfig) -> SnowflakeStatus {
+    ffi_call(|| {
+        let config = non_null(config, "config")?;
+        *config = SnowflakeInitConfig::from(&SnowflakeConfig::default());
+        Ok(())
+    })
+}
+
+/// 使用指定配置创建全局Worker
+///
+/// 必须在第一次生成ID之前调用；`config` 为空指针时使用默认配置。
+/// Worker ID仍按环境变量、租约目录、snowflake.toml、IP的优先级分配，
+/// snowflake.toml中出现的配置项 (如 `max_backward_ms`) 覆盖 `config` 中的对应字段。
+/// 全局Worker已经存在时返回 `SNOWFLAKE_STATUS_ALREADY_INITIALIZED`，配置不生效。
+///
+/// # Safety
+/// `config` 必须为空指针或指向有效的 `SnowflakeInitConfig`
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_init(config: *const SnowflakeInitConfig) -> SnowflakeStatus {
+    ffi_call(|| {
+        let config = match config.as_ref() {
+            Some(config) => parse_init_config(config)?,
+            None => SnowflakeConfig::default(),
+        };
+        if init_global_worker(config)? {
+            Ok(())
+        } else {
+            Err(Failure {
+                status: SnowflakeStatus::AlreadyInitialized,
+                message: "全局Worker已经存在，配置未生效".to_string(),
+            })
+        }
+    })
+}
+
+/// 生成下一个ID，写入 `out`
+///
+/// # Safety
+/// `out` 必须为空指针或指向可写的 `uint64_t`
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_next_id(out: *mut u64) -> SnowflakeStatus {
+    ffi_call(|| {
+        let out = non_null(out, "out")?;
+        *out = get_next_id()?;
+        Ok(())
+    })
+}
+
+/// 批量生成 `count` 个ID，按生成顺序写入 `out`
+/// 失败时 `out` 的内容未定义
+///
+/// # Safety
+/// `out` 必须指向至少 `count` 个可写的 `uint64_t` (`count` 为0时可以为空指针)
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_next_ids(out: *mut u64, count: usize) -> SnowflakeStatus {
+    ffi_call(|| {
+        if count == 0 {
+            return Ok(());
+        }
+        let out = non_null(out, "out")?;
+        let ids = get_next_ids(count)?;
+        ptr::copy_nonoverlapping(ids.as_ptr(), out, count);
+        Ok(())
+    })
+}
+
+/// 按全局生成器的位布局解析ID，写入 `out`
+/// 不会触发全局Worker的创建
+///
+/// # Safety
+/// `out` 必须为空指针或指向可写的 `SnowflakeDecodedId`
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_decode(id: u64, out: *mut SnowflakeDecodedId) -> SnowflakeStatus {
+    ffi_call(|| {
+        let out = non_null(out, "out")?;
+        let decoded = global_config().decode(id);
+        *out = SnowflakeDecodedId {
+            id: decoded.id,
+            timestamp_ms: decoded.timestamp_ms,
+            worker_id: decoded.worker_id,
+            sequence: decoded.sequence,
+        };
+        Ok(())
+    })
+}
+
+/// 获取全局Worker的Worker ID，写入 `out`
+/// 全局Worker尚未创建时按当前配置创建
+///
+/// # Safety
+/// `out` 必须为空指针或指向可写的 `uint8_t`
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_worker_id(out: *mut u8) -> SnowflakeStatus {
+    ffi_call(|| {
+        let out = non_null(out, "out")?;
+        *out = get_worker_info().0;
+        Ok(())
+    })
+}
+
+/// 复制当前线程最近一次失败的错误信息 (UTF-8) 到 `buf`
+///
+/// 与 `snprintf` 相同: 最多写入 `len - 1` 个字节并以 `'\0'` 结尾，
+/// 返回完整信息的字节数 (不含结尾的 `'\0'`)；返回值不小于 `len` 表示被截断。
+/// 没有失败记录时返回0。`buf` 为空指针或 `len` 为0时只返回长度。
+///
+/// # Safety
+/// `buf` 必须为空指针或指向至少 `len` 个可写的字节
+#[no_mangle]
+pub unsafe extern "C" fn snowflake_last_error(buf: *mut c_char, len: usize) -> usize {
+    LAST_ERROR.with(|last| {
+        let message = last.borrow();
+        if !buf.is_null() && len > 0 {
+            let copied = message.len().min(len - 1);
+            ptr::copy_nonoverlapping(message.as_ptr(), buf as *mut u8, copied);
+            *buf.add(copied) = 0;
+        }
+        message.len()
+    })
+}
@@ -0,0 +1,208 @@
+/*!
+ID生成器通用接口
+
+业务代码依赖 `IdGenerator` 而不是具体的 `get_next_id()`，就可以注入任意生成器:
+- `GlobalIdGenerator`: 全局锁生成器 (与 `get_next_id()` 相同)
+- `Mutex<SnowflakeIdWorker>`: 独立的雪花算法生成器
+- `AtomicIdWorker`: 无锁雪花算法生成器 (CAS)
+- `PrefetchClient` / `Mutex<BinaryClient>`: 远程发号服务
+- `SegmentIdGenerator`: 号段模式
+- `SequentialIdGenerator`: 测试用的确定性顺序生成器
+
+异步代码使用 `AsyncIdGenerator`。同步生成器可能阻塞 (等待下一毫秒、时钟回拨、
+预取客户端的网络请求)，需要用 `BlockingIdGenerator` (`tokio` feature) 包装后
+在阻塞线程池中调用；不会阻塞的 `SequentialIdGenerator` 直接实现了该接口。
+*/
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+#[cfg(feature = "tokio")]
+use std::sync::Arc;
+
+use crate::client::PrefetchClient;
+use crate::proto::BinaryClient;
+use crate::segment::SegmentIdGenerator;
+use crate::{get_next_id, get_next_ids, SnowflakeError, SnowflakeIdWorker};
+
+/// ID生成器 (同步)
+pub trait IdGenerator: Send + Sync {
+    /// 生成下一个ID
+    fn next_id(&self) -> Result<u64, SnowflakeError>;
+
+    /// 批量生成ID
+    /// 默认逐个调用 `next_id`，支持批量的生成器可以覆盖以减少加锁或网络往返
+    fn next_ids(&self, count: usize) -> Result<Vec<u64>, SnowflakeError> {
+        (0..count).map(|_| self.next_id()).collect()
+    }
+}
+
+/// ID生成器 (异步)
+///
+/// 实现不能阻塞异步运行时的工作线程。同步生成器用 `BlockingIdGenerator` 包装后使用。
+pub trait AsyncIdGenerator: Send + Sync {
+    /// 生成下一个ID
+    fn next_id(&self) -> impl Future<Output = Result<u64, SnowflakeError>> + Send;
+
+    /// 批量生成ID
+    fn next_ids(&self, count: usize) -> impl Future<Output = Result<Vec<u64>, SnowflakeError>> + Send;
+}
+
+/// 在tokio阻塞线程池中调用同步生成器的异步适配器
+///
+/// 等待下一毫秒、时钟回拨等待、预取客户端的网络请求都在阻塞线程中进行，不会占住异步工作线程。
+/// 必须在tokio运行时中调用。
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct BlockingIdGenerator<G: ?Sized> {
+    inner: Arc<G>,
+}
+
+#[cfg(feature = "tokio")]
+impl<G: IdGenerator + 'static> BlockingIdGenerator<G> {
+    /// 包装同步生成器
+    pub fn new(generator: G) -> Self {
+        Self {
+            inner: Arc::new(generator),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<G: IdGenerator + ?Sized + 'static> BlockingIdGenerator<G> {
+    /// 包装已共享的同步生成器 (同时在同步代码中使用)
+    pub fn from_arc(generator: Arc<G>) -> Self {
+        Self { inner: generator }
+    }
+
+    /// 被包装的同步生成器
+    pub fn inner(&self) -> &Arc<G> {
+        &self.inner
+    }
+
+    /// 在阻塞线程池中执行
+    async fn run<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&G) -> Result<T, SnowflakeError> + Send + 'static,
+    ) -> Result<T, SnowflakeError> {
+        let inner = Arc::clone(&self.inner);
+        match tokio::task::spawn_blocking(move || f(&inner)).await {
+            Ok(result) => result,
+            // 生成器panic时在调用方继续panic，与直接调用同步生成器的行为一致
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => Err(SnowflakeError::ConfigError(format!("运行时已关闭，阻塞任务被取消: {}", e))),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<G: ?Sized> Clone for BlockingIdGenerator<G> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<G: IdGenerator + ?Sized + 'static> AsyncIdGenerator for BlockingIdGenerator<G> {
+    async fn next_id(&self) -> Result<u64, SnowflakeError> {
+        self.run(|generator| generator.next_id()).await
+    }
+
+    async fn next_ids(&self, count: usize) -> Result<Vec<u64>, SnowflakeError> {
+        self.run(move |generator| generator.next_ids(count)).await
+    }
+}
+
+/// 全局锁生成器
+/// 与 `get_next_id()` 共享同一个全局Worker
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GlobalIdGenerator;
+
+impl IdGenerator for GlobalIdGenerator {
+    fn next_id(&self) -> Result<u64, SnowflakeError> {
+        get_next_id()
+    }
+
+    fn next_ids(&self, count: usize) -> Result<Vec<u64>, SnowflakeError> {
+        get_next_ids(count)
+    }
+}
+
+impl IdGenerator for Mutex<SnowflakeIdWorker> {
+    fn next_id(&self) -> Result<u64, SnowflakeError> {
+        self.lock().unwrap().next_id()
+    }
+
+    fn next_ids(&self, count: usize) -> Result<Vec<u64>, SnowflakeError> {
+        let mut worker = self.lock().unwrap();
+        (0..count).map(|_| worker.next_id()).collect()
+    }
+}
+
+impl IdGenerator for PrefetchClient {
+    fn next_id(&self) -> Result<u64, SnowflakeError> {
+        PrefetchClient::next_id(self)
+    }
+}
+
+impl IdGenerator for Mutex<BinaryClient> {
+    fn next_id(&self) -> Result<u64, SnowflakeError> {
+        self.lock().unwrap().next_id()
+    }
+
+    fn next_ids(&self, count: usize) -> Result<Vec<u64>, SnowflakeError> {
+        let count = u32::try_from(count)
+            .map_err(|_| SnowflakeError::ProtocolError(format!("单次请求数量{}超过u32上限", count)))?;
+        self.lock().unwrap().next_ids(count)
+    }
+}
+
+impl IdGenerator for SegmentIdGenerator {
+    fn next_id(&self) -> Result<u64, SnowflakeError> {
+        SegmentIdGenerator::next_id(self)
+    }
+}
+
+/// 确定性顺序生成器 (用于测试)
+/// 从起始值开始每次加1，结果可预测，便于在测试中断言
+#[derive(Debug)]
+pub struct SequentialIdGenerator {
+    next: AtomicU64,
+}
+
+impl SequentialIdGenerator {
+    /// 创建从start开始的顺序生成器
+    pub fn new(start: u64) -> Self {
+        Self {
+            next: AtomicU64::new(start),
+        }
+    }
+}
+
+impl Default for SequentialIdGenerator {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl IdGenerator for SequentialIdGenerator {
+    fn next_id(&self) -> Result<u64, SnowflakeError> {
+        // 到达u64上限后报错而不是回绕，避免重复发放
+        self.next
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_add(1))
+            .map_err(|_| SnowflakeError::SequenceExhausted("顺序生成器已达到u64上限".to_string()))
+    }
+}
+
+/// 只做一次原子操作，不会阻塞，可以直接在异步任务中调用
+impl AsyncIdGenerator for SequentialIdGenerator {
+    async fn next_id(&self) -> Result<u64, SnowflakeError> {
+        IdGenerator::next_id(self)
+    }
+
+    async fn next_ids(&self, count: usize) -> Result<Vec<u64>, SnowflakeError> {
+        IdGenerator::next_ids(self, count)
+    }
+}
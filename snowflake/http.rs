@@ -0,0 +1,165 @@
+/*!
+HTTP发号接口 (server feature)
+
+把全局ID生成器包装成axum路由，`snowflake-server` 使用它提供HTTP服务，
+也可以合并到其他axum应用中。
+
+接口列表:
+- GET /id              生成一个ID
+- GET /ids?count=N     批量生成N个ID (N不超过最大批量)
+- GET /decode/:id      解析ID的时间戳、Worker ID和序列号
+- GET /health          健康检查 (Worker ID、分配方式、时钟状态)
+- GET /metrics         Prometheus指标
+
+ID生成失败 (时钟回拨、序列号用尽) 返回503，参数错误返回400，响应体为 `{"error": "..."}`。
+
+```ignore
+let app = snowflake::http::app(1000);
+let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
+axum::serve(listener, app).await?;
+```
+*/
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::get,
+    Router,
+};
+use chrono::{FixedOffset, TimeZone};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::error;
+
+use crate::{
+    decode_id, get_clock_state, get_metrics, get_next_id, get_next_ids, get_worker_info, render_prometheus,
+    SnowflakeError,
+};
+
+// ============================================================================
+// 错误响应
+// ============================================================================
+
+/// 接口错误 (状态码 + 错误信息)
+#[derive(Debug)]
+pub struct ApiError(pub StatusCode, pub String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<SnowflakeError> for ApiError {
+    fn from(e: SnowflakeError) -> Self {
+        // 时钟回拨、序列号用尽都是暂时性错误，客户端可以重试
+        error!(error = %e, "ID生成失败");
+        ApiError(StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+    }
+}
+
+// ============================================================================
+// 接口处理函数
+// ============================================================================
+
+/// 在阻塞线程池中生成ID
+/// 全局锁竞争、序列号用尽和时钟回拨时都可能等待，放到阻塞线程池避免占住异步工作线程
+async fn generate<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, SnowflakeError> + Send + 'static,
+) -> Result<T, ApiError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(ApiError::from)
+}
+
+/// 生成一个ID
+async fn get_id() -> Result<Json<Value>, ApiError> {
+    let id = generate(get_next_id).await?;
+    Ok(Json(json!({ "id": id })))
+}
+
+/// 批量生成的查询参数
+#[derive(Deserialize)]
+struct IdsQuery {
+    /// 生成数量，默认为1
+    count: Option<usize>,
+}
+
+/// 批量生成ID
+async fn get_ids(State(max_count): State<usize>, Query(params): Query<IdsQuery>) -> Result<Json<Value>, ApiError> {
+    let count = params.count.unwrap_or(1);
+    if count == 0 || count > max_count {
+        return Err(ApiError(StatusCode::BAD_REQUEST, format!("count必须在1到{}之间", max_count)));
+    }
+
+    let ids = generate(move || get_next_ids(count)).await?;
+    Ok(Json(json!({ "count": ids.len(), "ids": ids })))
+}
+
+/// 解析ID
+async fn decode(Path(id): Path<String>) -> Result<Json<Value>, ApiError> {
+    let id = id
+        .parse::<u64>()
+        .map_err(|_| ApiError(StatusCode::BAD_REQUEST, format!("无效的ID: {}", id)))?;
+    let decoded = decode_id(id);
+
+    // 按上海时区展示生成时间，与基准时间的时区一致
+    let datetime = FixedOffset::east_opt(8 * 3600)
+        .and_then(|tz| tz.timestamp_millis_opt(decoded.timestamp_ms as i64).single())
+        .map(|dt| dt.to_rfc3339());
+
+    Ok(Json(json!({
+        "id": decoded.id,
+        "timestamp_ms": decoded.timestamp_ms,
+        "datetime": datetime,
+        "worker_id": decoded.worker_id,
+        "sequence": decoded.sequence,
+    })))
+}
+
+/// 健康检查
+/// 系统时钟落后于上次生成时间 (时钟回拨) 时报告degraded，借用未来毫秒不算异常
+async fn health() -> Json<Value> {
+    let (worker_id, strategy) = get_worker_info();
+    let clock = get_clock_state();
+    let metrics = get_metrics();
+    let status = if clock.behind_ms > 0 && !clock.borrowing { "degraded" } else { "ok" };
+
+    Json(json!({
+        "status": status,
+        "worker_id": worker_id,
+        "strategy": strategy.as_str(),
+        "clock": {
+            "system_ms": clock.system_ms,
+            "last_ms": clock.last_ms,
+            "behind_ms": clock.behind_ms,
+            "borrowing": clock.borrowing,
+            "backward_events": metrics.clock_backward_events,
+            "backward_max_ms": metrics.clock_backward_max_ms,
+        },
+    }))
+}
+
+/// Prometheus指标
+async fn metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        render_prometheus(),
+    )
+}
+
+/// 创建路由
+///
+/// 参数:
+/// - max_count: `/ids` 单次请求最多生成的ID数
+pub fn app(max_count: usize) -> Router {
+    Router::new()
+        .route("/id", get(get_id))
+        .route("/ids", get(get_ids))
+        .route("/decode/:id", get(decode))
+        .route("/health", get(health))
+        .route("/metrics", get(metrics))
+        .with_state(max_count)
+}
@@ -0,0 +1,238 @@
+/*!
+类型化的雪花ID
+
+裸 `u64` 在代码里很容易把订单ID传给用户ID的参数，这里提供 `SnowflakeId<T>` 包装类型:
+- `T` 是可选的实体标记类型，`SnowflakeId<Order>` 和 `SnowflakeId<User>` 不能互相赋值
+- 不需要区分实体时直接使用 `SnowflakeId` (即 `SnowflakeId<()>`)
+- 内存布局与 `u64` 相同，比较、哈希、排序都按数值进行
+
+开启 `serde` feature 后支持序列化:
+- JSON等文本格式默认序列化为十进制字符串，避免JavaScript的Number (53位精度) 丢失精度，
+  反序列化同时接受字符串和数字
+- bincode、postcard等二进制格式 (`is_human_readable()` 为false) 始终按u64读写
+- 需要在文本格式中序列化为数字的字段可以使用 `#[serde(with = "snowflake::id::as_number")]`
+*/
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use crate::{get_next_id, global_config, DecodedId, SnowflakeError};
+
+/// 类型化的雪花ID
+///
+/// `PhantomData<fn() -> T>` 让标记类型不影响 `Send` / `Sync` / `Copy`，
+/// 标记类型本身也不需要实现任何trait。
+#[repr(transparent)]
+pub struct SnowflakeId<T: ?Sized = ()> {
+    value: u64,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: ?Sized> SnowflakeId<T> {
+    /// 包装一个已有的ID
+    pub const fn new(value: u64) -> Self {
+        Self {
+            value,
+            marker: PhantomData,
+        }
+    }
+
+    /// 使用全局生成器生成一个新ID
+    pub fn generate() -> Result<Self, SnowflakeError> {
+        get_next_id().map(Self::new)
+    }
+
+    /// 原始数值
+    pub const fn get(self) -> u64 {
+        self.value
+    }
+
+    /// 转换为另一个实体的ID (显式地绕过类型检查)
+    pub const fn cast<U: ?Sized>(self) -> SnowflakeId<U> {
+        SnowflakeId::new(self.value)
+    }
+
+    /// 按全局生成器的位布局解析ID (与 `decode_id` 相同)
+    pub fn decode(self) -> DecodedId {
+        global_config().decode(self.value)
+    }
+
+    /// 生成时间 (Unix毫秒时间戳)
+    pub fn timestamp_ms(self) -> u64 {
+        self.decode().timestamp_ms
+    }
+
+    /// 生成ID的Worker ID
+    pub fn worker_id(self) -> u64 {
+        self.decode().worker_id
+    }
+
+    /// 同一毫秒内的序列号
+    pub fn sequence(self) -> u64 {
+        self.decode().sequence
+    }
+}
+
+// 以下trait都手动实现，避免derive给标记类型T加上多余的约束
+
+impl<T: ?Sized> Clone for SnowflakeId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for SnowflakeId<T> {}
+
+impl<T: ?Sized> PartialEq for SnowflakeId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: ?Sized> Eq for SnowflakeId<T> {}
+
+impl<T: ?Sized> PartialOrd for SnowflakeId<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: ?Sized> Ord for SnowflakeId<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: ?Sized> Hash for SnowflakeId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for SnowflakeId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SnowflakeId({})", self.value)
+    }
+}
+
+/// 十进制格式
+impl<T: ?Sized> fmt::Display for SnowflakeId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value, f)
+    }
+}
+
+/// 从十进制字符串解析
+impl<T: ?Sized> FromStr for SnowflakeId<T> {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self::new)
+    }
+}
+
+impl<T: ?Sized> From<u64> for SnowflakeId<T> {
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized> From<SnowflakeId<T>> for u64 {
+    fn from(id: SnowflakeId<T>) -> Self {
+        id.value
+    }
+}
+
+// ============================================================================
+// serde支持
+// ============================================================================
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    use serde::de::{self, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::SnowflakeId;
+
+    /// 文本格式序列化为十进制字符串，二进制格式序列化为u64
+    impl<T: ?Sized> Serialize for SnowflakeId<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                serializer.collect_str(self)
+            } else {
+                serializer.serialize_u64(self.get())
+            }
+        }
+    }
+
+    /// 文本格式同时接受字符串和非负整数，二进制格式读取u64
+    impl<'de, T: ?Sized> Deserialize<'de> for SnowflakeId<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserialize_id(deserializer)
+        }
+    }
+
+    /// 按格式选择反序列化方式
+    /// 二进制格式通常不是自描述的，不支持 `deserialize_any`
+    pub(super) fn deserialize_id<'de, T: ?Sized, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<SnowflakeId<T>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(IdVisitor(PhantomData))
+        } else {
+            deserializer.deserialize_u64(IdVisitor(PhantomData))
+        }
+    }
+
+    struct IdVisitor<T: ?Sized>(PhantomData<fn() -> T>);
+
+    impl<T: ?Sized> Visitor<'_> for IdVisitor<T> {
+        type Value = SnowflakeId<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("十进制字符串或非负整数形式的雪花ID")
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(SnowflakeId::new(v))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            u64::try_from(v)
+                .map(SnowflakeId::new)
+                .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            v.parse()
+                .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+        }
+    }
+}
+
+/// 序列化为数字的字段使用 `#[serde(with = "snowflake::id::as_number")]`
+/// 反序列化与默认行为相同，文本格式同时接受字符串和数字
+#[cfg(feature = "serde")]
+pub mod as_number {
+    use serde::{Deserializer, Serializer};
+
+    use super::serde_impl::deserialize_id;
+    use super::SnowflakeId;
+
+    /// 序列化为u64数字
+    pub fn serialize<T: ?Sized, S: Serializer>(id: &SnowflakeId<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(id.get())
+    }
+
+    /// 从字符串或数字反序列化
+    pub fn deserialize<'de, T: ?Sized, D: Deserializer<'de>>(deserializer: D) -> Result<SnowflakeId<T>, D::Error> {
+        deserialize_id(deserializer)
+    }
+}
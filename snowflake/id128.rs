@@ -0,0 +1,299 @@
+/*!
+128位ID: UUIDv7、ULID，以及雪花ID与UUIDv8的互相转换
+
+部分下游系统只接受128位标识符。UUIDv7和ULID生成器与雪花ID生成器共用:
+- 时钟抽象 (`Clock`)，测试中可以注入模拟时钟
+- 时钟回拨处理: 容忍范围内等待，超出时返回 `SnowflakeError::ClockBackward`
+- 同一毫秒内单调递增: 每毫秒的随机部分从随机值开始 (最高位为0)，之后每次加1；
+  随机部分用尽时等待下一毫秒
+
+ID布局:
+- UUIDv7 (RFC 9562): 48位Unix毫秒时间戳 + 版本(7) + 12位rand_a + 变体(10) + 62位rand_b，
+  rand_a与rand_b合起来作为74位单调计数器
+- ULID: 48位Unix毫秒时间戳 + 80位随机部分，26个字符的Crockford base32
+
+雪花ID可以无损地放入UUIDv8: ID的高48位、中间12位、低4位分别放入custom_a、custom_b和
+custom_c的最高4位，其余位为0。转换后UUID的顺序与雪花ID的顺序一致。
+*/
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use uuid::Variant;
+
+/// 生成器返回的UUID类型 (uuid crate)
+pub use uuid::Uuid;
+
+use crate::clock::{self, Clock, SystemClock};
+use crate::encoding::{base32_char, base32_digit, DecodeError};
+use crate::SnowflakeError;
+
+/// 48位时间戳能表示的最大Unix毫秒时间戳 (公元10889年)
+const MAX_TIMESTAMP_MS: u64 = (1 << 48) - 1;
+
+/// UUIDv7单调计数器位数 (rand_a 12位 + rand_b 62位)
+const UUID_V7_COUNTER_BITS: u32 = 74;
+
+/// ULID随机部分位数
+const ULID_RANDOM_BITS: u32 = 80;
+
+/// ULID字符串长度 (26 * 5 = 130位)
+pub const ULID_WIDTH: usize = 26;
+
+/// 128位ID生成器配置
+#[derive(Debug, Clone)]
+pub struct Id128Config {
+    /// 时钟回拨容忍度(毫秒)，含义与 `SnowflakeConfig::max_backward_ms` 相同
+    pub max_backward_ms: u64,
+}
+
+impl Default for Id128Config {
+    fn default() -> Self {
+        Self {
+            max_backward_ms: 10, // 容忍10毫秒时钟回拨
+        }
+    }
+}
+
+// ============================================================================
+// 单调时间戳 + 计数器
+// ============================================================================
+
+/// 上次生成的时间戳和计数器
+#[derive(Debug, Default)]
+struct MonotonicState {
+    last_ms: Option<u64>,
+    counter: u128,
+}
+
+/// UUIDv7和ULID共用的单调生成逻辑
+#[derive(Debug)]
+struct Monotonic {
+    config: Id128Config,
+    clock: Arc<dyn Clock>,
+    /// 计数器位数
+    bits: u32,
+    state: Mutex<MonotonicState>,
+}
+
+impl Monotonic {
+    fn new(config: Option<Id128Config>, clock: Arc<dyn Clock>, bits: u32) -> Self {
+        Self {
+            config: config.unwrap_or_default(),
+            clock,
+            bits,
+            state: Mutex::new(MonotonicState::default()),
+        }
+    }
+
+    /// 生成下一个 (时间戳, 计数器)，结果严格递增
+    fn next(&self) -> Result<(u64, u128), SnowflakeError> {
+        let mut state = self.state.lock().unwrap();
+        let mut now = self.clock.now_ms()?;
+
+        if let Some(last) = state.last_ms {
+            if now < last {
+                now = clock::wait_out_backward(self.clock.as_ref(), now, last, self.config.max_backward_ms, |_, _| {})?;
+            }
+            if now == last {
+                let counter = state.counter + 1;
+                if counter >> self.bits == 0 {
+                    state.counter = counter;
+                    return Ok((now, counter));
+                }
+                // 同一毫秒内计数器用尽 (实际几乎不可能发生)，等待下一毫秒
+                while now <= last {
+                    thread::yield_now();
+                    now = self.clock.now_ms()?;
+                }
+            }
+        }
+
+        if now > MAX_TIMESTAMP_MS {
+            return Err(SnowflakeError::TimeOutOfRange(format!(
+                "Unix毫秒时间戳{}超过48位上限",
+                now
+            )));
+        }
+
+        // 新的一毫秒: 从随机值开始，最高位为0，保证之后至少还能递增 2^(bits-1) 次
+        let counter = random_u128()? & ((1u128 << (self.bits - 1)) - 1);
+        state.last_ms = Some(now);
+        state.counter = counter;
+        Ok((now, counter))
+    }
+}
+
+/// 从操作系统获取随机数
+fn random_u128() -> Result<u128, SnowflakeError> {
+    let mut buf = [0u8; 16];
+    getrandom::getrandom(&mut buf)
+        .map_err(|e| SnowflakeError::ConfigError(format!("无法获取系统随机数: {}", e)))?;
+    Ok(u128::from_be_bytes(buf))
+}
+
+// ============================================================================
+// UUIDv7
+// ============================================================================
+
+/// UUIDv7生成器 (线程安全)
+#[derive(Debug)]
+pub struct UuidV7Generator {
+    inner: Monotonic,
+}
+
+impl UuidV7Generator {
+    /// 创建使用系统时钟的生成器
+    pub fn new(config: Option<Id128Config>) -> Self {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+
+    /// 使用指定时钟创建生成器
+    pub fn with_clock(config: Option<Id128Config>, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            inner: Monotonic::new(config, clock, UUID_V7_COUNTER_BITS),
+        }
+    }
+
+    /// 生成下一个UUIDv7 (同一生成器内严格递增)
+    pub fn next_uuid(&self) -> Result<Uuid, SnowflakeError> {
+        let (ms, counter) = self.inner.next()?;
+        let rand_a = (counter >> 62) & 0xfff;
+        let rand_b = counter & ((1u128 << 62) - 1);
+        let value = (ms as u128) << 80 | 0x7 << 76 | rand_a << 64 | 0b10 << 62 | rand_b;
+        Ok(Uuid::from_u128(value))
+    }
+}
+
+impl Default for UuidV7Generator {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+// ============================================================================
+// ULID
+// ============================================================================
+
+/// ULID (128位，26个字符的Crockford base32)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ulid(u128);
+
+impl Ulid {
+    /// 由128位数值创建
+    pub const fn from_u128(value: u128) -> Self {
+        Self(value)
+    }
+
+    /// 128位数值
+    pub const fn to_u128(self) -> u128 {
+        self.0
+    }
+
+    /// 生成时间 (Unix毫秒时间戳)
+    pub const fn timestamp_ms(self) -> u64 {
+        (self.0 >> ULID_RANDOM_BITS) as u64
+    }
+
+    /// 80位随机部分
+    pub const fn random(self) -> u128 {
+        self.0 & ((1u128 << ULID_RANDOM_BITS) - 1)
+    }
+}
+
+impl fmt::Display for Ulid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = [0u8; ULID_WIDTH];
+        let mut value = self.0;
+        for slot in buf.iter_mut().rev() {
+            *slot = base32_char((value & 0x1f) as u8) as u8;
+            value >>= 5;
+        }
+        f.write_str(std::str::from_utf8(&buf).expect("base32字母表为ASCII"))
+    }
+}
+
+/// 解析26个字符的ULID (不区分大小写)
+impl FromStr for Ulid {
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let actual = s.chars().count();
+        if actual != ULID_WIDTH {
+            return Err(DecodeError::InvalidLength { expected: ULID_WIDTH, actual });
+        }
+        let mut value: u128 = 0;
+        for (position, ch) in s.chars().enumerate() {
+            let digit = base32_digit(ch).ok_or(DecodeError::InvalidChar { ch, position })?;
+            // 26个字符共130位，第一个字符只能是0-7
+            if position == 0 && digit > 7 {
+                return Err(DecodeError::Overflow);
+            }
+            value = value << 5 | digit as u128;
+        }
+        Ok(Self(value))
+    }
+}
+
+/// ULID生成器 (线程安全)
+#[derive(Debug)]
+pub struct UlidGenerator {
+    inner: Monotonic,
+}
+
+impl UlidGenerator {
+    /// 创建使用系统时钟的生成器
+    pub fn new(config: Option<Id128Config>) -> Self {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+
+    /// 使用指定时钟创建生成器
+    pub fn with_clock(config: Option<Id128Config>, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            inner: Monotonic::new(config, clock, ULID_RANDOM_BITS),
+        }
+    }
+
+    /// 生成下一个ULID (同一生成器内严格递增)
+    pub fn next_ulid(&self) -> Result<Ulid, SnowflakeError> {
+        let (ms, counter) = self.inner.next()?;
+        Ok(Ulid((ms as u128) << ULID_RANDOM_BITS | counter))
+    }
+}
+
+impl Default for UlidGenerator {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+// ============================================================================
+// 雪花ID <-> UUIDv8
+// ============================================================================
+
+/// 把雪花ID放入UUIDv8 (无损，保持顺序)
+pub fn snowflake_to_uuid_v8(id: u64) -> Uuid {
+    let id = id as u128;
+    let custom_a = id >> 16; // 高48位
+    let custom_b = (id >> 4) & 0xfff; // 中间12位
+    let custom_c = (id & 0xf) << 58; // 低4位放在custom_c的最高位
+    Uuid::from_u128(custom_a << 80 | 0x8 << 76 | custom_b << 64 | 0b10 << 62 | custom_c)
+}
+
+/// 从UUIDv8中取出雪花ID
+/// 不是由 `snowflake_to_uuid_v8` 生成的UUID (版本、变体不符或保留位非0) 返回None
+pub fn snowflake_from_uuid_v8(uuid: &Uuid) -> Option<u64> {
+    if uuid.get_version_num() != 8 || uuid.get_variant() != Variant::RFC4122 {
+        return None;
+    }
+    let value = uuid.as_u128();
+    if value & ((1u128 << 58) - 1) != 0 {
+        return None;
+    }
+    let custom_a = value >> 80;
+    let custom_b = (value >> 64) & 0xfff;
+    let custom_c = (value >> 58) & 0xf;
+    Some((custom_a << 16 | custom_b << 4 | custom_c) as u64)
+}
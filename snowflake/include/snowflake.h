@@ -0,0 +1,151 @@
+/* 雪花算法ID生成器的C接口 (由cbindgen根据ffi.rs生成，请勿手工修改) */
+
+#ifndef SNOWFLAKE_H
+#define SNOWFLAKE_H
+
+/* 修改接口后运行 SNOWFLAKE_UPDATE_HEADER=1 cargo test --test ffi 重新生成 */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// 序列号用尽时自旋等待下一毫秒
+#define SNOWFLAKE_POLICY_SPIN 0
+
+// 序列号用尽时休眠等待下一毫秒
+#define SNOWFLAKE_POLICY_SLEEP 1
+
+// 序列号用尽时返回 `SNOWFLAKE_STATUS_SEQUENCE_EXHAUSTED`
+#define SNOWFLAKE_POLICY_ERROR 2
+
+// 序列号用尽时借用未来毫秒 (最多领先 `borrow_ahead_ms`)
+#define SNOWFLAKE_POLICY_BORROW 3
+
+// 每毫秒序列号从0开始
+#define SNOWFLAKE_SEQUENCE_START_ZERO 0
+
+// 每毫秒序列号从随机值开始
+#define SNOWFLAKE_SEQUENCE_START_RANDOM 1
+
+// 每毫秒序列号起始值轮转
+#define SNOWFLAKE_SEQUENCE_START_ROTATING 2
+
+// 返回码
+// 1-6和10与 `SnowflakeError` 的各个变体一一对应
+typedef enum {
+  // 成功
+  SNOWFLAKE_STATUS_OK = 0,
+  // 时钟回拨超出容忍范围 (`SnowflakeError::ClockBackward`)
+  SNOWFLAKE_STATUS_CLOCK_BACKWARD = 1,
+  // 配置错误 (`SnowflakeError::ConfigError`)
+  SNOWFLAKE_STATUS_CONFIG_ERROR = 2,
+  // 网络错误 (`SnowflakeError::NetworkError`)
+  SNOWFLAKE_STATUS_NETWORK_ERROR = 3,
+  // 序列号用尽 (`SnowflakeError::SequenceExhausted`)
+  SNOWFLAKE_STATUS_SEQUENCE_EXHAUSTED = 4,
+  // 协议错误 (`SnowflakeError::ProtocolError`)
+  SNOWFLAKE_STATUS_PROTOCOL_ERROR = 5,
+  // 时间超出范围 (`SnowflakeError::TimeOutOfRange`)
+  SNOWFLAKE_STATUS_TIME_OUT_OF_RANGE = 6,
+  // 参数无效 (空指针、未知的策略编号等)
+  SNOWFLAKE_STATUS_INVALID_ARGUMENT = 7,
+  // 全局Worker已经存在，`snowflake_init` 的配置未生效
+  SNOWFLAKE_STATUS_ALREADY_INITIALIZED = 8,
+  // Rust代码发生panic (内部错误)
+  SNOWFLAKE_STATUS_PANIC = 9,
+  // IO错误 (`SnowflakeError::IoError`)
+  SNOWFLAKE_STATUS_IO_ERROR = 10,
+} SnowflakeStatus;
+
+// 全局生成器配置 (对应 `SnowflakeConfig`)
+// 先用 `snowflake_config_default` 填充默认值，再修改需要的字段
+typedef struct {
+  // Worker ID位数 (1-8)
+  uint8_t worker_id_bits;
+  // 序列号位数 (至少1位，与Worker ID位数之和小于63)
+  uint8_t sequence_bits;
+  // 时钟回拨容忍度 (毫秒)
+  uint64_t max_backward_ms;
+  // 序列号用尽策略 (`SNOWFLAKE_POLICY_*`)
+  uint32_t exhausted_policy;
+  // Borrow策略最多领先系统时钟的毫秒数 (其他策略忽略)
+  uint64_t borrow_ahead_ms;
+  // 每毫秒序列号的起始值 (`SNOWFLAKE_SEQUENCE_START_*`)
+  uint32_t sequence_start;
+} SnowflakeInitConfig;
+
+// 解析后的ID各组成部分 (对应 `DecodedId`)
+typedef struct {
+  // 原始ID
+  uint64_t id;
+  // 生成时间 (Unix毫秒时间戳)
+  uint64_t timestamp_ms;
+  // 生成ID的Worker ID
+  uint64_t worker_id;
+  // 同一毫秒内的序列号
+  uint64_t sequence;
+} SnowflakeDecodedId;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// 用默认配置填充 `config`
+//
+// # Safety
+// `config` 必须为空指针或指向可写的 `SnowflakeInitConfig`
+SnowflakeStatus snowflake_config_default(SnowflakeInitConfig *config);
+
+// 使用指定配置创建全局Worker
+//
+// 必须在第一次生成ID之前调用；`config` 为空指针时使用默认配置。
+// Worker ID仍按环境变量、租约目录、snowflake.toml、IP的优先级分配，
+// snowflake.toml中出现的配置项 (如 `max_backward_ms`) 覆盖 `config` 中的对应字段。
+// 全局Worker已经存在时返回 `SNOWFLAKE_STATUS_ALREADY_INITIALIZED`，配置不生效。
+//
+// # Safety
+// `config` 必须为空指针或指向有效的 `SnowflakeInitConfig`
+SnowflakeStatus snowflake_init(const SnowflakeInitConfig *config);
+
+// 生成下一个ID，写入 `out`
+//
+// # Safety
+// `out` 必须为空指针或指向可写的 `uint64_t`
+SnowflakeStatus snowflake_next_id(uint64_t *out);
+
+// 批量生成 `count` 个ID，按生成顺序写入 `out`
+// 失败时 `out` 的内容未定义
+//
+// # Safety
+// `out` 必须指向至少 `count` 个可写的 `uint64_t` (`count` 为0时可以为空指针)
+SnowflakeStatus snowflake_next_ids(uint64_t *out, size_t count);
+
+// 按全局生成器的位布局解析ID，写入 `out`
+// 不会触发全局Worker的创建
+//
+// # Safety
+// `out` 必须为空指针或指向可写的 `SnowflakeDecodedId`
+SnowflakeStatus snowflake_decode(uint64_t id, SnowflakeDecodedId *out);
+
+// 获取全局Worker的Worker ID，写入 `out`
+// 全局Worker尚未创建时按当前配置创建
+//
+// # Safety
+// `out` 必须为空指针或指向可写的 `uint8_t`
+SnowflakeStatus snowflake_worker_id(uint8_t *out);
+
+// 复制当前线程最近一次失败的错误信息 (UTF-8) 到 `buf`
+//
+// 与 `snprintf` 相同: 最多写入 `len - 1` 个字节并以 `'\0'` 结尾，
+// 返回完整信息的字节数 (不含结尾的 `'\0'`)；返回值不小于 `len` 表示被截断。
+// 没有失败记录时返回0。`buf` 为空指针或 `len` 为0时只返回长度。
+//
+// # Safety
+// `buf` 必须为空指针或指向至少 `len` 个可写的字节
+size_t snowflake_last_error(char *buf,
+                            size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SNOWFLAKE_H */
@@ -0,0 +1,86 @@
+/*!
+Worker ID文件租约
+
+同一台机器上运行多个进程时，IP段分配会得到相同的Worker ID，逐个设置环境变量又容易出错。
+设置环境变量 `SNOWFLAKE_WORKER_LEASE_DIR` 后，进程在该目录中为每个Worker ID准备一个锁文件
+(`worker-<id>.lock`)，从0开始依次尝试加排他锁，第一个加锁成功的ID即为本进程的Worker ID。
+
+- 锁由操作系统持有: 进程退出 (包括崩溃、被kill) 时自动释放，不会留下失效的租约
+- 锁文件中写入持有者的进程号，便于排查
+- 所有进程必须使用同一个目录，且该目录不能在不支持文件锁的网络文件系统上
+*/
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use fs2::FileExt;
+use tracing::debug;
+
+use crate::SnowflakeError;
+
+/// 指定租约目录的环境变量
+pub const LEASE_DIR_ENV: &str = "SNOWFLAKE_WORKER_LEASE_DIR";
+
+/// 已获得的Worker ID租约
+/// 释放 (drop) 时解锁，其他进程可以重新使用该Worker ID
+#[derive(Debug)]
+pub struct WorkerLease {
+    worker_id: u8,
+    path: PathBuf,
+    /// 持有排他锁的文件
+    file: File,
+}
+
+impl WorkerLease {
+    /// 在目录中获取第一个空闲的Worker ID (0..=max_worker_id)
+    pub fn acquire(dir: impl AsRef<Path>, max_worker_id: u8) -> Result<Self, SnowflakeError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|e| {
+            SnowflakeError::ConfigError(format!("无法创建租约目录 {}: {}", dir.display(), e))
+        })?;
+
+        for worker_id in 0..=max_worker_id {
+            let path = dir.join(format!("worker-{}.lock", worker_id));
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .map_err(|e| SnowflakeError::ConfigError(format!("无法打开租约文件 {}: {}", path.display(), e)))?;
+            if file.try_lock_exclusive().is_err() {
+                continue; // 已被其他进程持有
+            }
+
+            // 记录持有者，写入失败不影响租约本身
+            let _ = file.set_len(0).and_then(|_| writeln!(&file, "{}", process::id()));
+            debug!(worker_id, path = %path.display(), "获得Worker ID租约");
+            return Ok(Self { worker_id, path, file });
+        }
+
+        Err(SnowflakeError::ConfigError(format!(
+            "租约目录 {} 中的{}个Worker ID都已被占用",
+            dir.display(),
+            max_worker_id as u32 + 1
+        )))
+    }
+
+    /// 租到的Worker ID
+    pub fn worker_id(&self) -> u8 {
+        self.worker_id
+    }
+
+    /// 锁文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for WorkerLease {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+        debug!(worker_id = self.worker_id, "释放Worker ID租约");
+    }
+}
@@ -0,0 +1,676 @@
+/*!
+雪花ID命令行工具
+
+子命令:
+- gen: 生成ID，支持dec/hex/base62/base32/json格式
+- decode: 解析ID，不带参数或参数为 - 时从标准输入逐行读取
+- config check: 检查配置，输出Worker ID、分配方式和位布局
+- bounds: 计算时间范围内的最小/最大ID
+- audit: 审计多个节点导出的ID (重复、Worker ID、未来ID、回退、序列号饱和)
+- demo: 运行演示程序
+
+时间格式: RFC 3339 (例如 2025-06-01T00:00:00+08:00)、Unix毫秒时间戳或 now。
+结果输出到stdout，日志和错误输出到stderr。退出码: 0成功，1执行失败，2参数错误。
+*/
+
+use std::env;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+use std::process;
+
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use serde_json::{json, Value};
+use snowflake::audit::{AuditConfig, AuditReport, Auditor, DumpFormat};
+use snowflake::encoding::Encoding;
+use snowflake::lease::LEASE_DIR_ENV;
+use snowflake::reload::{config_file_path, FileConfig};
+use snowflake::{
+    get_next_ids, init_logging, DecodedId, SnowflakeConfig, SnowflakeError, SnowflakeIdWorker, TWEPOCH_MS,
+};
+
+/// 用法说明
+const USAGE: &str = "\
+用法: snowflake <子命令> [选项]
+
+子命令:
+  gen [-n N] [--format dec|hex|base62|base32|json]
+        生成N个ID (默认1个)，每行一个；json格式输出一个JSON对象
+  decode [--format dec|hex|base62|base32] [--json] <id>...
+        解析ID；不带ID或ID为 - 时从标准输入逐行读取；--json 每行输出一个JSON对象
+  config check [--json]
+        读取环境变量和snowflake.toml，输出Worker ID、分配方式和位布局 (不获取租约)
+  bounds --from <时间> --to <时间> [--json]
+        计算时间范围内可能出现的最小和最大ID (用于数据库范围查询)
+  audit [--binary] [--chunk-size N] [--future-tolerance-ms N] [--worker-id-bits N] [--sequence-bits N]
+        [--json] [节点=]<文件>...
+        审计多个节点导出的ID: 重复ID、各节点实际使用的Worker ID、未来ID、
+        同一Worker ID的回退和每毫秒序列号饱和；发现重复或未来ID时退出码为1。
+        .bin 文件按8字节大端序读取 (--binary 强制二进制)，其他文件每行一个十进制ID；
+        未指定节点名时使用文件名。位布局默认取snowflake.toml，选项优先
+  demo
+        运行演示程序
+
+时间格式: RFC 3339 (2025-06-01T00:00:00+08:00)、Unix毫秒时间戳或 now
+decode、config check、bounds 使用snowflake.toml中的位布局 (与生成ID时一致)";
+
+/// 单次批量生成的ID数 (大批量时分批生成并输出，避免占用过多内存)
+const GEN_CHUNK: usize = 10_000;
+
+/// 命令行错误
+enum CliError {
+    /// 参数错误 (退出码2，并打印用法)
+    Usage(String),
+    /// 执行失败 (退出码1)
+    Failed(String),
+}
+
+impl From<SnowflakeError> for CliError {
+    fn from(e: SnowflakeError) -> Self {
+        CliError::Failed(e.to_string())
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        CliError::Failed(format!("IO错误: {}", e))
+    }
+}
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// 十进制
+    Dec,
+    /// 字符串编码 (hex / base62 / base32)
+    Encoded(Encoding),
+    /// JSON
+    Json,
+}
+
+impl Format {
+    fn parse(value: &str) -> Result<Self, CliError> {
+        match value {
+            "dec" => Ok(Format::Dec),
+            "hex" => Ok(Format::Encoded(Encoding::Hex)),
+            "base62" => Ok(Format::Encoded(Encoding::Base62)),
+            "base32" => Ok(Format::Encoded(Encoding::Base32)),
+            "json" => Ok(Format::Json),
+            other => Err(CliError::Usage(format!("未知格式: {}", other))),
+        }
+    }
+}
+
+fn main() {
+    init_logging();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("gen") => cmd_gen(&args[1..]),
+        Some("decode") => cmd_decode(&args[1..]),
+        Some("config") => cmd_config(&args[1..]),
+        Some("bounds") => cmd_bounds(&args[1..]),
+        Some("audit") => cmd_audit(&args[1..]),
+        Some("demo") => {
+            snowflake::demo();
+            Ok(())
+        }
+        Some("help" | "-h" | "--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(other) => Err(CliError::Usage(format!("未知子命令: {}", other))),
+        None => Err(CliError::Usage("缺少子命令".to_string())),
+    };
+
+    match result {
+        Ok(()) => {}
+        Err(CliError::Usage(msg)) => {
+            eprintln!("{}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+        Err(CliError::Failed(msg)) => {
+            eprintln!("错误: {}", msg);
+            process::exit(1);
+        }
+    }
+}
+
+/// 取选项的值
+fn option_value<'a>(args: &mut impl Iterator<Item = &'a String>, name: &str) -> Result<&'a str, CliError> {
+    args.next()
+        .map(String::as_str)
+        .ok_or_else(|| CliError::Usage(format!("{} 缺少参数值", name)))
+}
+
+/// 读取配置文件 (`CONFIG_PATHS` 中第一个存在的文件)，文件中没有的项使用默认值
+/// 与全局生成器启动时使用的配置一致
+fn load_config() -> Result<(Option<&'static str>, SnowflakeConfig), CliError> {
+    let mut config = SnowflakeConfig::default();
+    let path = config_file_path();
+    if let Some(path) = path {
+        FileConfig::load(path)?.apply_to(&mut config);
+    }
+    Ok((path, config))
+}
+
+/// 按上海时区格式化Unix毫秒时间戳 (与基准时间的时区一致)
+fn format_datetime(ms: u64) -> Option<String> {
+    FixedOffset::east_opt(8 * 3600)
+        .and_then(|tz| tz.timestamp_millis_opt(ms as i64).single())
+        .map(|dt| dt.to_rfc3339())
+}
+
+// ============================================================================
+// gen
+// ============================================================================
+
+/// 生成ID
+fn cmd_gen(args: &[String]) -> Result<(), CliError> {
+    let mut count: usize = 1;
+    let mut format = Format::Dec;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-n" | "--count" => {
+                let value = option_value(&mut iter, arg)?;
+                count = match value.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(CliError::Usage(format!("数量必须为正整数: {}", value))),
+                };
+            }
+            "--format" => format = Format::parse(option_value(&mut iter, arg)?)?,
+            other => return Err(CliError::Usage(format!("gen: 未知参数: {}", other))),
+        }
+    }
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    // JSON按流式写出，数量很大时也不需要把所有ID放在内存里
+    // ID写成十进制字符串 (与SnowflakeId的serde序列化一致)，超过2^53的ID在JavaScript中不会丢失精度
+    if format == Format::Json {
+        write!(out, "{{\"count\":{},\"ids\":[", count)?;
+    }
+    let mut remaining = count;
+    let mut first = true;
+    while remaining > 0 {
+        let chunk = remaining.min(GEN_CHUNK);
+        for id in get_next_ids(chunk)? {
+            match format {
+                Format::Dec => writeln!(out, "{}", id)?,
+                Format::Encoded(encoding) => writeln!(out, "{}", encoding.encode(id))?,
+                Format::Json => {
+                    if !first {
+                        out.write_all(b",")?;
+                    }
+                    write!(out, "\"{}\"", id)?;
+                }
+            }
+            first = false;
+        }
+        remaining -= chunk;
+    }
+    if format == Format::Json {
+        writeln!(out, "]}}")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+// ============================================================================
+// decode
+// ============================================================================
+
+/// 解析ID
+fn cmd_decode(args: &[String]) -> Result<(), CliError> {
+    let mut input_format = Format::Dec;
+    let mut as_json = false;
+    let mut ids: Vec<&str> = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => {
+                input_format = Format::parse(option_value(&mut iter, arg)?)?;
+                if input_format == Format::Json {
+                    return Err(CliError::Usage("decode的输入格式不支持json".to_string()));
+                }
+            }
+            "--json" => as_json = true,
+            "-" => {}
+            other if other.starts_with("--") => {
+                return Err(CliError::Usage(format!("decode: 未知参数: {}", other)));
+            }
+            id => ids.push(id),
+        }
+    }
+
+    let (_, config) = load_config()?;
+    config.check_layout()?;
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut failed = 0usize;
+
+    let mut decode_one = |input: &str, out: &mut dyn Write| -> io::Result<()> {
+        let parsed = match input_format {
+            Format::Encoded(encoding) => encoding.decode(input).map_err(|e| e.to_string()),
+            _ => input.parse::<u64>().map_err(|_| "不是合法的十进制ID".to_string()),
+        };
+        match parsed {
+            Ok(id) => write_decoded(out, &config.decode(id), as_json),
+            Err(e) => {
+                failed += 1;
+                if as_json {
+                    writeln!(out, "{}", json!({ "input": input, "error": e }))
+                } else {
+                    eprintln!("无效的ID {:?}: {}", input, e);
+                    Ok(())
+                }
+            }
+        }
+    };
+
+    if ids.is_empty() {
+        for line in io::stdin().lock().lines() {
+            let line = line?;
+            let input = line.trim();
+            if !input.is_empty() {
+                decode_one(input, &mut out)?;
+            }
+        }
+    } else {
+        for input in ids {
+            decode_one(input, &mut out)?;
+        }
+    }
+    out.flush()?;
+
+    if failed > 0 {
+        return Err(CliError::Failed(format!("{}个ID无法解析", failed)));
+    }
+    Ok(())
+}
+
+/// 输出一个解析结果: 文本为制表符分隔的 ID、时间、Worker ID、序列号
+fn write_decoded(out: &mut dyn Write, decoded: &DecodedId, as_json: bool) -> io::Result<()> {
+    let datetime = format_datetime(decoded.timestamp_ms);
+    if as_json {
+        writeln!(
+            out,
+            "{}",
+            json!({
+                "id": decoded.id.to_string(),
+                "timestamp_ms": decoded.timestamp_ms,
+                "datetime": datetime,
+                "worker_id": decoded.worker_id,
+                "sequence": decoded.sequence,
+            })
+        )
+    } else {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}",
+            decoded.id,
+            datetime.as_deref().unwrap_or("-"),
+            decoded.worker_id,
+            decoded.sequence
+        )
+    }
+}
+
+// ============================================================================
+// config check
+// ============================================================================
+
+/// 检查配置
+fn cmd_config(args: &[String]) -> Result<(), CliError> {
+    let mut as_json = false;
+    match args.first().map(String::as_str) {
+        Some("check") => {}
+        Some(other) => return Err(CliError::Usage(format!("config: 未知子命令: {}", other))),
+        None => return Err(CliError::Usage("config: 缺少子命令 (check)".to_string())),
+    }
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "--json" => as_json = true,
+            other => return Err(CliError::Usage(format!("config check: 未知参数: {}", other))),
+        }
+    }
+
+    let (config_file, config) = load_config()?;
+    let env_worker_id = env::var("SNOWFLAKE_WORKER_ID").ok();
+    let lease_dir = env::var(LEASE_DIR_ENV).ok();
+
+    // 位布局无效时其他项无法计算，直接报告
+    if let Err(e) = config.check_layout() {
+        if as_json {
+            println!("{}", json!({ "ok": false, "problems": [e.to_string()], "config_file": config_file }));
+        } else {
+            println!("配置文件:         {}", config_file.unwrap_or("(未找到)"));
+            println!("问题: {}", e);
+        }
+        return Err(CliError::Failed("配置检查发现1个问题".to_string()));
+    }
+
+    // 与全局生成器相同的方式分配Worker ID，失败时直接报告错误
+    // 不获取租约: 检查配置不应占用正在运行的实例可能需要的Worker ID
+    let (worker_id, strategy) = SnowflakeIdWorker::resolve_worker_id(Some(config.clone()))?;
+
+    let max_worker_id = (1u64 << config.worker_id_bits) - 1;
+    let mut problems = Vec::new();
+    if let Some(worker_id) = worker_id.filter(|&id| id as u64 > max_worker_id) {
+        problems.push(format!(
+            "Worker ID {} 超过 {} 位能表示的最大值 {}，会与时间戳位重叠",
+            worker_id, config.worker_id_bits, max_worker_id
+        ));
+    }
+
+    if as_json {
+        let report: Value = json!({
+            "ok": problems.is_empty(),
+            "problems": problems,
+            "worker_id": worker_id,
+            "strategy": strategy.as_str(),
+            "config_file": config_file,
+            "env_worker_id": env_worker_id,
+            "lease_dir": lease_dir,
+            "layout": {
+                "timestamp_bits": config.timestamp_bits(),
+                "worker_id_bits": config.worker_id_bits,
+                "sequence_bits": config.sequence_bits,
+                "max_worker_id": max_worker_id,
+                "ids_per_ms": 1u64 << config.sequence_bits,
+                "epoch_ms": TWEPOCH_MS,
+                "epoch": format_datetime(TWEPOCH_MS),
+                "max_timestamp_ms": config.max_timestamp_ms(),
+                "max_datetime": format_datetime(config.max_timestamp_ms()),
+            },
+            "max_backward_ms": config.max_backward_ms,
+            "sequence_exhausted_policy": format!("{:?}", config.sequence_exhausted_policy),
+            "sequence_start": format!("{:?}", config.sequence_start),
+        });
+        println!("{}", report);
+    } else {
+        match worker_id {
+            Some(worker_id) => println!("Worker ID:        {}", worker_id),
+            None => println!("Worker ID:        (启动时从租约目录获取)"),
+        }
+        println!("分配方式:         {}", strategy.as_str());
+        println!("配置文件:         {}", config_file.unwrap_or("(未找到)"));
+        println!("SNOWFLAKE_WORKER_ID: {}", env_worker_id.as_deref().unwrap_or("(未设置)"));
+        println!("{}: {}", LEASE_DIR_ENV, lease_dir.as_deref().unwrap_or("(未设置)"));
+        println!(
+            "位布局:           时间戳{}位 + Worker ID {}位 + 序列号{}位",
+            config.timestamp_bits(),
+            config.worker_id_bits,
+            config.sequence_bits
+        );
+        println!("Worker ID范围:    0-{}", max_worker_id);
+        println!("每毫秒容量:       {}", 1u64 << config.sequence_bits);
+        println!("基准时间:         {}", format_datetime(TWEPOCH_MS).unwrap_or_default());
+        println!("可用至:           {}", format_datetime(config.max_timestamp_ms()).unwrap_or_default());
+        println!("时钟回拨容忍:     {}ms", config.max_backward_ms);
+        println!("序列号用尽策略:   {:?}", config.sequence_exhausted_policy);
+        println!("序列号起始值:     {:?}", config.sequence_start);
+        for problem in &problems {
+            println!("问题: {}", problem);
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(CliError::Failed(format!("配置检查发现{}个问题", problems.len())));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// bounds
+// ============================================================================
+
+/// 解析时间参数: RFC 3339、Unix毫秒时间戳或now
+fn parse_time(value: &str) -> Result<DateTime<Utc>, CliError> {
+    if value == "now" {
+        return Ok(Utc::now());
+    }
+    if let Ok(ms) = value.parse::<i64>() {
+        return Utc
+            .timestamp_millis_opt(ms)
+            .single()
+            .ok_or_else(|| CliError::Usage(format!("无效的时间戳: {}", value)));
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| CliError::Usage(format!("无效的时间 {}: {}", value, e)))
+}
+
+/// 计算时间范围内的最小/最大ID
+fn cmd_bounds(args: &[String]) -> Result<(), CliError> {
+    let mut from = None;
+    let mut to = None;
+    let mut as_json = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--from" => from = Some(parse_time(option_value(&mut iter, arg)?)?),
+            "--to" => to = Some(parse_time(option_value(&mut iter, arg)?)?),
+            "--json" => as_json = true,
+            other => return Err(CliError::Usage(format!("bounds: 未知参数: {}", other))),
+        }
+    }
+    let from = from.ok_or_else(|| CliError::Usage("bounds: 缺少 --from".to_string()))?;
+    let to = to.ok_or_else(|| CliError::Usage("bounds: 缺少 --to".to_string()))?;
+    if from > to {
+        return Err(CliError::Usage("bounds: --from 不能晚于 --to".to_string()));
+    }
+
+    let (_, config) = load_config()?;
+    config.check_layout()?;
+    let min_id = config.min_id_for(&from)?;
+    let max_id = config.max_id_for(&to)?;
+
+    if as_json {
+        println!(
+            "{}",
+            json!({
+                "from": from.to_rfc3339(),
+                "to": to.to_rfc3339(),
+                "min_id": min_id.to_string(),
+                "max_id": max_id.to_string(),
+            })
+        );
+    } else {
+        println!("min_id: {}", min_id);
+        println!("max_id: {}", max_id);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// audit
+// ============================================================================
+
+/// 审计ID导出
+fn cmd_audit(args: &[String]) -> Result<(), CliError> {
+    let (_, layout) = load_config()?;
+    let mut config = AuditConfig { layout, ..Default::default() };
+    let mut force_binary = false;
+    let mut as_json = false;
+    let mut dumps: Vec<(String, &Path)> = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--binary" => force_binary = true,
+            "--json" => as_json = true,
+            "--chunk-size" => {
+                let value = option_value(&mut iter, arg)?;
+                config.chunk_size = match value.parse() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(CliError::Usage(format!("缓冲区大小必须为正整数: {}", value))),
+                };
+            }
+            "--future-tolerance-ms" => {
+                let value = option_value(&mut iter, arg)?;
+                config.future_tolerance_ms = value
+                    .parse()
+                    .map_err(|_| CliError::Usage(format!("容忍度必须为非负整数: {}", value)))?;
+            }
+            "--worker-id-bits" | "--sequence-bits" => {
+                let value = option_value(&mut iter, arg)?;
+                let bits = value
+                    .parse()
+                    .map_err(|_| CliError::Usage(format!("位数必须为0-255的整数: {}", value)))?;
+                if arg == "--worker-id-bits" {
+                    config.layout.worker_id_bits = bits;
+                } else {
+                    config.layout.sequence_bits = bits;
+                }
+            }
+            other if other.starts_with("--") => {
+                return Err(CliError::Usage(format!("audit: 未知参数: {}", other)));
+            }
+            dump => {
+                let (node, path) = match dump.split_once('=') {
+                    Some((node, path)) => (node.to_string(), Path::new(path)),
+                    None => {
+                        let path = Path::new(dump);
+                        let node = path.file_stem().unwrap_or(path.as_os_str()).to_string_lossy();
+                        (node.into_owned(), path)
+                    }
+                };
+                dumps.push((node, path));
+            }
+        }
+    }
+    if dumps.is_empty() {
+        return Err(CliError::Usage("audit: 缺少导出文件".to_string()));
+    }
+
+    let mut auditor = Auditor::new(Some(config))?;
+    for (node, path) in &dumps {
+        let format = if force_binary { DumpFormat::Binary } else { DumpFormat::from_path(path) };
+        auditor.add_dump(node, path, format)?;
+    }
+    let report = auditor.finish()?;
+
+    if as_json {
+        println!("{}", audit_json(&report));
+    } else {
+        print_audit(&report);
+    }
+
+    if !report.is_clean() {
+        return Err(CliError::Failed(format!(
+            "发现{}个重复ID、{}个未来ID",
+            report.duplicate_ids, report.future_ids
+        )));
+    }
+    Ok(())
+}
+
+/// 审计报告的JSON表示
+fn audit_json(report: &AuditReport) -> Value {
+    json!({
+        "clean": report.is_clean(),
+        "total_ids": report.total_ids,
+        "distinct_ids": report.distinct_ids,
+        "nodes": report.nodes.iter().map(|node| json!({
+            "name": node.name,
+            "ids": node.ids,
+            "invalid_records": node.invalid_records,
+            "workers": node.workers.iter().map(|w| json!({
+                "worker_id": w.worker_id,
+                "ids": w.ids,
+                "first": format_datetime(w.first_ms),
+                "last": format_datetime(w.last_ms),
+            })).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+        "duplicates": {
+            "ids": report.duplicate_ids,
+            "extra_occurrences": report.duplicate_occurrences,
+            "samples": report.duplicates.iter().map(|d| json!({
+                "id": d.id,
+                "occurrences": d.occurrences,
+                "nodes": d.nodes,
+            })).collect::<Vec<_>>(),
+        },
+        "future": {
+            "ids": report.future_ids,
+            "samples": report.future_samples.iter().map(|f| json!({
+                "node": f.node,
+                "id": f.id,
+                "datetime": format_datetime(f.timestamp_ms),
+            })).collect::<Vec<_>>(),
+        },
+        "non_monotonic": report.non_monotonic.iter().map(|r| json!({
+            "node": r.node,
+            "worker_id": r.worker_id,
+            "regressions": r.regressions,
+            "max_backward_ms": r.max_backward_ms,
+            "first": [r.first.0, r.first.1],
+        })).collect::<Vec<_>>(),
+        "saturation": {
+            "ids_per_ms_limit": report.ids_per_ms_limit,
+            "max_ids_per_ms": report.max_ids_per_ms,
+            "saturated_ticks": report.saturated_ticks,
+            "samples": report.saturated_samples.iter().map(|t| json!({
+                "worker_id": t.worker_id,
+                "datetime": format_datetime(t.timestamp_ms),
+                "ids": t.ids,
+            })).collect::<Vec<_>>(),
+        },
+    })
+}
+
+/// 输出文本格式的审计报告
+fn print_audit(report: &AuditReport) {
+    println!("ID总数:       {} (不同ID {})", report.total_ids, report.distinct_ids);
+    for node in &report.nodes {
+        let workers: Vec<String> = node.workers.iter().map(|w| w.worker_id.to_string()).collect();
+        println!(
+            "节点 {}: {}个ID，Worker ID [{}]，无法解析{}条",
+            node.name,
+            node.ids,
+            workers.join(", "),
+            node.invalid_records
+        );
+    }
+
+    println!("重复ID:       {} (多出{}次)", report.duplicate_ids, report.duplicate_occurrences);
+    for d in &report.duplicates {
+        println!("  {} 出现{}次，节点: {}", d.id, d.occurrences, d.nodes.join(", "));
+    }
+
+    println!("未来ID:       {}", report.future_ids);
+    for f in &report.future_samples {
+        println!(
+            "  {} 节点 {}，时间 {}",
+            f.id,
+            f.node,
+            format_datetime(f.timestamp_ms).unwrap_or_default()
+        );
+    }
+
+    println!("回退:         {}个(节点, Worker ID)", report.non_monotonic.len());
+    for r in &report.non_monotonic {
+        println!(
+            "  节点 {} Worker {}: 回退{}次，最大{}ms，首次 {} -> {}",
+            r.node, r.worker_id, r.regressions, r.max_backward_ms, r.first.0, r.first.1
+        );
+    }
+
+    println!(
+        "序列号饱和:   {}个毫秒 (每毫秒上限{}，最大{})",
+        report.saturated_ticks, report.ids_per_ms_limit, report.max_ids_per_ms
+    );
+    for t in &report.saturated_samples {
+        println!(
+            "  Worker {} 在 {}: {}个ID",
+            t.worker_id,
+            format_datetime(t.timestamp_ms).unwrap_or_default(),
+            t.ids
+        );
+    }
+}
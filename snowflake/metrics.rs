@@ -0,0 +1,153 @@
+/*!
+雪花算法生成器指标
+
+记录ID生成数量、时钟回拨、锁等待等运行指标，并渲染为Prometheus文本格式，
+可以挂到任意HTTP服务的 `/metrics` 接口上。
+
+指标分两类:
+1. 计数器: 由生成器在生成ID时原子累加，读取时不需要拿生成器的锁
+2. 快照: 读取时合并计数器、序列号容量统计和Worker信息，一次性渲染
+*/
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::{SequenceStats, WorkerIdStrategy};
+
+/// 生成器运行指标 (线程安全)
+/// 所有字段都是原子计数器，可以在不持有生成器锁的情况下读取
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// 成功生成的ID总数
+    ids_generated: AtomicU64,
+    /// 检测到的时钟回拨次数 (包括容忍范围内等待的和超出范围报错的)
+    clock_backward_events: AtomicU64,
+    /// 超出容忍范围而报错的时钟回拨次数
+    clock_backward_errors: AtomicU64,
+    /// 观察到的最大时钟回拨幅度 (毫秒)
+    clock_backward_max_ms: AtomicU64,
+    /// 获取生成器锁的次数
+    lock_acquisitions: AtomicU64,
+    /// 获取生成器锁的累计等待时间 (纳秒)
+    lock_wait_nanos: AtomicU64,
+    /// 获取生成器锁的最大等待时间 (纳秒)
+    lock_wait_max_nanos: AtomicU64,
+}
+
+impl Metrics {
+    /// 创建空的指标集
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录成功生成一个ID
+    pub(crate) fn record_id(&self) {
+        self.ids_generated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次时钟回拨
+    ///
+    /// 参数:
+    /// - diff_ms: 回拨幅度 (毫秒)
+    /// - rejected: 是否超出容忍范围而报错
+    pub(crate) fn record_clock_backward(&self, diff_ms: u64, rejected: bool) {
+        self.clock_backward_events.fetch_add(1, Ordering::Relaxed);
+        if rejected {
+            self.clock_backward_errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.clock_backward_max_ms.fetch_max(diff_ms, Ordering::Relaxed);
+    }
+
+    /// 记录一次获取生成器锁的等待时间
+    pub(crate) fn record_lock_wait(&self, wait: Duration) {
+        let nanos = wait.as_nanos() as u64;
+        self.lock_acquisitions.fetch_add(1, Ordering::Relaxed);
+        self.lock_wait_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.lock_wait_max_nanos.fetch_max(nanos, Ordering::Relaxed);
+    }
+}
+
+/// 指标快照
+/// 某一时刻生成器全部指标的只读副本
+#[derive(Debug, Clone)]
+pub struct MetricsSnapshot {
+    /// 当前Worker ID
+    pub worker_id: u8,
+    /// Worker ID的分配方式
+    pub strategy: WorkerIdStrategy,
+    /// 成功生成的ID总数
+    pub ids_generated: u64,
+    /// 序列号容量统计 (用尽、等待、借用等)
+    pub sequence: SequenceStats,
+    /// 检测到的时钟回拨次数
+    pub clock_backward_events: u64,
+    /// 超出容忍范围而报错的时钟回拨次数
+    pub clock_backward_errors: u64,
+    /// 观察到的最大时钟回拨幅度 (毫秒)
+    pub clock_backward_max_ms: u64,
+    /// 获取生成器锁的次数
+    pub lock_acquisitions: u64,
+    /// 获取生成器锁的累计等待时间
+    pub lock_wait_total: Duration,
+    /// 获取生成器锁的最大等待时间
+    pub lock_wait_max: Duration,
+}
+
+impl MetricsSnapshot {
+    /// 合并计数器、序列号统计和Worker信息生成快照
+    pub(crate) fn new(metrics: &Metrics, sequence: SequenceStats, worker_id: u8, strategy: WorkerIdStrategy) -> Self {
+        Self {
+            worker_id,
+            strategy,
+            ids_generated: metrics.ids_generated.load(Ordering::Relaxed),
+            sequence,
+            clock_backward_events: metrics.clock_backward_events.load(Ordering::Relaxed),
+            clock_backward_errors: metrics.clock_backward_errors.load(Ordering::Relaxed),
+            clock_backward_max_ms: metrics.clock_backward_max_ms.load(Ordering::Relaxed),
+            lock_acquisitions: metrics.lock_acquisitions.load(Ordering::Relaxed),
+            lock_wait_total: Duration::from_nanos(metrics.lock_wait_nanos.load(Ordering::Relaxed)),
+            lock_wait_max: Duration::from_nanos(metrics.lock_wait_max_nanos.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// 渲染为Prometheus文本格式 (text/plain; version=0.0.4)
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let seq = &self.sequence;
+
+        write_metric(&mut out, "snowflake_ids_generated_total", "counter", "成功生成的ID总数", self.ids_generated);
+        write_metric(&mut out, "snowflake_sequence_active_ticks_total", "counter", "至少生成过一个ID的毫秒数", seq.active_ticks);
+        write_metric(&mut out, "snowflake_sequence_exhausted_ticks_total", "counter", "序列号被用尽的毫秒数", seq.exhausted_ticks);
+        write_metric(&mut out, "snowflake_sequence_exhausted_waits_total", "counter", "因序列号用尽而等待下一毫秒的次数", seq.wait_count);
+        write_metric(&mut out, "snowflake_sequence_exhausted_wait_seconds_total", "counter", "因序列号用尽而等待的累计时间", seq.wait_micros as f64 / 1e6);
+        write_metric(&mut out, "snowflake_sequence_exhausted_rejections_total", "counter", "因序列号用尽而返回错误的次数", seq.rejected_count);
+        write_metric(&mut out, "snowflake_sequence_borrowed_ticks_total", "counter", "借用未来毫秒的次数", seq.borrowed_ticks);
+        write_metric(&mut out, "snowflake_clock_backward_events_total", "counter", "检测到的时钟回拨次数", self.clock_backward_events);
+        write_metric(&mut out, "snowflake_clock_backward_errors_total", "counter", "超出容忍范围而报错的时钟回拨次数", self.clock_backward_errors);
+        write_metric(&mut out, "snowflake_clock_backward_max_ms", "gauge", "观察到的最大时钟回拨幅度(毫秒)", self.clock_backward_max_ms);
+        write_metric(&mut out, "snowflake_lock_acquisitions_total", "counter", "获取生成器锁的次数", self.lock_acquisitions);
+        write_metric(&mut out, "snowflake_lock_wait_seconds_total", "counter", "获取生成器锁的累计等待时间", self.lock_wait_total.as_secs_f64());
+        write_metric(&mut out, "snowflake_lock_wait_max_seconds", "gauge", "获取生成器锁的最大等待时间", self.lock_wait_max.as_secs_f64());
+        write_metric(&mut out, "snowflake_worker_id", "gauge", "当前Worker ID", self.worker_id);
+
+        // Worker信息以标签形式输出，值固定为1
+        let _ = writeln!(out, "# HELP snowflake_worker_info Worker ID及其分配方式");
+        let _ = writeln!(out, "# TYPE snowflake_worker_info gauge");
+        let _ = writeln!(
+            out,
+            "snowflake_worker_info{{worker_id=\"{}\",strategy=\"{}\"}} 1",
+            self.worker_id,
+            self.strategy.as_str()
+        );
+
+        out
+    }
+}
+
+/// 写入一个不带标签的指标 (HELP、TYPE和值各一行)
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
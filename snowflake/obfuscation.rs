@@ -0,0 +1,221 @@
+/*!
+对外ID混淆
+
+雪花ID直接暴露了生成时间、Worker ID和每毫秒的发号量。对外展示时可以用带密钥的
+Feistel网络把ID置换成看不出规律的公开ID，服务端再用同一个密钥还原:
+- 置换: 64位Feistel网络 (8轮)，对任意密钥都是u64上的双射，不会产生冲突
+- 公开ID: 1个字符的密钥版本号 + 11个字符的base62 (共12个字符)
+- 密钥轮换: 新ID使用当前密钥，历史密钥只用于还原，旧的公开ID在轮换后依然有效
+
+注意: 这是混淆而不是加密。它能防止随手推算出发号量，但不能替代访问控制。
+*/
+
+use std::fmt;
+
+use sha2::{Digest, Sha256};
+
+use crate::encoding::{base62_char, base62_digit, decode_base62, encode_base62, DecodeError, BASE62_WIDTH};
+use crate::SnowflakeError;
+
+/// Feistel网络轮数
+const ROUNDS: usize = 8;
+
+/// 密钥版本号上限 (版本号用一个base62字符表示)
+pub const MAX_KEY_VERSION: u8 = 61;
+
+/// 公开ID的长度: 版本号 + base62
+pub const PUBLIC_ID_WIDTH: usize = 1 + BASE62_WIDTH;
+
+/// 混淆密钥
+#[derive(Clone)]
+pub struct ObfuscationKey {
+    /// 密钥版本号 (0-61)
+    version: u8,
+    /// 每轮的轮密钥
+    round_keys: [u64; ROUNDS],
+}
+
+impl ObfuscationKey {
+    /// 由版本号和密钥材料创建密钥
+    ///
+    /// 轮密钥由 SHA-256(版本号 || 轮次 || secret) 派生，同样的版本号和secret总是得到同样的置换
+    ///
+    /// 返回:
+    /// - Err(SnowflakeError::ConfigError): 版本号超过61或secret为空
+    pub fn new(version: u8, secret: &[u8]) -> Result<Self, SnowflakeError> {
+        if version > MAX_KEY_VERSION {
+            return Err(SnowflakeError::ConfigError(format!(
+                "密钥版本号{}超过上限{}",
+                version, MAX_KEY_VERSION
+            )));
+        }
+        if secret.is_empty() {
+            return Err(SnowflakeError::ConfigError("密钥不能为空".to_string()));
+        }
+
+        let mut round_keys = [0u64; ROUNDS];
+        for (round, key) in round_keys.iter_mut().enumerate() {
+            let digest = Sha256::new()
+                .chain_update([version, round as u8])
+                .chain_update(secret)
+                .finalize();
+            *key = u64::from_be_bytes(digest[..8].try_into().unwrap());
+        }
+        Ok(Self { version, round_keys })
+    }
+
+    /// 密钥版本号
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// 置换ID (不带版本号)
+    pub fn permute(&self, id: u64) -> u64 {
+        let (mut left, mut right) = ((id >> 32) as u32, id as u32);
+        for &key in &self.round_keys {
+            (left, right) = (right, left ^ round_function(right, key));
+        }
+        ((left as u64) << 32) | right as u64
+    }
+
+    /// 还原置换前的ID
+    pub fn unpermute(&self, value: u64) -> u64 {
+        let (mut left, mut right) = ((value >> 32) as u32, value as u32);
+        for &key in self.round_keys.iter().rev() {
+            (left, right) = (right ^ round_function(left, key), left);
+        }
+        ((left as u64) << 32) | right as u64
+    }
+}
+
+/// 不输出轮密钥
+impl fmt::Debug for ObfuscationKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObfuscationKey")
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Feistel轮函数: 半块与轮密钥混合后做splitmix64终结变换，取高32位
+/// 轮函数本身不需要可逆，Feistel结构保证整体可逆
+fn round_function(half: u32, key: u64) -> u32 {
+    let mut x = half as u64 ^ key;
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    (x >> 32) as u32
+}
+
+/// 公开ID解析错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevealError {
+    /// 格式错误 (长度或字符不合法)
+    Decode(DecodeError),
+    /// 公开ID使用的密钥版本不存在 (可能是已删除的历史密钥)
+    UnknownKeyVersion(u8),
+}
+
+impl fmt::Display for RevealError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RevealError::Decode(e) => write!(f, "公开ID格式错误: {}", e),
+            RevealError::UnknownKeyVersion(v) => write!(f, "未知的密钥版本: {}", v),
+        }
+    }
+}
+
+impl std::error::Error for RevealError {}
+
+/// ID混淆器 (当前密钥 + 历史密钥)
+#[derive(Debug, Clone)]
+pub struct Obfuscator {
+    /// 用于生成公开ID的密钥
+    current: ObfuscationKey,
+    /// 只用于还原的历史密钥
+    previous: Vec<ObfuscationKey>,
+}
+
+impl Obfuscator {
+    /// 创建混淆器
+    ///
+    /// 参数:
+    /// - current: 当前密钥
+    /// - previous: 历史密钥 (轮换前的公开ID需要用它们还原)
+    ///
+    /// 返回:
+    /// - Err(SnowflakeError::ConfigError): 密钥版本号重复
+    pub fn new(current: ObfuscationKey, previous: Vec<ObfuscationKey>) -> Result<Self, SnowflakeError> {
+        let mut obfuscator = Self {
+            current,
+            previous: Vec::with_capacity(previous.len()),
+        };
+        for key in previous {
+            obfuscator.check_version(key.version)?;
+            obfuscator.previous.push(key);
+        }
+        Ok(obfuscator)
+    }
+
+    /// 轮换密钥: 新密钥成为当前密钥，原当前密钥转为历史密钥
+    pub fn rotate(&mut self, key: ObfuscationKey) -> Result<(), SnowflakeError> {
+        self.check_version(key.version)?;
+        let old = std::mem::replace(&mut self.current, key);
+        self.previous.push(old);
+        Ok(())
+    }
+
+    /// 当前密钥的版本号
+    pub fn current_version(&self) -> u8 {
+        self.current.version
+    }
+
+    /// 把ID转换为公开ID (使用当前密钥)
+    pub fn obfuscate(&self, id: u64) -> String {
+        let mut public = String::with_capacity(PUBLIC_ID_WIDTH);
+        public.push(base62_char(self.current.version));
+        public.push_str(&encode_base62(self.current.permute(id)));
+        public
+    }
+
+    /// 把公开ID还原为ID (按公开ID中的版本号选择密钥)
+    pub fn reveal(&self, public: &str) -> Result<u64, RevealError> {
+        let actual = public.chars().count();
+        if actual != PUBLIC_ID_WIDTH {
+            return Err(RevealError::Decode(DecodeError::InvalidLength {
+                expected: PUBLIC_ID_WIDTH,
+                actual,
+            }));
+        }
+
+        let mut chars = public.chars();
+        let ch = chars.next().expect("长度已校验");
+        let version = base62_digit(ch)
+            .ok_or(RevealError::Decode(DecodeError::InvalidChar { ch, position: 0 }))? as u8;
+        let key = self.key(version).ok_or(RevealError::UnknownKeyVersion(version))?;
+
+        // 错误位置换算为整个公开ID中的下标
+        let value = decode_base62(chars.as_str()).map_err(|e| match e {
+            DecodeError::InvalidChar { ch, position } => {
+                RevealError::Decode(DecodeError::InvalidChar { ch, position: position + 1 })
+            }
+            other => RevealError::Decode(other),
+        })?;
+        Ok(key.unpermute(value))
+    }
+
+    /// 按版本号查找密钥
+    fn key(&self, version: u8) -> Option<&ObfuscationKey> {
+        std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|key| key.version == version)
+    }
+
+    /// 检查版本号是否已被占用
+    fn check_version(&self, version: u8) -> Result<(), SnowflakeError> {
+        if self.key(version).is_some() {
+            return Err(SnowflakeError::ConfigError(format!("密钥版本号{}重复", version)));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,453 @@
+/*!
+多进程唯一性测试
+
+`stress_test` 只在一个进程内用多个线程共享全局生成器，测不到Worker ID分配的问题。
+本程序为每种Worker ID分配方式启动一轮子进程 (子进程就是本程序自身的 child 模式):
+
+- env: 每个子进程设置不同的 SNOWFLAKE_WORKER_ID
+- config: 每个子进程在各自的工作目录中放一个 snowflake.toml (不同的 datacenter_id/machine_id)
+- lease: 所有子进程共用一个 SNOWFLAKE_WORKER_LEASE_DIR，由文件锁分配Worker ID
+
+子进程先分配Worker ID并报告给父进程，等所有子进程都就绪后同时开始生成ID并写入二进制导出文件。
+父进程检查:
+1. 每个子进程使用了预期的分配方式和Worker ID (lease方式下各不相同)
+2. 每个子进程导出的ID都带有它报告的Worker ID
+3. 所有子进程的ID合并后没有重复 (使用 `snowflake::audit` 做外部排序合并)
+
+发现问题时退出码为1，参数错误时为2。
+*/
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Command, Stdio};
+
+use serde_json::{json, Value};
+use snowflake::audit::{Auditor, DumpFormat};
+use snowflake::lease::LEASE_DIR_ENV;
+use snowflake::{get_next_ids, get_worker_info, init_logging, SnowflakeConfig};
+
+/// 用法说明
+const USAGE: &str = "\
+用法: process_harness [选项]
+
+选项:
+  --processes N         每轮启动的子进程数 (默认 8，最多 256)
+  --ids N               每个子进程生成的ID数 (默认 100000)
+  --strategies LIST     Worker ID分配方式 env,config,lease (默认全部)
+  --work-dir DIR        工作目录 (默认使用临时目录并在结束后删除；指定时保留导出文件)
+  --json                输出JSON报告
+  -h, --help            显示帮助";
+
+/// 子进程单次批量生成的ID数
+const CHUNK: usize = 10_000;
+
+/// Worker ID分配方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Env,
+    Config,
+    Lease,
+}
+
+impl Strategy {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "env" => Ok(Strategy::Env),
+            "config" => Ok(Strategy::Config),
+            "lease" => Ok(Strategy::Lease),
+            other => Err(format!("未知分配方式: {}", other)),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Strategy::Env => "env",
+            Strategy::Config => "config",
+            Strategy::Lease => "lease",
+        }
+    }
+}
+
+/// 测试参数
+#[derive(Debug)]
+struct Options {
+    processes: usize,
+    ids: usize,
+    strategies: Vec<Strategy>,
+    work_dir: Option<PathBuf>,
+    json: bool,
+}
+
+impl Options {
+    /// 解析命令行参数，返回None表示只需显示帮助
+    fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let mut options = Options {
+            processes: 8,
+            ids: 100_000,
+            strategies: vec![Strategy::Env, Strategy::Config, Strategy::Lease],
+            work_dir: None,
+            json: false,
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || iter.next().ok_or_else(|| format!("{} 缺少参数值", arg));
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--json" => options.json = true,
+                "--processes" => options.processes = parse_positive(arg, value()?)?,
+                "--ids" => options.ids = parse_positive(arg, value()?)?,
+                "--strategies" => {
+                    options.strategies = value()?
+                        .split(',')
+                        .filter(|item| !item.is_empty())
+                        .map(Strategy::parse)
+                        .collect::<Result<_, _>>()?;
+                }
+                "--work-dir" => options.work_dir = Some(PathBuf::from(value()?)),
+                other => return Err(format!("未知参数: {}", other)),
+            }
+        }
+        if options.processes > 256 {
+            return Err("子进程数不能超过Worker ID数量 (256)".to_string());
+        }
+        if options.strategies.is_empty() {
+            return Err("至少需要一种分配方式".to_string());
+        }
+        Ok(Some(options))
+    }
+}
+
+fn parse_positive(name: &str, value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("{} 必须为正整数: {}", name, value)),
+    }
+}
+
+// ============================================================================
+// 子进程
+// ============================================================================
+
+/// 子进程: 分配Worker ID并报告，收到父进程的开始信号后生成ID写入导出文件
+fn run_child(args: &[String]) -> Result<(), String> {
+    let mut ids = None;
+    let mut out = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = iter.next().ok_or_else(|| format!("{} 缺少参数值", arg))?;
+        match arg.as_str() {
+            "--ids" => ids = Some(parse_positive(arg, value)?),
+            "--out" => out = Some(PathBuf::from(value)),
+            other => return Err(format!("未知参数: {}", other)),
+        }
+    }
+    let ids = ids.ok_or("缺少 --ids")?;
+    let out = out.ok_or("缺少 --out")?;
+
+    // 第一次调用时初始化全局生成器，Worker ID在此时分配 (lease方式下持有租约直到进程退出)
+    let (worker_id, strategy) = get_worker_info();
+    println!(
+        "{}",
+        json!({ "pid": process::id(), "worker_id": worker_id, "strategy": strategy.as_str() })
+    );
+    io::stdout().flush().map_err(|e| e.to_string())?;
+
+    // 等待开始信号 (父进程关闭stdin)
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).map_err(|e| e.to_string())?;
+
+    let file = File::create(&out).map_err(|e| format!("无法创建 {}: {}", out.display(), e))?;
+    let mut writer = BufWriter::new(file);
+    let mut remaining = ids;
+    while remaining > 0 {
+        let chunk = remaining.min(CHUNK);
+        for id in get_next_ids(chunk).map_err(|e| e.to_string())? {
+            writer.write_all(&id.to_be_bytes()).map_err(|e| e.to_string())?;
+        }
+        remaining -= chunk;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// 父进程
+// ============================================================================
+
+/// 一个子进程的结果
+#[derive(Debug)]
+struct ChildReport {
+    node: String,
+    pid: u64,
+    worker_id: u64,
+    strategy: String,
+    expected_worker_id: Option<u64>,
+}
+
+/// 一轮测试的结果
+#[derive(Debug)]
+struct RoundReport {
+    strategy: Strategy,
+    children: Vec<ChildReport>,
+    total_ids: u64,
+    duplicate_ids: u64,
+    problems: Vec<String>,
+}
+
+impl RoundReport {
+    fn to_json(&self) -> Value {
+        json!({
+            "strategy": self.strategy.as_str(),
+            "passed": self.problems.is_empty(),
+            "total_ids": self.total_ids,
+            "duplicate_ids": self.duplicate_ids,
+            "problems": self.problems,
+            "children": self.children.iter().map(|c| json!({
+                "node": c.node,
+                "pid": c.pid,
+                "worker_id": c.worker_id,
+                "strategy": c.strategy,
+                "expected_worker_id": c.expected_worker_id,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    fn print(&self) {
+        let status = if self.problems.is_empty() { "✅" } else { "❌" };
+        let workers: Vec<String> = self.children.iter().map(|c| c.worker_id.to_string()).collect();
+        println!(
+            "{} {}: {}个子进程，{}个ID，重复{}个，Worker ID [{}]",
+            status,
+            self.strategy.as_str(),
+            self.children.len(),
+            self.total_ids,
+            self.duplicate_ids,
+            workers.join(", ")
+        );
+        for problem in &self.problems {
+            println!("   问题: {}", problem);
+        }
+    }
+}
+
+/// 运行一轮: 用指定的分配方式启动所有子进程并检查结果
+fn run_round(options: &Options, strategy: Strategy, dir: &Path) -> Result<RoundReport, String> {
+    let exe = env::current_exe().map_err(|e| format!("无法定位程序自身: {}", e))?;
+    let round_dir = dir.join(strategy.as_str());
+    let lease_dir = round_dir.join("leases");
+    fs::create_dir_all(&round_dir).map_err(|e| format!("无法创建 {}: {}", round_dir.display(), e))?;
+
+    let mut children: Vec<(String, Option<u64>, PathBuf, Child)> = Vec::new();
+    for i in 0..options.processes {
+        let node = format!("{}-{}", strategy.as_str(), i);
+        let node_dir = round_dir.join(&node);
+        fs::create_dir_all(&node_dir).map_err(|e| format!("无法创建 {}: {}", node_dir.display(), e))?;
+        let out = node_dir.join("ids.bin");
+
+        let mut command = Command::new(&exe);
+        command
+            .arg("child")
+            .arg("--ids")
+            .arg(options.ids.to_string())
+            .arg("--out")
+            .arg(&out)
+            .current_dir(&node_dir)
+            .env_remove("SNOWFLAKE_WORKER_ID")
+            .env_remove(LEASE_DIR_ENV)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+        if env::var_os("RUST_LOG").is_none() {
+            command.env("RUST_LOG", "warn");
+        }
+
+        let expected = match strategy {
+            Strategy::Env => {
+                command.env("SNOWFLAKE_WORKER_ID", i.to_string());
+                Some(i as u64)
+            }
+            Strategy::Config => {
+                // 与配置文件映射规则一致: Worker ID = (datacenter_id << 6) | machine_id
+                let (datacenter_id, machine_id) = (i / 64, i % 64);
+                let config = format!("datacenter_id = {}\nmachine_id = {}\n", datacenter_id, machine_id);
+                fs::write(node_dir.join("snowflake.toml"), config).map_err(|e| e.to_string())?;
+                Some((datacenter_id << 6 | machine_id) as u64)
+            }
+            Strategy::Lease => {
+                command.env(LEASE_DIR_ENV, &lease_dir);
+                None
+            }
+        };
+
+        let child = command.spawn().map_err(|e| format!("无法启动子进程: {}", e))?;
+        children.push((node, expected, out, child));
+    }
+
+    // 读取每个子进程报告的Worker ID (此时所有子进程都已分配好并持有各自的Worker ID)
+    let mut reports = Vec::new();
+    let mut problems = Vec::new();
+    for (node, expected, _, child) in &mut children {
+        let mut line = String::new();
+        let stdout = child.stdout.as_mut().expect("stdout已设置为管道");
+        BufReader::new(stdout).read_line(&mut line).map_err(|e| e.to_string())?;
+        let header: Value = match serde_json::from_str(&line) {
+            Ok(header) => header,
+            Err(_) => {
+                problems.push(format!("{}: 子进程没有报告Worker ID", node));
+                continue;
+            }
+        };
+        reports.push(ChildReport {
+            node: node.clone(),
+            pid: header["pid"].as_u64().unwrap_or_default(),
+            worker_id: header["worker_id"].as_u64().unwrap_or_default(),
+            strategy: header["strategy"].as_str().unwrap_or_default().to_string(),
+            expected_worker_id: *expected,
+        });
+    }
+
+    // 同时开始生成
+    for (_, _, _, child) in &mut children {
+        drop(child.stdin.take());
+    }
+    for (node, _, _, child) in &mut children {
+        let status = child.wait().map_err(|e| e.to_string())?;
+        if !status.success() {
+            problems.push(format!("{}: 子进程异常退出 ({})", node, status));
+        }
+    }
+
+    // 检查分配方式和Worker ID
+    for report in &reports {
+        if report.strategy != strategy.as_str() {
+            problems.push(format!("{}: 分配方式为 {}，预期为 {}", report.node, report.strategy, strategy.as_str()));
+        }
+        if let Some(expected) = report.expected_worker_id {
+            if report.worker_id != expected {
+                problems.push(format!("{}: Worker ID为 {}，预期为 {}", report.node, report.worker_id, expected));
+            }
+        }
+    }
+    let mut worker_ids: Vec<u64> = reports.iter().map(|r| r.worker_id).collect();
+    worker_ids.sort_unstable();
+    if worker_ids.windows(2).any(|w| w[0] == w[1]) {
+        problems.push(format!("多个子进程使用了相同的Worker ID: {:?}", worker_ids));
+    }
+
+    // 合并检查唯一性，并确认每个子进程的ID都带有它报告的Worker ID
+    let mut auditor = Auditor::new(None).map_err(|e| e.to_string())?;
+    for (node, _, out, _) in &children {
+        if out.exists() {
+            auditor.add_dump(node, out, DumpFormat::Binary).map_err(|e| e.to_string())?;
+        }
+    }
+    let audit = auditor.finish().map_err(|e| e.to_string())?;
+    for node in &audit.nodes {
+        let reported = reports.iter().find(|r| r.node == node.name).map(|r| r.worker_id);
+        let used: Vec<u64> = node.workers.iter().map(|w| w.worker_id).collect();
+        if used.len() != 1 || Some(used[0]) != reported {
+            problems.push(format!("{}: ID中的Worker ID为 {:?}，子进程报告的是 {:?}", node.name, used, reported));
+        }
+        if node.ids != options.ids as u64 {
+            problems.push(format!("{}: 导出了{}个ID，预期{}个", node.name, node.ids, options.ids));
+        }
+    }
+    if audit.duplicate_ids > 0 {
+        problems.push(format!("发现{}个重复ID", audit.duplicate_ids));
+    }
+    if !audit.non_monotonic.is_empty() {
+        problems.push(format!("{}个子进程的ID没有严格递增", audit.non_monotonic.len()));
+    }
+
+    Ok(RoundReport {
+        strategy,
+        children: reports,
+        total_ids: audit.total_ids,
+        duplicate_ids: audit.duplicate_ids,
+        problems,
+    })
+}
+
+fn run(options: &Options) -> Result<Vec<RoundReport>, String> {
+    let (dir, keep) = match &options.work_dir {
+        Some(dir) => (dir.clone(), true),
+        None => (env::temp_dir().join(format!("snowflake-process-harness-{}", process::id())), false),
+    };
+    fs::create_dir_all(&dir).map_err(|e| format!("无法创建 {}: {}", dir.display(), e))?;
+
+    let layout = SnowflakeConfig::default();
+    if !options.json {
+        println!(
+            "🧪 多进程唯一性测试: 每轮{}个子进程 × {}个ID (Worker ID {}位)",
+            options.processes, options.ids, layout.worker_id_bits
+        );
+    }
+
+    let mut rounds = Vec::new();
+    for &strategy in &options.strategies {
+        let round = run_round(options, strategy, &dir);
+        if let Ok(report) = &round {
+            if !options.json {
+                report.print();
+            }
+        }
+        rounds.push(round);
+    }
+
+    if !keep {
+        let _ = fs::remove_dir_all(&dir);
+    }
+    rounds.into_iter().collect()
+}
+
+fn main() {
+    init_logging();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("child") {
+        if let Err(msg) = run_child(&args[1..]) {
+            eprintln!("错误: {}", msg);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let options = match Options::parse(&args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(msg) => {
+            eprintln!("{}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let rounds = match run(&options) {
+        Ok(rounds) => rounds,
+        Err(msg) => {
+            eprintln!("错误: {}", msg);
+            process::exit(1);
+        }
+    };
+    let passed = rounds.iter().all(|round| round.problems.is_empty());
+
+    if options.json {
+        println!(
+            "{}",
+            json!({
+                "passed": passed,
+                "processes": options.processes,
+                "ids_per_process": options.ids,
+                "rounds": rounds.iter().map(RoundReport::to_json).collect::<Vec<_>>(),
+            })
+        );
+    } else if passed {
+        println!("🎉 所有子进程的ID全局唯一");
+    }
+    if !passed {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,407 @@
+/*!
+二进制发号协议
+
+HTTP的开销在每秒数千次取号的场景下占了大头，这里提供一个基于TCP的紧凑二进制协议。
+
+帧格式 (所有整数均为大端序):
++----------------+------------------+
+| 长度 (u32)     | 负载 (长度字节)   |
++----------------+------------------+
+
+请求负载: 需要的ID数量 (u32)，固定4字节
+响应负载:
+- 成功: 状态码0 (u8) + count个ID (每个u64)
+- 失败: 错误码 (u8) + UTF-8错误信息
+
+流水线: 客户端可以连续发送多个请求而不等待响应，服务端按请求顺序依次返回响应。
+*/
+
+use std::collections::VecDeque;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+
+use tracing::{debug, warn};
+
+use crate::{get_next_ids, SnowflakeError};
+
+/// 请求负载长度 (一个u32)
+const REQUEST_LEN: u32 = 4;
+
+/// 响应帧的最大长度 (64MB)，防止异常长度导致客户端分配过多内存
+const MAX_RESPONSE_LEN: u32 = 64 * 1024 * 1024;
+
+/// 单次请求最多能获取的ID数 (成功响应为1字节状态码 + 每个ID 8字节，不能超过响应帧的最大长度)
+pub const MAX_COUNT: u32 = (MAX_RESPONSE_LEN - 1) / 8;
+
+/// 响应状态码
+/// 错误码与 `SnowflakeError` 的变体一一对应，客户端可以还原出相同的错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Status {
+    /// 成功
+    Ok = 0,
+    /// 时钟回拨
+    ClockBackward = 1,
+    /// 配置错误
+    ConfigError = 2,
+    /// 网络错误
+    NetworkError = 3,
+    /// 序列号用尽
+    SequenceExhausted = 4,
+    /// 请求不合法 (数量为0、超过上限、帧格式错误)
+    ProtocolError = 5,
+    /// 时间超出范围
+    TimeOutOfRange = 6,
+    /// IO错误
+    IoError = 7,
+}
+
+impl Status {
+    /// 从字节解析状态码
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Status::Ok),
+            1 => Some(Status::ClockBackward),
+            2 => Some(Status::ConfigError),
+            3 => Some(Status::NetworkError),
+            4 => Some(Status::SequenceExhausted),
+            5 => Some(Status::ProtocolError),
+            6 => Some(Status::TimeOutOfRange),
+            7 => Some(Status::IoError),
+            _ => None,
+        }
+    }
+
+    /// 错误对应的状态码
+    fn of_error(e: &SnowflakeError) -> Self {
+        match e {
+            SnowflakeError::ClockBackward(_) => Status::ClockBackward,
+            SnowflakeError::ConfigError(_) => Status::ConfigError,
+            SnowflakeError::NetworkError(_) => Status::NetworkError,
+            SnowflakeError::SequenceExhausted(_) => Status::SequenceExhausted,
+            SnowflakeError::ProtocolError(_) => Status::ProtocolError,
+            SnowflakeError::TimeOutOfRange(_) => Status::TimeOutOfRange,
+            SnowflakeError::IoError(_) => Status::IoError,
+        }
+    }
+
+    /// 把错误码和错误信息还原为 `SnowflakeError`
+    fn into_error(self, msg: String) -> SnowflakeError {
+        match self {
+            Status::ClockBackward => SnowflakeError::ClockBackward(msg),
+            Status::ConfigError => SnowflakeError::ConfigError(msg),
+            Status::NetworkError => SnowflakeError::NetworkError(msg),
+            Status::SequenceExhausted => SnowflakeError::SequenceExhausted(msg),
+            Status::TimeOutOfRange => SnowflakeError::TimeOutOfRange(msg),
+            Status::IoError => SnowflakeError::IoError(msg),
+            Status::Ok | Status::ProtocolError => SnowflakeError::ProtocolError(msg),
+        }
+    }
+}
+
+/// 错误的原始信息 (不含Display的类型前缀，客户端还原时会重新加上)
+fn error_message(e: &SnowflakeError) -> &str {
+    match e {
+        SnowflakeError::ClockBackward(msg)
+        | SnowflakeError::ConfigError(msg)
+        | SnowflakeError::NetworkError(msg)
+        | SnowflakeError::SequenceExhausted(msg)
+        | SnowflakeError::ProtocolError(msg)
+        | SnowflakeError::TimeOutOfRange(msg)
+        | SnowflakeError::IoError(msg) => msg,
+    }
+}
+
+// ============================================================================
+// 帧读写
+// ============================================================================
+
+/// 读取一帧，返回负载
+/// 长度超过max_len时返回InvalidData错误
+fn read_frame(reader: &mut impl Read, max_len: u32) -> io::Result<Vec<u8>> {
+    let mut len_buf = [0u8; 4];
+    reader.read_exact(&mut len_buf)?;
+    let len = u32::from_be_bytes(len_buf);
+    if len > max_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("帧长度{}超过上限{}", len, max_len),
+        ));
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+/// 写入一帧 (长度前缀 + 负载)
+fn write_frame(writer: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+    writer.write_all(payload)
+}
+
+/// 编码成功响应
+fn encode_ids(ids: &[u64]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(1 + ids.len() * 8);
+    payload.push(Status::Ok as u8);
+    for id in ids {
+        payload.extend_from_slice(&id.to_be_bytes());
+    }
+    payload
+}
+
+/// 编码错误响应
+fn encode_error(status: Status, msg: &str) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(1 + msg.len());
+    payload.push(status as u8);
+    payload.extend_from_slice(msg.as_bytes());
+    payload
+}
+
+/// 解码响应负载
+///
+/// 返回:
+/// - Err(SnowflakeError::ProtocolError): 响应格式错误 (空响应、未知状态码、长度不是8的整数倍)
+/// - Ok(Err(SnowflakeError)): 服务端返回的错误
+/// - Ok(Ok(ids)): 服务端返回的ID
+fn decode_response(payload: &[u8]) -> Result<Result<Vec<u64>, SnowflakeError>, SnowflakeError> {
+    let (&code, body) = payload
+        .split_first()
+        .ok_or_else(|| SnowflakeError::ProtocolError("空响应".to_string()))?;
+    let status = Status::from_u8(code)
+        .ok_or_else(|| SnowflakeError::ProtocolError(format!("未知状态码: {}", code)))?;
+
+    if status != Status::Ok {
+        return Ok(Err(status.into_error(String::from_utf8_lossy(body).into_owned())));
+    }
+    if body.len() % 8 != 0 {
+        return Err(SnowflakeError::ProtocolError(format!("响应长度{}不是8的整数倍", body.len())));
+    }
+    Ok(Ok(body
+        .chunks_exact(8)
+        .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+        .collect()))
+}
+
+// ============================================================================
+// 服务端
+// ============================================================================
+
+/// 运行二进制发号服务 (阻塞当前线程)
+///
+/// 每个连接一个线程，使用全局生成器发号。
+///
+/// 参数:
+/// - listener: 已绑定的TCP监听器
+/// - max_count: 单次请求最多生成的ID数 (超过 `MAX_COUNT` 时按 `MAX_COUNT` 处理)
+pub fn serve(listener: TcpListener, max_count: u32) -> io::Result<()> {
+    if max_count > MAX_COUNT {
+        warn!(max_count, limit = MAX_COUNT, "最大批量超过响应帧能容纳的ID数，已调整");
+    }
+    let max_count = max_count.min(MAX_COUNT);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                thread::spawn(move || {
+                    let peer = stream.peer_addr().ok();
+                    if let Err(e) = handle_connection(stream, max_count) {
+                        debug!(peer = ?peer, error = %e, "二进制协议连接异常关闭");
+                    }
+                });
+            }
+            Err(e) => warn!(error = %e, "接受二进制协议连接失败"),
+        }
+    }
+    Ok(())
+}
+
+/// 处理一个连接上的所有请求
+/// 流水线请求会被连续处理，读缓冲区清空后才刷新写缓冲区，减少系统调用
+fn handle_connection(stream: TcpStream, max_count: u32) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+
+    loop {
+        let payload = match read_frame(&mut reader, REQUEST_LEN) {
+            Ok(payload) => payload,
+            // 客户端正常关闭连接
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return writer.flush(),
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                // 帧格式错误后无法再定位下一帧，返回错误并关闭连接
+                write_frame(&mut writer, &encode_error(Status::ProtocolError, &e.to_string()))?;
+                return writer.flush();
+            }
+            Err(e) => return Err(e),
+        };
+
+        let response = match <[u8; 4]>::try_from(payload.as_slice()).map(u32::from_be_bytes) {
+            Ok(count) if count == 0 || count > max_count => encode_error(
+                Status::ProtocolError,
+                &format!("数量必须在1到{}之间", max_count),
+            ),
+            Ok(count) => match get_next_ids(count as usize) {
+                Ok(ids) => encode_ids(&ids),
+                Err(e) => encode_error(Status::of_error(&e), error_message(&e)),
+            },
+            Err(_) => encode_error(Status::ProtocolError, &format!("请求长度{}不合法", payload.len())),
+        };
+        write_frame(&mut writer, &response)?;
+
+        if reader.buffer().is_empty() {
+            writer.flush()?;
+        }
+    }
+}
+
+// ============================================================================
+// 客户端
+// ============================================================================
+
+/// 二进制发号协议客户端
+///
+/// 除了一问一答的 `next_id` / `next_ids`，还可以用 `send` 连续发送多个请求，
+/// 再用 `recv` 按顺序读取响应 (流水线)。
+///
+/// 读写出错 (包括超时) 或收到格式错误的响应后，连接上的请求和响应已经无法对应，
+/// 之后的调用都返回NetworkError，需要重新连接。
+#[derive(Debug)]
+pub struct BinaryClient {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+    /// 已发送但尚未读取响应的请求 (每个请求的ID数量)
+    pending: VecDeque<u32>,
+    /// 连接已失效
+    poisoned: bool,
+}
+
+impl BinaryClient {
+    /// 连接发号服务
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self, SnowflakeError> {
+        let stream = TcpStream::connect(addr).map_err(network_error)?;
+        Self::from_stream(stream)
+    }
+
+    /// 带超时地连接发号服务
+    /// 连接、读、写都受timeout限制，避免不可达的服务端让调用方无限阻塞
+    pub fn connect_timeout(addr: impl ToSocketAddrs, timeout: Duration) -> Result<Self, SnowflakeError> {
+        let mut last_err = None;
+        for addr in addr.to_socket_addrs().map_err(network_error)? {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(timeout)).map_err(network_error)?;
+                    stream.set_write_timeout(Some(timeout)).map_err(network_error)?;
+                    return Self::from_stream(stream);
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.map_or_else(
+            || SnowflakeError::NetworkError("地址解析结果为空".to_string()),
+            network_error,
+        ))
+    }
+
+    /// 从已建立的连接创建客户端
+    fn from_stream(stream: TcpStream) -> Result<Self, SnowflakeError> {
+        stream.set_nodelay(true).map_err(network_error)?;
+        let reader = BufReader::new(stream.try_clone().map_err(network_error)?);
+        Ok(Self {
+            reader,
+            writer: BufWriter::new(stream),
+            pending: VecDeque::new(),
+            poisoned: false,
+        })
+    }
+
+    /// 获取一个ID
+    pub fn next_id(&mut self) -> Result<u64, SnowflakeError> {
+        let ids = self.next_ids(1)?;
+        ids.first()
+            .copied()
+            .ok_or_else(|| SnowflakeError::ProtocolError("响应中没有ID".to_string()))
+    }
+
+    /// 批量获取ID
+    ///
+    /// 还有流水线请求的响应未读取时返回ProtocolError (否则读到的会是先前请求的响应)，
+    /// 需要先用 `recv` 读完
+    pub fn next_ids(&mut self, count: u32) -> Result<Vec<u64>, SnowflakeError> {
+        self.check_usable()?;
+        if !self.pending.is_empty() {
+            return Err(SnowflakeError::ProtocolError(format!(
+                "还有{}个流水线请求的响应未读取，请先调用recv",
+                self.pending.len()
+            )));
+        }
+        self.send(count)?;
+        self.recv()
+    }
+
+    /// 发送一个请求但不等待响应 (流水线)
+    /// 请求先写入缓冲区，在下一次 `recv` 或 `flush` 时发出
+    pub fn send(&mut self, count: u32) -> Result<(), SnowflakeError> {
+        self.check_usable()?;
+        if let Err(e) = write_frame(&mut self.writer, &count.to_be_bytes()) {
+            return Err(self.poison(network_error(e)));
+        }
+        self.pending.push_back(count);
+        Ok(())
+    }
+
+    /// 把缓冲区中的请求发出去
+    pub fn flush(&mut self) -> Result<(), SnowflakeError> {
+        self.check_usable()?;
+        self.writer.flush().map_err(|e| self.poison(network_error(e)))
+    }
+
+    /// 按发送顺序读取下一个响应
+    /// 成功的响应中ID数量与请求不符时返回ProtocolError
+    pub fn recv(&mut self) -> Result<Vec<u64>, SnowflakeError> {
+        self.check_usable()?;
+        let Some(&count) = self.pending.front() else {
+            return Err(SnowflakeError::ProtocolError("没有等待响应的请求".to_string()));
+        };
+        self.flush()?;
+        let payload = read_frame(&mut self.reader, MAX_RESPONSE_LEN).map_err(|e| self.poison(network_error(e)))?;
+        self.pending.pop_front();
+
+        let ids = decode_response(&payload).map_err(|e| self.poison(e))??;
+        if ids.len() != count as usize {
+            return Err(SnowflakeError::ProtocolError(format!(
+                "请求{}个ID，响应中有{}个",
+                count,
+                ids.len()
+            )));
+        }
+        Ok(ids)
+    }
+
+    /// 已发送但尚未读取响应的请求数
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 连接已失效时返回错误
+    fn check_usable(&self) -> Result<(), SnowflakeError> {
+        if self.poisoned {
+            return Err(SnowflakeError::NetworkError(
+                "连接在之前的读写错误后已失效，请重新连接".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 标记连接失效，返回原来的错误
+    fn poison(&mut self, e: SnowflakeError) -> SnowflakeError {
+        warn!(error = %e, "二进制协议连接失效");
+        self.poisoned = true;
+        e
+    }
+}
+
+/// IO错误转换为网络错误
+fn network_error(e: io::Error) -> SnowflakeError {
+    SnowflakeError::NetworkError(e.to_string())
+}
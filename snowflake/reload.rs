@@ -0,0 +1,405 @@
+/*!
+运行时重新加载snowflake.toml
+
+配置分为两类:
+- 可以立即生效: `max_backward_ms`、`sequence_exhausted_policy`、`sequence_start`
+- 不能在运行时修改: `worker_id_bits`、`sequence_bits` (位布局改变后新旧ID无法按同一规则解析，
+  也可能与已发放的ID重复)，与当前值不同时拒绝并记录错误日志，其他修改照常生效
+
+`datacenter_id`/`machine_id` 映射出的Worker ID改变时，先等待当前毫秒结束再切换，
+切换前后的ID不会落在同一毫秒。Worker ID来自环境变量或租约时优先级更高，配置文件中的映射被忽略。
+
+全局Worker创建时已经按同一个文件确定了全部配置项 (见 `startup_config`)，
+因此启动后重新加载未修改的文件不会产生任何变化。
+
+启用 `reload` feature 后可以用 `ConfigWatcher` 监听文件变化自动重新加载。
+
+配置文件中的策略写法:
+
+```toml
+max_backward_ms = 50
+sequence_exhausted_policy = "borrow:5"   # spin / sleep / error / borrow:N
+sequence_start = "rotating"              # zero / random / rotating
+```
+*/
+
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+use std::sync::Mutex;
+
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    config_entries, parse_worker_id_mapping, SequenceExhaustedPolicy, SequenceStart, SnowflakeConfig,
+    SnowflakeError, SnowflakeIdWorker, WorkerIdStrategy, CONFIG_PATHS, GLOBAL_WORKER,
+};
+
+/// snowflake.toml中与生成器相关的配置 (未出现的项为None)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileConfig {
+    /// datacenter_id + machine_id 映射出的Worker ID
+    pub worker_id: Option<u8>,
+    /// Worker ID位数
+    pub worker_id_bits: Option<u8>,
+    /// 序列号位数
+    pub sequence_bits: Option<u8>,
+    /// 时钟回拨容忍度(毫秒)
+    pub max_backward_ms: Option<u64>,
+    /// 序列号用尽策略
+    pub sequence_exhausted_policy: Option<SequenceExhaustedPolicy>,
+    /// 每毫秒序列号的起始值
+    pub sequence_start: Option<SequenceStart>,
+}
+
+impl FileConfig {
+    /// 解析配置文件内容
+    ///
+    /// 返回:
+    /// - Err(SnowflakeError::ConfigError): 某一项的值无法解析 (此时不应用任何修改)
+    pub fn parse(content: &str) -> Result<Self, SnowflakeError> {
+        let mut config = FileConfig {
+            worker_id: parse_worker_id_mapping(content),
+            ..Default::default()
+        };
+
+        for (number, key, value) in config_entries(content) {
+            let invalid = || SnowflakeError::ConfigError(format!("第{}行 {} 的值无效: {}", number, key, value));
+            match key {
+                "worker_id_bits" => config.worker_id_bits = Some(value.parse().map_err(|_| invalid())?),
+                "sequence_bits" => config.sequence_bits = Some(value.parse().map_err(|_| invalid())?),
+                "max_backward_ms" => config.max_backward_ms = Some(value.parse().map_err(|_| invalid())?),
+                "sequence_exhausted_policy" => {
+                    config.sequence_exhausted_policy = Some(parse_policy(value).ok_or_else(invalid)?)
+                }
+                "sequence_start" => config.sequence_start = Some(parse_sequence_start(value).ok_or_else(invalid)?),
+                _ => {}
+            }
+        }
+        Ok(config)
+    }
+
+    /// 读取并解析配置文件
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnowflakeError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| SnowflakeError::ConfigError(format!("无法读取配置文件 {}: {}", path.display(), e)))?;
+        Self::parse(&content)
+    }
+
+    /// 用文件中出现的项覆盖配置 (Worker ID映射由生成器创建时自己读取)
+    pub fn apply_to(&self, config: &mut SnowflakeConfig) {
+        if let Some(bits) = self.worker_id_bits {
+            config.worker_id_bits = bits;
+        }
+        if let Some(bits) = self.sequence_bits {
+            config.sequence_bits = bits;
+        }
+        if let Some(ms) = self.max_backward_ms {
+            config.max_backward_ms = ms;
+        }
+        if let Some(policy) = self.sequence_exhausted_policy {
+            config.sequence_exhausted_policy = policy;
+        }
+        if let Some(start) = self.sequence_start {
+            config.sequence_start = start;
+        }
+    }
+}
+
+fn parse_policy(value: &str) -> Option<SequenceExhaustedPolicy> {
+    match value {
+        "spin" => Some(SequenceExhaustedPolicy::Spin),
+        "sleep" => Some(SequenceExhaustedPolicy::Sleep),
+        "error" => Some(SequenceExhaustedPolicy::Error),
+        _ => value
+            .strip_prefix("borrow:")
+            .and_then(|ms| ms.trim().parse().ok())
+            .map(SequenceExhaustedPolicy::Borrow),
+    }
+}
+
+fn parse_sequence_start(value: &str) -> Option<SequenceStart> {
+    match value {
+        "zero" => Some(SequenceStart::Zero),
+        "random" => Some(SequenceStart::Random),
+        "rotating" => Some(SequenceStart::Rotating),
+        _ => None,
+    }
+}
+
+/// 一次重新加载的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadReport {
+    /// 已生效的修改 (例如 "max_backward_ms: 10 -> 50")
+    pub applied: Vec<String>,
+    /// 被拒绝的修改及原因
+    pub rejected: Vec<String>,
+    /// Worker ID的切换 (旧, 新)
+    pub worker_id: Option<(u8, u8)>,
+}
+
+impl ReloadReport {
+    /// 配置文件与当前配置相同
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.rejected.is_empty() && self.worker_id.is_none()
+    }
+}
+
+impl SnowflakeIdWorker {
+    /// 应用重新加载的配置
+    ///
+    /// 安全的修改立即生效，位布局的修改被拒绝 (记录在 `ReloadReport::rejected` 中并输出错误日志)。
+    /// Worker ID改变时等待当前毫秒结束后再切换。
+    pub fn apply_file_config(&mut self, file: &FileConfig) -> Result<ReloadReport, SnowflakeError> {
+        let mut report = ReloadReport::default();
+        // 切换Worker ID前要等时钟越过借用的毫秒，按借用时的上限计算领先量
+        // (策略从Borrow改为其他策略时，生成器同样沿用该上限直到时钟追上)
+        let borrowed_ahead_ms = if self.borrowing { self.borrow_ahead_ms } else { 0 };
+
+        // 位布局: 只能在创建生成器时确定
+        let layout = [
+            ("worker_id_bits", file.worker_id_bits, self.config.worker_id_bits),
+            ("sequence_bits", file.sequence_bits, self.config.sequence_bits),
+        ];
+        for (key, new, current) in layout {
+            if let Some(new) = new.filter(|&new| new != current) {
+                error!(key, current, new, "位布局不能在运行时修改，需要重启，已忽略");
+                report.rejected.push(format!("{}: {} -> {} (位布局不能在运行时修改)", key, current, new));
+            }
+        }
+
+        if let Some(new) = file.max_backward_ms.filter(|&new| new != self.config.max_backward_ms) {
+            report.applied.push(format!("max_backward_ms: {} -> {}", self.config.max_backward_ms, new));
+            self.config.max_backward_ms = new;
+        }
+        if let Some(new) = file.sequence_exhausted_policy.filter(|&new| new != self.config.sequence_exhausted_policy) {
+            report.applied.push(format!("sequence_exhausted_policy: {:?} -> {:?}", self.config.sequence_exhausted_policy, new));
+            self.config.sequence_exhausted_policy = new;
+        }
+        if let Some(new) = file.sequence_start.filter(|&new| new != self.config.sequence_start) {
+            report.applied.push(format!("sequence_start: {:?} -> {:?}", self.config.sequence_start, new));
+            self.config.sequence_start = new;
+        }
+
+        if let Some(new) = file.worker_id.filter(|&new| new != self.worker_id) {
+            match self.strategy {
+                // 环境变量和租约的优先级高于配置文件
+                WorkerIdStrategy::Env | WorkerIdStrategy::Lease => {
+                    warn!(
+                        worker_id = self.worker_id,
+                        strategy = self.strategy.as_str(),
+                        mapped = new,
+                        "Worker ID来自更高优先级的方式，忽略配置文件中的映射"
+                    );
+                    report.rejected.push(format!(
+                        "worker_id: {} -> {} (当前Worker ID来自{})",
+                        self.worker_id,
+                        new,
+                        self.strategy.as_str()
+                    ));
+                }
+                _ if self.config.worker_id_bits < 8 && new >> self.config.worker_id_bits != 0 => {
+                    error!(worker_id = new, worker_id_bits = self.config.worker_id_bits, "Worker ID超出位布局范围，已忽略");
+                    report.rejected.push(format!(
+                        "worker_id: {} -> {} (超出{}位能表示的范围)",
+                        self.worker_id, new, self.config.worker_id_bits
+                    ));
+                }
+                _ => match self.switch_worker_id(new, borrowed_ahead_ms) {
+                    Ok(old) => report.worker_id = Some((old, new)),
+                    Err(e) => report.rejected.push(format!("worker_id: {} -> {} ({})", self.worker_id, new, e)),
+                },
+            }
+        }
+
+        for change in &report.applied {
+            info!(change = %change, "配置已重新加载");
+        }
+        Ok(report)
+    }
+
+    /// 等待当前毫秒结束后切换Worker ID，返回旧的Worker ID
+    /// 切换后的第一个ID的时间戳一定大于用旧Worker ID生成的最后一个ID
+    ///
+    /// 借用了未来毫秒时last_timestamp领先于时钟，同样要等时钟越过它。
+    /// 时钟落后超过借用量加上 `max_backward_ms` 时不等待，返回 `ClockBackward` 并保持原Worker ID
+    fn switch_worker_id(&mut self, new: u8, borrowed_ahead_ms: u64) -> Result<u8, SnowflakeError> {
+        let old = self.worker_id;
+        let tolerance_ms = (self.config.max_backward_ms + borrowed_ahead_ms) as i64;
+        loop {
+            let now = self.time_gen()? as i64;
+            if now > self.last_timestamp {
+                break;
+            }
+            let behind_ms = self.last_timestamp - now;
+            if behind_ms > tolerance_ms {
+                error!(old, new, behind_ms, tolerance_ms, "时钟回拨超过容忍度，不切换Worker ID");
+                return Err(SnowflakeError::ClockBackward(format!(
+                    "时钟落后上一个ID {}ms，超过容忍度{}ms",
+                    behind_ms, tolerance_ms
+                )));
+            }
+            thread::yield_now();
+        }
+        self.worker_id = new;
+        self.strategy = WorkerIdStrategy::Config;
+        info!(old, new, "Worker ID已切换");
+        Ok(old)
+    }
+}
+
+// ============================================================================
+// 重新加载入口
+// ============================================================================
+
+/// 读取配置文件并应用到生成器
+/// 文件无法读取或解析时返回错误，生成器保持原配置
+pub fn reload_file(path: impl AsRef<Path>, worker: &Mutex<SnowflakeIdWorker>) -> Result<ReloadReport, SnowflakeError> {
+    let file = FileConfig::load(path)?;
+    worker.lock().unwrap().apply_file_config(&file)
+}
+
+/// 重新加载全局Worker的配置文件 (按 `CONFIG_PATHS` 顺序找到的第一个文件)
+pub fn reload_global_config() -> Result<ReloadReport, SnowflakeError> {
+    let path = find_config_file()?;
+    reload_file(path, &GLOBAL_WORKER)
+}
+
+/// 按 `CONFIG_PATHS` 顺序找到的第一个配置文件
+pub fn config_file_path() -> Option<&'static str> {
+    CONFIG_PATHS.iter().copied().find(|path| Path::new(path).is_file())
+}
+
+fn find_config_file() -> Result<&'static str, SnowflakeError> {
+    config_file_path()
+        .ok_or_else(|| SnowflakeError::ConfigError(format!("找不到配置文件 ({})", CONFIG_PATHS.join(", "))))
+}
+
+/// 创建全局Worker时使用的配置: 配置文件中出现的项覆盖代码中的配置
+///
+/// 与重新加载读取同一个文件，启动后立即重新加载不会产生任何修改。
+/// 位布局只能在这里由文件决定。文件内容有误时输出错误日志并使用代码中的配置。
+pub(crate) fn startup_config(config: SnowflakeConfig) -> SnowflakeConfig {
+    let Some(path) = config_file_path() else {
+        return config;
+    };
+    let mut merged = config.clone();
+    match FileConfig::load(path).and_then(|file| {
+        file.apply_to(&mut merged);
+        merged.check_layout()
+    }) {
+        Ok(()) => {
+            debug!(path, ?merged, "已应用配置文件");
+            merged
+        }
+        Err(e) => {
+            error!(path, error = %e, "配置文件无效，全局Worker使用代码中的配置");
+            config
+        }
+    }
+}
+
+// ============================================================================
+// 文件监听 (reload feature)
+// ============================================================================
+
+#[cfg(feature = "reload")]
+pub use watcher::{watch_global_config, ConfigWatcher};
+
+#[cfg(feature = "reload")]
+mod watcher {
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+    use tracing::{debug, error, info};
+
+    use super::{find_config_file, reload_file};
+    use crate::{SnowflakeError, SnowflakeIdWorker, GLOBAL_WORKER};
+
+    /// 配置文件监听器
+    /// 文件被修改或替换 (编辑器通常写临时文件后改名) 时重新加载，释放 (drop) 后停止监听
+    pub struct ConfigWatcher {
+        path: PathBuf,
+        _watcher: RecommendedWatcher,
+        /// 释放后置为false: 监听线程在释放后才送达的事件不再重新加载
+        active: Arc<AtomicBool>,
+    }
+
+    impl std::fmt::Debug for ConfigWatcher {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("ConfigWatcher").field("path", &self.path).finish()
+        }
+    }
+
+    impl ConfigWatcher {
+        /// 立即加载一次配置文件，然后监听它的变化
+        ///
+        /// 生成器创建时只读取了配置文件中的Worker ID映射 (全局Worker除外)，其他配置项在这次加载时生效
+        pub fn start(path: impl AsRef<Path>, worker: Arc<Mutex<SnowflakeIdWorker>>) -> Result<Self, SnowflakeError> {
+            let path = path.as_ref().to_path_buf();
+            let report = reload_file(&path, &worker)?;
+            debug!(path = %path.display(), ?report, "初次加载配置文件");
+
+            // 监听所在目录: 文件被改名替换后，对原文件的监听会失效
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            let file_name = path.file_name().map(|name| name.to_os_string());
+            let target = path.clone();
+            let active = Arc::new(AtomicBool::new(true));
+            let still_active = active.clone();
+            let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        error!(error = %e, "配置文件监听出错");
+                        return;
+                    }
+                };
+                if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    return;
+                }
+                if !event.paths.iter().any(|p| p.file_name().map(|n| n.to_os_string()) == file_name) {
+                    return;
+                }
+                if !still_active.load(Ordering::SeqCst) {
+                    return;
+                }
+                match reload_file(&target, &worker) {
+                    Ok(report) if report.is_empty() => debug!(path = %target.display(), "配置文件未变化"),
+                    Ok(report) => info!(path = %target.display(), ?report, "配置文件已重新加载"),
+                    // 文件正在写入或内容有误: 保持原配置，等待下一次修改
+                    Err(e) => error!(path = %target.display(), error = %e, "重新加载配置文件失败，保持原配置"),
+                }
+            })
+            .map_err(|e| SnowflakeError::ConfigError(format!("无法创建文件监听: {}", e)))?;
+            watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .map_err(|e| SnowflakeError::ConfigError(format!("无法监听 {}: {}", dir.display(), e)))?;
+
+            info!(path = %path.display(), "开始监听配置文件");
+            Ok(Self { path, _watcher: watcher, active })
+        }
+
+        /// 监听的配置文件
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl Drop for ConfigWatcher {
+        fn drop(&mut self) {
+            self.active.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// 监听全局Worker的配置文件 (按 `CONFIG_PATHS` 顺序找到的第一个文件)
+    pub fn watch_global_config() -> Result<ConfigWatcher, SnowflakeError> {
+        ConfigWatcher::start(find_config_file()?, Arc::clone(&GLOBAL_WORKER))
+    }
+}
@@ -0,0 +1,483 @@
+/*!
+号段模式ID生成器 (Leaf风格)
+
+部分业务表需要稠密、严格递增的ID，这是雪花算法做不到的。号段模式按业务标签 (tag)
+从本地持久化存储中租用一段连续的ID (号段)，在内存中逐个发放:
+
+1. 持久化: 每次租用号段都先追加写入日志文件并fsync，重启后从日志恢复每个标签的已租用上限
+2. 双缓冲: 当前号段消耗到一定比例时，后台加载线程提前租用下一个号段，切换号段时不需要等待磁盘
+   (每个分配器只有一个加载线程，按请求顺序为各标签预加载)
+3. 严格递增: 同一标签的号段按租用顺序递增，重启后从已租用上限之后继续 (未用完的号段会被跳过)
+
+日志格式 (每行一条记录): `<tag> <已租用的最大ID>`，同一标签以最后一条记录为准。
+
+同一时间只能有一个分配器打开日志: 打开时对旁边的 `<日志文件名>.lock` 加排他锁，
+两个分配器 (或两个进程) 各自回放同一份日志会租出重叠的号段。
+锁加在单独的文件上，压缩日志时替换日志文件不会影响锁。
+*/
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+use fs2::FileExt;
+use tracing::{debug, info, warn};
+
+use crate::SnowflakeError;
+
+/// 日志记录数超过该值时压缩为每个标签一条记录
+const COMPACT_THRESHOLD: usize = 10_000;
+
+/// 号段生成器配置
+#[derive(Debug, Clone)]
+pub struct SegmentConfig {
+    /// 每个号段包含的ID数量
+    pub step: u64,
+
+    /// 当前号段消耗到该比例时预加载下一个号段 (0.0-1.0)
+    pub preload_ratio: f64,
+}
+
+impl Default for SegmentConfig {
+    fn default() -> Self {
+        Self {
+            step: 1000,         // 每段1000个ID
+            preload_ratio: 0.1, // 消耗10%后预加载下一段
+        }
+    }
+}
+
+// ============================================================================
+// 持久化存储
+// ============================================================================
+
+/// 号段日志文件
+/// 记录每个标签已租用的最大ID，只追加写入
+#[derive(Debug)]
+struct SegmentJournal {
+    path: PathBuf,
+    file: File,
+    /// 持有排他锁的锁文件 (日志关闭时释放)
+    _lock: File,
+    /// 每个标签已租用的最大ID
+    max_ids: HashMap<String, u64>,
+    /// 日志中的记录数 (用于判断是否需要压缩)
+    records: usize,
+}
+
+impl SegmentJournal {
+    /// 打开日志文件 (不存在时创建)，回放所有记录
+    /// 日志已被其他分配器打开时返回ConfigError
+    fn open(path: &Path) -> Result<Self, SnowflakeError> {
+        let lock = lock_journal(path)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| io_error(path, e))?;
+
+        let mut max_ids = HashMap::new();
+        let mut records = 0;
+        // 最后一个完整记录 (以换行结尾) 之后的位置
+        let mut valid_len = 0u64;
+
+        let mut reader = BufReader::new(&file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let n = reader.read_line(&mut line).map_err(|e| io_error(path, e))?;
+            if n == 0 {
+                break;
+            }
+            if !line.ends_with('\n') {
+                // 写入过程中崩溃留下的半行记录，截断丢弃
+                warn!(path = %path.display(), "号段日志末尾有不完整的记录，已丢弃");
+                break;
+            }
+            let (tag, max_id) = parse_record(&line).ok_or_else(|| {
+                SnowflakeError::ConfigError(format!("号段日志 {} 格式错误: {}", path.display(), line.trim_end()))
+            })?;
+            max_ids.insert(tag.to_string(), max_id);
+            records += 1;
+            valid_len += n as u64;
+        }
+
+        if file.metadata().map_err(|e| io_error(path, e))?.len() != valid_len {
+            file.set_len(valid_len).map_err(|e| io_error(path, e))?;
+            file.seek(SeekFrom::End(0)).map_err(|e| io_error(path, e))?;
+        }
+
+        debug!(path = %path.display(), tags = max_ids.len(), records, "号段日志已加载");
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            _lock: lock,
+            max_ids,
+            records,
+        })
+    }
+
+    /// 为标签租用一个号段，返回 [start, end) 区间
+    /// 记录写入磁盘 (fsync) 后才返回，保证重启后不会重复发放
+    fn lease(&mut self, tag: &str, step: u64) -> Result<Segment, SnowflakeError> {
+        let current = self.max_ids.get(tag).copied().unwrap_or(0);
+        let max_id = current
+            .checked_add(step)
+            .ok_or_else(|| SnowflakeError::SequenceExhausted(format!("标签 {} 的号段已达到u64上限", tag)))?;
+
+        writeln!(self.file, "{} {}", tag, max_id).map_err(|e| io_error(&self.path, e))?;
+        self.file.sync_data().map_err(|e| io_error(&self.path, e))?;
+        self.max_ids.insert(tag.to_string(), max_id);
+        self.records += 1;
+
+        if self.records > COMPACT_THRESHOLD {
+            self.compact()?;
+        }
+
+        Ok(Segment {
+            next: current + 1,
+            end: max_id + 1,
+        })
+    }
+
+    /// 压缩日志: 每个标签只保留最新一条记录
+    /// 先写临时文件并fsync，再原子替换原文件
+    fn compact(&mut self) -> Result<(), SnowflakeError> {
+        let tmp_path = self.path.with_extension("compact");
+        let mut tmp = File::create(&tmp_path).map_err(|e| io_error(&tmp_path, e))?;
+        for (tag, max_id) in &self.max_ids {
+            writeln!(tmp, "{} {}", tag, max_id).map_err(|e| io_error(&tmp_path, e))?;
+        }
+        tmp.sync_all().map_err(|e| io_error(&tmp_path, e))?;
+        fs::rename(&tmp_path, &self.path).map_err(|e| io_error(&self.path, e))?;
+        // 目录项落盘后替换才是持久的，否则崩溃后可能看到旧文件或压缩临时文件
+        sync_parent_dir(&self.path)?;
+
+        self.file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| io_error(&self.path, e))?;
+        self.records = self.max_ids.len();
+        info!(path = %self.path.display(), records = self.records, "号段日志已压缩");
+        Ok(())
+    }
+}
+
+/// 对日志旁边的锁文件加排他锁
+fn lock_journal(path: &Path) -> Result<File, SnowflakeError> {
+    let mut lock_path = OsString::from(path.as_os_str());
+    lock_path.push(".lock");
+    let lock_path = PathBuf::from(lock_path);
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&lock_path)
+        .map_err(|e| io_error(&lock_path, e))?;
+    if file.try_lock_exclusive().is_err() {
+        return Err(SnowflakeError::ConfigError(format!(
+            "号段日志 {} 已被其他分配器打开 (锁文件 {})",
+            path.display(),
+            lock_path.display()
+        )));
+    }
+    Ok(file)
+}
+
+/// 解析一条日志记录 `<tag> <max_id>`
+fn parse_record(line: &str) -> Option<(&str, u64)> {
+    let mut parts = line.split_whitespace();
+    let tag = parts.next()?;
+    let max_id = parts.next()?.parse().ok()?;
+    parts.next().is_none().then_some((tag, max_id))
+}
+
+/// fsync文件所在目录
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<(), SnowflakeError> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir).and_then(|dir| dir.sync_all()).map_err(|e| io_error(dir, e))
+}
+
+/// 非unix平台无法打开目录，rename的持久性由文件系统保证
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<(), SnowflakeError> {
+    Ok(())
+}
+
+/// 文件IO错误转换为IO错误 (带上文件路径)
+fn io_error(path: &Path, e: std::io::Error) -> SnowflakeError {
+    SnowflakeError::IoError(format!("号段日志 {} 读写失败: {}", path.display(), e))
+}
+
+// ============================================================================
+// 号段与双缓冲
+// ============================================================================
+
+/// 一个号段 [next, end)
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    /// 下一个要发放的ID
+    next: u64,
+    /// 号段结束位置 (不包含)
+    end: u64,
+}
+
+impl Segment {
+    /// 号段是否已用完
+    fn is_empty(&self) -> bool {
+        self.next >= self.end
+    }
+}
+
+/// 单个标签的双缓冲状态
+#[derive(Debug)]
+struct Buffers {
+    /// 正在发放的号段
+    current: Segment,
+    /// 预加载好的下一个号段
+    next: Option<Segment>,
+    /// 加载线程正在租用下一个号段
+    loading: bool,
+    /// 最近一次后台租用失败的错误
+    load_error: Option<SnowflakeError>,
+}
+
+/// 单个标签的共享状态
+#[derive(Debug)]
+struct TagState {
+    tag: String,
+    buffers: Mutex<Buffers>,
+    /// 后台租用完成时通知等待的调用方
+    loaded: Condvar,
+}
+
+// ============================================================================
+// 公共接口
+// ============================================================================
+
+/// 号段分配器
+/// 管理一个日志文件和其中的所有业务标签
+#[derive(Debug)]
+pub struct SegmentAllocator {
+    config: SegmentConfig,
+    /// 与加载线程共享的日志
+    journal: Arc<Mutex<SegmentJournal>>,
+    tags: Mutex<HashMap<String, Arc<TagState>>>,
+    /// 预加载请求队列，由唯一的加载线程处理 (释放分配器时关闭)
+    preload: Option<Sender<Arc<TagState>>>,
+    /// 加载线程
+    loader: Option<JoinHandle<()>>,
+}
+
+impl SegmentAllocator {
+    /// 打开号段分配器
+    ///
+    /// 参数:
+    /// - path: 日志文件路径 (不存在时创建)
+    /// - config: 可选的配置参数，如果为None则使用默认配置
+    pub fn open(path: impl AsRef<Path>, config: Option<SegmentConfig>) -> Result<Arc<Self>, SnowflakeError> {
+        let config = config.unwrap_or_default();
+        if config.step == 0 {
+            return Err(SnowflakeError::ConfigError("号段步长必须大于0".to_string()));
+        }
+        if !(0.0..=1.0).contains(&config.preload_ratio) {
+            return Err(SnowflakeError::ConfigError("预加载比例必须在0.0到1.0之间".to_string()));
+        }
+
+        let journal = Arc::new(Mutex::new(SegmentJournal::open(path.as_ref())?));
+        let (preload, requests) = mpsc::channel();
+        let loader = Loader {
+            journal: Arc::clone(&journal),
+            step: config.step,
+        };
+        let handle = thread::Builder::new()
+            .name("snowflake-segment-loader".to_string())
+            .spawn(move || loader.run(requests))
+            .map_err(|e| SnowflakeError::IoError(format!("无法启动号段加载线程: {}", e)))?;
+
+        Ok(Arc::new(Self {
+            config,
+            journal,
+            tags: Mutex::new(HashMap::new()),
+            preload: Some(preload),
+            loader: Some(handle),
+        }))
+    }
+
+    /// 获取业务标签的ID生成器
+    /// 同一标签的生成器共享同一组号段，可以在多个线程间克隆使用
+    pub fn generator(self: &Arc<Self>, tag: &str) -> Result<SegmentIdGenerator, SnowflakeError> {
+        if tag.is_empty() || tag.chars().any(char::is_whitespace) {
+            return Err(SnowflakeError::ConfigError(format!("业务标签不能为空或包含空白字符: {:?}", tag)));
+        }
+
+        let mut tags = self.tags.lock().unwrap();
+        let state = match tags.get(tag) {
+            Some(state) => Arc::clone(state),
+            None => {
+                let current = self.lease(tag)?;
+                let state = Arc::new(TagState {
+                    tag: tag.to_string(),
+                    buffers: Mutex::new(Buffers {
+                        current,
+                        next: None,
+                        loading: false,
+                        load_error: None,
+                    }),
+                    loaded: Condvar::new(),
+                });
+                tags.insert(tag.to_string(), Arc::clone(&state));
+                state
+            }
+        };
+
+        Ok(SegmentIdGenerator {
+            allocator: Arc::clone(self),
+            state,
+        })
+    }
+
+    /// 为业务标签生成下一个ID
+    pub fn next_id(self: &Arc<Self>, tag: &str) -> Result<u64, SnowflakeError> {
+        self.generator(tag)?.next_id()
+    }
+
+    /// 租用一个号段
+    fn lease(&self, tag: &str) -> Result<Segment, SnowflakeError> {
+        lease(&self.journal, tag, self.config.step)
+    }
+}
+
+impl Drop for SegmentAllocator {
+    /// 关闭请求队列并等待加载线程退出，返回时日志和锁文件都已关闭，可以立即重新打开
+    fn drop(&mut self) {
+        self.preload.take();
+        if let Some(handle) = self.loader.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 从日志租用一个号段
+fn lease(journal: &Mutex<SegmentJournal>, tag: &str, step: u64) -> Result<Segment, SnowflakeError> {
+    let segment = journal.lock().unwrap().lease(tag, step)?;
+    debug!(tag, start = segment.next, end = segment.end, "已租用号段");
+    Ok(segment)
+}
+
+/// 后台加载线程
+struct Loader {
+    journal: Arc<Mutex<SegmentJournal>>,
+    step: u64,
+}
+
+impl Loader {
+    /// 加载循环: 依次为请求的标签租用下一个号段，完成后通知等待的调用方，直到请求队列关闭
+    fn run(self, requests: Receiver<Arc<TagState>>) {
+        while let Ok(state) = requests.recv() {
+            self.load(&state);
+        }
+        debug!("号段加载线程已退出");
+    }
+
+    /// 为一个标签租用下一个号段
+    fn load(&self, state: &TagState) {
+        let result = lease(&self.journal, &state.tag, self.step);
+
+        let mut buffers = state.buffers.lock().unwrap();
+        match result {
+            Ok(segment) => buffers.next = Some(segment),
+            Err(e) => {
+                warn!(tag = %state.tag, error = %e, "预加载号段失败");
+                buffers.load_error = Some(e);
+            }
+        }
+        buffers.loading = false;
+        state.loaded.notify_all();
+    }
+}
+
+/// 单个业务标签的号段ID生成器
+/// 发放的ID稠密且严格递增
+#[derive(Debug, Clone)]
+pub struct SegmentIdGenerator {
+    allocator: Arc<SegmentAllocator>,
+    state: Arc<TagState>,
+}
+
+impl SegmentIdGenerator {
+    /// 业务标签
+    pub fn tag(&self) -> &str {
+        &self.state.tag
+    }
+
+    /// 生成下一个ID
+    pub fn next_id(&self) -> Result<u64, SnowflakeError> {
+        let mut buffers = self.state.buffers.lock().unwrap();
+
+        loop {
+            if !buffers.current.is_empty() {
+                let id = buffers.current.next;
+                buffers.current.next += 1;
+                self.maybe_preload(&mut buffers);
+                return Ok(id);
+            }
+
+            // 当前号段用完，切换到预加载的号段
+            if let Some(next) = buffers.next.take() {
+                buffers.current = next;
+                continue;
+            }
+
+            if buffers.loading {
+                // 后台正在租用，等待完成
+                buffers = self.state.loaded.wait(buffers).unwrap();
+                if !buffers.loading && buffers.next.is_none() {
+                    if let Some(e) = buffers.load_error.take() {
+                        return Err(e);
+                    }
+                }
+                continue;
+            }
+
+            // 没有预加载 (例如后台租用失败)，同步租用
+            buffers.current = self.allocator.lease(&self.state.tag)?;
+        }
+    }
+
+    /// 当前号段消耗超过预加载比例时，请求加载线程租用下一个号段
+    fn maybe_preload(&self, buffers: &mut Buffers) {
+        if buffers.next.is_some() || buffers.loading {
+            return;
+        }
+        let step = self.allocator.config.step;
+        let remaining = buffers.current.end - buffers.current.next;
+        let consumed = (step - remaining.min(step)) as f64 / step as f64;
+        if consumed < self.allocator.config.preload_ratio {
+            return;
+        }
+
+        buffers.load_error = None;
+        // 加载线程只在分配器释放后退出，而生成器持有分配器，发送失败说明加载线程异常终止:
+        // 不标记loading，号段用完时改为同步租用
+        buffers.loading = match &self.allocator.preload {
+            Some(preload) => preload.send(Arc::clone(&self.state)).is_ok(),
+            None => false,
+        };
+        if !buffers.loading {
+            debug!(tag = %self.state.tag, "号段加载线程已退出，改为同步租用");
+        }
+    }
+}
@@ -0,0 +1,134 @@
+/*!
+雪花算法ID发号服务
+
+把全局ID生成器包装成HTTP服务，供非Rust服务获取雪花ID。
+接口见 `snowflake::http`: /id、/ids、/decode/:id、/health、/metrics。
+
+另外可以同时开启二进制发号协议 (见 `snowflake::proto`)，供高频取号的服务使用。
+
+配置 (命令行参数优先于环境变量):
+- --bind / SNOWFLAKE_SERVER_BIND          监听地址，默认 0.0.0.0:8080
+- --max-count / SNOWFLAKE_SERVER_MAX_COUNT 单次请求最多生成的ID数，默认 1000，不超过 `proto::MAX_COUNT`
+- --tcp-bind / SNOWFLAKE_SERVER_TCP_BIND  二进制协议监听地址，默认不开启
+*/
+
+use std::env;
+
+use snowflake::{get_worker_info, http, init_global_worker, init_logging, proto, SnowflakeConfig};
+use tracing::{error, info};
+
+/// 默认监听地址
+const DEFAULT_BIND: &str = "0.0.0.0:8080";
+/// 默认单次请求最多生成的ID数
+const DEFAULT_MAX_COUNT: usize = 1000;
+
+/// 服务配置
+#[derive(Debug, Clone)]
+struct ServerConfig {
+    /// 监听地址
+    bind: String,
+    /// 单次请求最多生成的ID数
+    max_count: usize,
+    /// 二进制协议监听地址 (None表示不开启)
+    tcp_bind: Option<String>,
+}
+
+impl ServerConfig {
+    /// 从环境变量和命令行参数读取配置 (命令行参数优先)
+    fn load() -> Result<Self, String> {
+        let mut config = Self {
+            bind: env::var("SNOWFLAKE_SERVER_BIND").unwrap_or_else(|_| DEFAULT_BIND.to_string()),
+            max_count: match env::var("SNOWFLAKE_SERVER_MAX_COUNT") {
+                Ok(value) => parse_max_count(&value)?,
+                Err(_) => DEFAULT_MAX_COUNT,
+            },
+            tcp_bind: env::var("SNOWFLAKE_SERVER_TCP_BIND").ok(),
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--bind" => {
+                    config.bind = args.next().ok_or("--bind 缺少监听地址")?;
+                }
+                "--max-count" => {
+                    let value = args.next().ok_or("--max-count 缺少数值")?;
+                    config.max_count = parse_max_count(&value)?;
+                }
+                "--tcp-bind" => {
+                    config.tcp_bind = Some(args.next().ok_or("--tcp-bind 缺少监听地址")?);
+                }
+                other => return Err(format!("未知参数: {}", other)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// 解析最大批量 (必须为正整数，且不超过二进制协议单个响应能容纳的ID数)
+fn parse_max_count(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<u32>() {
+        Ok(n) if n > 0 && n <= proto::MAX_COUNT => Ok(n as usize),
+        _ => Err(format!("最大批量必须为1到{}之间的整数: {}", proto::MAX_COUNT, value)),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    init_logging();
+
+    let config = match ServerConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("配置错误: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    // 启动前先初始化全局生成器，Worker ID分配失败时尽早退出
+    // (配置来自snowflake.toml，见 `reload::startup_config`)
+    if let Err(e) = init_global_worker(SnowflakeConfig::default()) {
+        error!(error = %e, "全局生成器初始化失败");
+        std::process::exit(1);
+    }
+    let (worker_id, strategy) = get_worker_info();
+
+    let listener = match tokio::net::TcpListener::bind(&config.bind).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!(bind = %config.bind, error = %e, "监听地址绑定失败");
+            std::process::exit(1);
+        }
+    };
+
+    // 二进制协议使用独立的阻塞线程，每个连接一个线程
+    if let Some(tcp_bind) = config.tcp_bind.clone() {
+        let tcp_listener = match std::net::TcpListener::bind(&tcp_bind) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!(bind = %tcp_bind, error = %e, "二进制协议监听地址绑定失败");
+                std::process::exit(1);
+            }
+        };
+        let max_count = config.max_count as u32;
+        std::thread::spawn(move || {
+            if let Err(e) = proto::serve(tcp_listener, max_count) {
+                error!(error = %e, "二进制协议服务异常退出");
+            }
+        });
+        info!(bind = %tcp_bind, "二进制发号协议已启动");
+    }
+
+    info!(
+        bind = %config.bind,
+        max_count = config.max_count,
+        worker_id,
+        strategy = strategy.as_str(),
+        "雪花ID发号服务已启动"
+    );
+
+    if let Err(e) = axum::serve(listener, http::app(config.max_count)).await {
+        error!(error = %e, "服务异常退出");
+        std::process::exit(1);
+    }
+}
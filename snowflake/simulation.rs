@@ -0,0 +1,356 @@
+/*!
+时钟跳变模拟
+
+`SimulatedClock` 区分 "真实时间" 和 "报告时间":
+- 真实时间只会前进: 每次读取时钟前进 `tick` (默认1微秒)，`sleep` 和 `advance` 直接推进
+- 报告时间 (生成器看到的时间) 在真实时间的基础上可以被脚本调整:
+  NTP回拨/跳跃 (`step`)、冻结 (`freeze`)、闰秒平滑 (`smear`)
+
+`ClockScenario` 是一段脚本: 生成ID、让时间流逝、调整时钟交替进行。
+`ClockScenario::run` 在模拟时钟上驱动任意生成器执行脚本，返回生成的ID和遇到的错误，
+测试中再根据恢复策略 (回拨容忍度、序列号用尽策略等) 断言结果。
+
+```ignore
+let clock = SimulatedClock::new(TWEPOCH_MS + 60_000);
+let mut worker = SnowflakeIdWorker::with_clock(None, clock.clone())?;
+let outcome = ClockScenario::ntp_step_back(50).run(&clock, || worker.next_id());
+assert!(outcome.is_strictly_increasing());
+assert!(outcome.clock_backward_errors() > 0);
+```
+*/
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{Clock, SnowflakeError};
+
+/// 生成出错后，调用方重试前等待的时间
+pub const RETRY_BACKOFF: Duration = Duration::from_millis(1);
+
+/// 单个生成步骤最多容忍的错误次数，超过后放弃该步骤
+pub const MAX_ERRORS_PER_STEP: usize = 10_000;
+
+// ============================================================================
+// 模拟时钟
+// ============================================================================
+
+/// 报告时间的计算方式 (从 `base_real_us` 开始分段)
+#[derive(Debug)]
+struct TimeState {
+    /// 真实时间 (微秒)
+    real_us: u64,
+    /// 当前分段起点的真实时间
+    base_real_us: u64,
+    /// 当前分段起点的报告时间
+    base_reported_us: u64,
+    /// 分段内报告时间的速率 (分子/分母)，冻结时为0，平滑时略小于或大于1
+    rate: (u64, u64),
+    /// 速率生效到该真实时间为止，之后恢复正常速度
+    rate_until_us: u64,
+}
+
+impl TimeState {
+    /// 指定真实时间对应的报告时间
+    fn reported_us(&self, real_us: u64) -> u64 {
+        let elapsed = real_us - self.base_real_us;
+        let adjusted = elapsed.min(self.rate_until_us.saturating_sub(self.base_real_us));
+        let (num, den) = self.rate;
+        self.base_reported_us + adjusted * num / den + (elapsed - adjusted)
+    }
+
+    /// 以当前时刻为起点开始新的分段
+    fn rebase(&mut self) {
+        self.base_reported_us = self.reported_us(self.real_us);
+        self.base_real_us = self.real_us;
+        self.rate = (1, 1);
+        self.rate_until_us = self.real_us;
+    }
+}
+
+/// 可编程的模拟时钟
+#[derive(Debug)]
+pub struct SimulatedClock {
+    /// 每次读取时钟推进的真实时间 (微秒)
+    tick_us: u64,
+    state: Mutex<TimeState>,
+}
+
+impl SimulatedClock {
+    /// 创建从指定时间 (Unix毫秒) 开始的模拟时钟，每次读取推进1微秒
+    pub fn new(start_ms: u64) -> Arc<Self> {
+        Self::with_tick(start_ms, Duration::from_micros(1))
+    }
+
+    /// 创建指定每次读取推进时间的模拟时钟
+    pub fn with_tick(start_ms: u64, tick: Duration) -> Arc<Self> {
+        let start_us = start_ms * 1000;
+        Arc::new(Self {
+            tick_us: tick.as_micros() as u64,
+            state: Mutex::new(TimeState {
+                real_us: start_us,
+                base_real_us: start_us,
+                base_reported_us: start_us,
+                rate: (1, 1),
+                rate_until_us: start_us,
+            }),
+        })
+    }
+
+    /// 当前真实时间 (Unix毫秒)
+    pub fn real_ms(&self) -> u64 {
+        self.state.lock().unwrap().real_us / 1000
+    }
+
+    /// 当前报告时间 (Unix毫秒)，不推进时间
+    pub fn reported_ms(&self) -> u64 {
+        let state = self.state.lock().unwrap();
+        state.reported_us(state.real_us) / 1000
+    }
+
+    /// 真实时间流逝
+    pub fn advance(&self, duration: Duration) {
+        self.state.lock().unwrap().real_us += duration.as_micros() as u64;
+    }
+
+    /// 报告时间跳变: 负数为回拨 (NTP step)，正数为向前跳跃
+    pub fn step(&self, delta_ms: i64) {
+        let mut state = self.state.lock().unwrap();
+        state.rebase();
+        let delta_us = delta_ms.unsigned_abs() * 1000;
+        state.base_reported_us = if delta_ms < 0 {
+            state.base_reported_us.saturating_sub(delta_us)
+        } else {
+            state.base_reported_us + delta_us
+        };
+    }
+
+    /// 报告时间在接下来的真实时间内停止前进，之后从停止处继续 (落后的时间不会追回)
+    pub fn freeze(&self, duration: Duration) {
+        self.set_rate((0, 1), duration);
+    }
+
+    /// 闰秒平滑: 在接下来的 `over` 真实时间内均匀地把报告时间调慢 `slew_ms` 毫秒
+    /// (负数为调快)，报告时间只会变慢或变快，不会回退
+    pub fn smear(&self, slew_ms: i64, over: Duration) {
+        let over_us = over.as_micros() as u64;
+        let reported_us = over_us.saturating_add_signed(-slew_ms * 1000);
+        self.set_rate((reported_us, over_us.max(1)), over);
+    }
+
+    fn set_rate(&self, rate: (u64, u64), duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.rebase();
+        state.rate = rate;
+        state.rate_until_us = state.real_us + duration.as_micros() as u64;
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now_ms(&self) -> Result<u64, SnowflakeError> {
+        let mut state = self.state.lock().unwrap();
+        state.real_us += self.tick_us;
+        Ok(state.reported_us(state.real_us) / 1000)
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+// ============================================================================
+// 场景脚本
+// ============================================================================
+
+/// 场景中的一个步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScenarioStep {
+    /// 成功生成指定数量的ID (出错时等待 `RETRY_BACKOFF` 后重试)
+    Generate(usize),
+    /// 真实时间流逝 (毫秒)
+    Advance(u64),
+    /// 报告时间跳变 (毫秒，负数为回拨)
+    Step(i64),
+    /// 报告时间冻结 (毫秒)
+    Freeze(u64),
+    /// 闰秒平滑: 在 `over_ms` 毫秒内调慢 `slew_ms` 毫秒
+    Smear { slew_ms: i64, over_ms: u64 },
+}
+
+/// 时钟跳变场景
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockScenario {
+    /// 场景名称
+    pub name: String,
+    /// 按顺序执行的步骤
+    pub steps: Vec<ScenarioStep>,
+}
+
+impl ClockScenario {
+    /// 正常生成一段时间后执行事件，再继续生成
+    fn around(name: String, event: ScenarioStep, after: usize) -> Self {
+        Self {
+            name,
+            steps: vec![
+                ScenarioStep::Generate(1000),
+                ScenarioStep::Advance(1),
+                event,
+                ScenarioStep::Generate(after),
+            ],
+        }
+    }
+
+    /// NTP把时钟回拨 `ms` 毫秒
+    pub fn ntp_step_back(ms: u64) -> Self {
+        Self::around(format!("ntp_step_back_{}ms", ms), ScenarioStep::Step(-(ms as i64)), 1000)
+    }
+
+    /// 时钟向前跳跃 `ms` 毫秒
+    pub fn forward_leap(ms: u64) -> Self {
+        Self::around(format!("forward_leap_{}ms", ms), ScenarioStep::Step(ms as i64), 1000)
+    }
+
+    /// 时钟冻结 `ms` 毫秒，期间持续生成 (足以用尽冻结那一毫秒的序列号)
+    pub fn frozen(ms: u64) -> Self {
+        Self::around(format!("frozen_{}ms", ms), ScenarioStep::Freeze(ms), 20_000)
+    }
+
+    /// 冻结时钟并持续生成，用尽冻结那一毫秒的序列号 (Borrow策略此时处于借用状态)，
+    /// 然后把时钟回拨 `ms` 毫秒
+    pub fn saturated_step_back(ms: u64) -> Self {
+        Self {
+            name: format!("saturated_step_back_{}ms", ms),
+            steps: vec![
+                ScenarioStep::Generate(1000),
+                ScenarioStep::Freeze(50),
+                // 默认布局下约3个毫秒的序列号
+                ScenarioStep::Generate(12_000),
+                ScenarioStep::Step(-(ms as i64)),
+                ScenarioStep::Generate(1000),
+            ],
+        }
+    }
+
+    /// 闰秒平滑: 在 `over_ms` 毫秒内调慢 `slew_ms` 毫秒，期间每毫秒生成50个ID
+    pub fn leap_smear(slew_ms: i64, over_ms: u64) -> Self {
+        let mut steps = vec![
+            ScenarioStep::Generate(1000),
+            ScenarioStep::Smear { slew_ms, over_ms },
+        ];
+        for _ in 0..over_ms + 10 {
+            steps.push(ScenarioStep::Generate(50));
+            steps.push(ScenarioStep::Advance(1));
+        }
+        Self {
+            name: format!("leap_smear_{}ms_over_{}ms", slew_ms, over_ms),
+            steps,
+        }
+    }
+
+    /// 标准场景: 回拨5ms/50ms/5s、向前跳跃1小时、冻结100ms、序列号用尽时回拨50ms、闰秒平滑
+    pub fn standard() -> Vec<Self> {
+        vec![
+            Self::ntp_step_back(5),
+            Self::ntp_step_back(50),
+            Self::ntp_step_back(5_000),
+            Self::forward_leap(3_600_000),
+            Self::frozen(100),
+            Self::saturated_step_back(50),
+            // 压缩的闰秒平滑: 2秒内调慢100毫秒 (真实的平滑在24小时内调慢1秒)
+            Self::leap_smear(100, 2_000),
+        ]
+    }
+
+    /// 在模拟时钟上执行场景
+    ///
+    /// next_id每次生成一个ID；出错时记录错误，真实时间前进 `RETRY_BACKOFF` 后重试，
+    /// 同一步骤错误超过 `MAX_ERRORS_PER_STEP` 次时放弃该步骤
+    pub fn run<T>(
+        &self,
+        clock: &SimulatedClock,
+        mut next_id: impl FnMut() -> Result<T, SnowflakeError>,
+    ) -> ScenarioOutcome<T> {
+        let mut outcome = ScenarioOutcome {
+            ids: Vec::new(),
+            errors: Vec::new(),
+            gave_up: false,
+        };
+        for (index, step) in self.steps.iter().enumerate() {
+            match *step {
+                ScenarioStep::Generate(count) => {
+                    let mut generated = 0;
+                    let mut errors = 0;
+                    while generated < count {
+                        match next_id() {
+                            Ok(id) => {
+                                outcome.ids.push(id);
+                                generated += 1;
+                            }
+                            Err(error) => {
+                                outcome.errors.push(ScenarioError { step: index, error });
+                                errors += 1;
+                                if errors >= MAX_ERRORS_PER_STEP {
+                                    outcome.gave_up = true;
+                                    break;
+                                }
+                                clock.advance(RETRY_BACKOFF);
+                            }
+                        }
+                    }
+                }
+                ScenarioStep::Advance(ms) => clock.advance(Duration::from_millis(ms)),
+                ScenarioStep::Step(delta_ms) => clock.step(delta_ms),
+                ScenarioStep::Freeze(ms) => clock.freeze(Duration::from_millis(ms)),
+                ScenarioStep::Smear { slew_ms, over_ms } => clock.smear(slew_ms, Duration::from_millis(over_ms)),
+            }
+        }
+        outcome
+    }
+}
+
+/// 场景中遇到的错误
+#[derive(Debug, Clone)]
+pub struct ScenarioError {
+    /// 出错的步骤序号
+    pub step: usize,
+    /// 生成器返回的错误
+    pub error: SnowflakeError,
+}
+
+/// 场景执行结果
+#[derive(Debug, Clone)]
+pub struct ScenarioOutcome<T = u64> {
+    /// 按生成顺序排列的ID
+    pub ids: Vec<T>,
+    /// 所有错误 (每次失败的调用一条)
+    pub errors: Vec<ScenarioError>,
+    /// 是否有步骤因错误过多而放弃
+    pub gave_up: bool,
+}
+
+impl<T: Ord> ScenarioOutcome<T> {
+    /// 生成顺序严格递增 (蕴含唯一)
+    pub fn is_strictly_increasing(&self) -> bool {
+        self.ids.windows(2).all(|w| w[0] < w[1])
+    }
+
+    /// 所有ID互不相同
+    pub fn is_unique(&self) -> bool {
+        let mut sorted: Vec<&T> = self.ids.iter().collect();
+        sorted.sort_unstable();
+        sorted.windows(2).all(|w| w[0] != w[1])
+    }
+
+    /// ClockBackward错误次数
+    pub fn clock_backward_errors(&self) -> usize {
+        self.count_errors(|e| matches!(e, SnowflakeError::ClockBackward(_)))
+    }
+
+    /// SequenceExhausted错误次数
+    pub fn sequence_exhausted_errors(&self) -> usize {
+        self.count_errors(|e| matches!(e, SnowflakeError::SequenceExhausted(_)))
+    }
+
+    fn count_errors(&self, predicate: impl Fn(&SnowflakeError) -> bool) -> usize {
+        self.errors.iter().filter(|e| predicate(&e.error)).count()
+    }
+}
@@ -28,8 +28,45 @@ use std::sync::{Mutex, Arc};
 use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::fs;
-use chrono::{TimeZone, FixedOffset, LocalResult};
+use chrono::{DateTime, TimeZone};
 use once_cell::sync::Lazy;
+use tracing::{debug, info, warn};
+
+use lease::{WorkerLease, LEASE_DIR_ENV};
+
+pub mod metrics;
+pub mod proto;
+pub mod client;
+pub mod segment;
+pub mod generator;
+pub mod encoding;
+pub mod id;
+pub mod obfuscation;
+pub mod clock;
+pub mod id128;
+pub mod audit;
+pub mod lease;
+pub mod atomic;
+pub mod simulation;
+pub mod reload;
+pub mod ffi;
+#[cfg(feature = "server")]
+pub mod http;
+
+pub use atomic::AtomicIdWorker;
+pub use clock::{Clock, SystemClock};
+pub use id::SnowflakeId;
+pub use generator::{AsyncIdGenerator, GlobalIdGenerator, IdGenerator, SequentialIdGenerator};
+#[cfg(feature = "tokio")]
+pub use generator::BlockingIdGenerator;
+pub use metrics::{Metrics, MetricsSnapshot};
+
+/// 基准时间戳 (上海时区 2025-03-08 00:00:00，Unix毫秒)
+/// ID中的时间戳部分是相对该时间的毫秒数
+pub const TWEPOCH_MS: u64 = 1_741_363_200_000;
+
+/// 配置文件查找路径 (按优先级排序: 当前目录、系统目录)
+pub const CONFIG_PATHS: [&str; 2] = ["snowflake.toml", "/etc/snowflake.toml"];
 
 /// 雪花算法配置结构
 /// 用于定义ID生成器的各种参数
@@ -46,6 +83,98 @@ pub struct SnowflakeConfig {
     /// 如果系统时钟往回调这个时间内，程序等待而不报错
     pub max_backward_ms: u64,
     
+    /// 序列号用尽策略
+    /// 同一毫秒内序列号用完时如何处理 (默认自旋等待下一毫秒)
+    pub sequence_exhausted_policy: SequenceExhaustedPolicy,
+    
+    /// 每毫秒序列号的起始值
+    /// 低流量时序列号几乎总是0，按 `id % N` 分片会全部落到0号分片
+    pub sequence_start: SequenceStart,
+    
+}
+
+impl SnowflakeConfig {
+    /// 按该配置的位布局解析ID
+    /// 只依赖位数配置，不需要创建生成器 (不会触发Worker ID分配)
+    pub fn decode(&self, id: u64) -> DecodedId {
+        let timestamp_shift = self.worker_id_bits + self.sequence_bits;
+        let worker_id_mask = (1u64 << self.worker_id_bits) - 1;
+        let sequence_mask = (1u64 << self.sequence_bits) - 1;
+        DecodedId {
+            id,
+            timestamp_ms: (id >> timestamp_shift) + TWEPOCH_MS,
+            worker_id: (id >> self.sequence_bits) & worker_id_mask,
+            sequence: id & sequence_mask,
+        }
+    }
+
+    /// 检查位布局: Worker ID 1-8位，序列号至少1位，两者之和小于63
+    pub fn check_layout(&self) -> Result<(), SnowflakeError> {
+        if !(1..=8).contains(&self.worker_id_bits)
+            || self.sequence_bits == 0
+            || self.worker_id_bits as u32 + self.sequence_bits as u32 >= 63
+        {
+            return Err(SnowflakeError::ConfigError(format!(
+                "无效的位布局: Worker ID {}位，序列号{}位 (Worker ID 1-8位，序列号至少1位，两者之和小于63)",
+                self.worker_id_bits, self.sequence_bits
+            )));
+        }
+        Ok(())
+    }
+
+    /// 时间戳部分的位数
+    /// 最高位保留为0，ID始终是正的i64，可以直接存入数据库的BIGINT列
+    pub fn timestamp_bits(&self) -> u8 {
+        63u8.saturating_sub(self.worker_id_bits + self.sequence_bits)
+    }
+
+    /// 该布局能表示的最大时间 (Unix毫秒时间戳)
+    pub fn max_timestamp_ms(&self) -> u64 {
+        TWEPOCH_MS + ((1u64 << self.timestamp_bits()) - 1)
+    }
+
+    /// 指定时间 (毫秒精度) 可能生成的最小ID
+    ///
+    /// 与 `max_id_for` 配合可以把时间范围查询转换为主键范围查询:
+    /// `WHERE id BETWEEN min_id_for(t1) AND max_id_for(t2)`
+    ///
+    /// 返回:
+    /// - Err(SnowflakeError::TimeOutOfRange): 时间早于基准时间或超过布局能表示的最大时间
+    pub fn min_id_for<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> Result<u64, SnowflakeError> {
+        let offset = self.timestamp_offset(datetime)?;
+        Ok(offset << (self.worker_id_bits + self.sequence_bits))
+    }
+
+    /// 指定时间 (毫秒精度) 可能生成的最大ID
+    ///
+    /// 返回:
+    /// - Err(SnowflakeError::TimeOutOfRange): 时间早于基准时间或超过布局能表示的最大时间
+    pub fn max_id_for<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> Result<u64, SnowflakeError> {
+        let shift = self.worker_id_bits + self.sequence_bits;
+        let offset = self.timestamp_offset(datetime)?;
+        Ok((offset << shift) | ((1u64 << shift) - 1))
+    }
+
+    /// 时间相对基准时间的毫秒数 (亚毫秒部分向下取整)
+    fn timestamp_offset<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> Result<u64, SnowflakeError> {
+        let ms = datetime.timestamp_millis();
+        if ms < TWEPOCH_MS as i64 {
+            return Err(SnowflakeError::TimeOutOfRange(format!(
+                "{} 早于基准时间 (Unix毫秒{})",
+                datetime.to_rfc3339(),
+                TWEPOCH_MS
+            )));
+        }
+        if ms as u64 > self.max_timestamp_ms() {
+            return Err(SnowflakeError::TimeOutOfRange(format!(
+                "{} 超过{}位时间戳能表示的最大时间 (Unix毫秒{})",
+                datetime.to_rfc3339(),
+                self.timestamp_bits(),
+                self.max_timestamp_ms()
+            )));
+        }
+        Ok(ms as u64 - TWEPOCH_MS)
+    }
 }
 
 impl Default for SnowflakeConfig {
@@ -54,12 +183,118 @@ impl Default for SnowflakeConfig {
             worker_id_bits: 8,          // 8位Worker ID (支持256个节点)
             sequence_bits: 12,          // 12位序列号 (每毫秒4096个ID)
             max_backward_ms: 10,        // 容忍10毫秒时钟回拨
+            sequence_exhausted_policy: SequenceExhaustedPolicy::Spin, // 自旋等待下一毫秒
+            sequence_start: SequenceStart::Zero, // 每毫秒从0开始
+        }
+    }
+}
+
+/// 序列号用尽策略
+/// 当同一毫秒内的序列号 (默认4096个) 全部用完时采取的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceExhaustedPolicy {
+    /// 自旋等待下一毫秒 (让出CPU时间片，延迟最低)
+    Spin,
+    /// 休眠等待下一毫秒 (节省CPU，延迟略高)
+    Sleep,
+    /// 立即返回 `SnowflakeError::SequenceExhausted` 错误，由调用方决定如何重试
+    Error,
+    /// 借用下一毫秒的时间戳，最多领先系统时钟N毫秒
+    /// 超过领先上限后退化为自旋等待
+    Borrow(u64),
+}
+
+/// 每毫秒序列号的起始值
+/// 非零起始值时序列号到达掩码上限后回绕到0，直到回到起始值才算用尽，
+/// 因此每毫秒的容量不变，但同一毫秒内回绕后的ID会小于回绕前的ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceStart {
+    /// 每毫秒从0开始 (默认，同一毫秒内ID严格递增)
+    Zero,
+    /// 每毫秒从随机值开始
+    Random,
+    /// 每毫秒的起始值在上一毫秒的基础上加1 (轮转)
+    Rotating,
+}
+
+/// 序列号容量统计
+/// 用于判断每毫秒的序列号容量是否够用，决定是否需要增大 `sequence_bits`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SequenceStats {
+    /// 至少生成过一个ID的毫秒数
+    pub active_ticks: u64,
+    /// 序列号被用尽的毫秒数
+    pub exhausted_ticks: u64,
+    /// 因序列号用尽而等待下一毫秒的次数 (Spin/Sleep策略或借用超限)
+    pub wait_count: u64,
+    /// 等待下一毫秒的累计耗时 (微秒)
+    pub wait_micros: u64,
+    /// 返回SequenceExhausted错误的次数 (Error策略)
+    pub rejected_count: u64,
+    /// 借用未来毫秒的次数 (Borrow策略)
+    pub borrowed_ticks: u64,
+    /// 借用时领先系统时钟的最大毫秒数
+    pub max_borrow_ahead_ms: u64,
+}
+
+/// 解析后的ID各组成部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedId {
+    /// 原始ID
+    pub id: u64,
+    /// 生成时间 (Unix毫秒时间戳)
+    pub timestamp_ms: u64,
+    /// 生成ID的Worker ID
+    pub worker_id: u64,
+    /// 同一毫秒内的序列号
+    pub sequence: u64,
+}
+
+/// 生成器时钟状态
+/// 用于健康检查，判断系统时钟是否落后于上次生成ID的时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockState {
+    /// 当前系统时间 (Unix毫秒时间戳)
+    pub system_ms: u64,
+    /// 上次生成ID使用的时间 (Unix毫秒时间戳，尚未生成过ID时为None)
+    pub last_ms: Option<u64>,
+    /// 系统时钟落后于上次生成时间的毫秒数 (时钟回拨或借用未来毫秒时大于0)
+    pub behind_ms: u64,
+    /// 当前是否运行在借用的未来时间戳上
+    pub borrowing: bool,
+}
+
+/// Worker ID的分配方式
+/// 记录当前Worker ID是通过哪种策略得到的，便于排查ID冲突
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerIdStrategy {
+    /// 环境变量 SNOWFLAKE_WORKER_ID
+    Env,
+    /// 租约目录中的文件锁 (环境变量 SNOWFLAKE_WORKER_LEASE_DIR)
+    Lease,
+    /// 配置文件中的数据中心+机器ID
+    Config,
+    /// 本机IP地址
+    Ip,
+    /// 主机名 (IP解析失败时的最后备选)
+    Hostname,
+}
+
+impl WorkerIdStrategy {
+    /// 策略名称 (用于日志字段和指标标签)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkerIdStrategy::Env => "env",
+            WorkerIdStrategy::Lease => "lease",
+            WorkerIdStrategy::Config => "config",
+            WorkerIdStrategy::Ip => "ip",
+            WorkerIdStrategy::Hostname => "hostname",
         }
     }
 }
 
 /// 雪花算法错误类型定义
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SnowflakeError {
     /// 时钟回拨错误 (系统时钟往回调整)
     ClockBackward(String),
@@ -67,6 +302,14 @@ pub enum SnowflakeError {
     ConfigError(String),
     /// 网络错误 (获取IP地址失败等)
     NetworkError(String),
+    /// 序列号用尽错误 (同一毫秒内ID已发完，且策略为Error)
+    SequenceExhausted(String),
+    /// 协议错误 (远程发号请求不合法、响应格式错误等)
+    ProtocolError(String),
+    /// 时间超出范围 (早于基准时间或超过ID布局能表示的最大时间)
+    TimeOutOfRange(String),
+    /// IO错误 (读写持久化文件失败)
+    IoError(String),
 }
 
 impl std::fmt::Display for SnowflakeError {
@@ -75,6 +318,10 @@ impl std::fmt::Display for SnowflakeError {
             SnowflakeError::ClockBackward(msg) => write!(f, "时钟回拨错误: {}", msg),
             SnowflakeError::ConfigError(msg) => write!(f, "配置错误: {}", msg),
             SnowflakeError::NetworkError(msg) => write!(f, "网络错误: {}", msg),
+            SnowflakeError::SequenceExhausted(msg) => write!(f, "序列号用尽: {}", msg),
+            SnowflakeError::ProtocolError(msg) => write!(f, "协议错误: {}", msg),
+            SnowflakeError::TimeOutOfRange(msg) => write!(f, "时间超出范围: {}", msg),
+            SnowflakeError::IoError(msg) => write!(f, "IO错误: {}", msg),
         }
     }
 }
@@ -97,10 +344,31 @@ pub struct SnowflakeIdWorker {
     twepoch: u64,
     /// 当前序列号 (同一毫秒内递增)
     sequence: u64,
+    /// 当前毫秒的序列号起始值 (序列号回到该值表示用尽)
+    tick_start: u64,
+    /// 随机起始值使用的xorshift状态
+    rng_state: u64,
     /// 上次生成ID的时间戳 (用于检测时钟回拨)
     last_timestamp: i64,
     /// 当前Worker ID (标识这台机器/进程)
     worker_id: u8,
+    /// Worker ID的分配方式
+    strategy: WorkerIdStrategy,
+    /// 当前是否运行在借用的未来时间戳上 (Borrow策略)
+    borrowing: bool,
+    /// 借用时的借用上限 (毫秒)
+    /// 重新加载把策略改为其他策略后，仍按该上限沿用借用的毫秒，直到系统时钟追上
+    borrow_ahead_ms: u64,
+    /// 最近一次被统计为用尽的毫秒 (避免同一毫秒重复计数)
+    exhausted_tick: i64,
+    /// 序列号容量统计
+    stats: SequenceStats,
+    /// 运行指标 (原子计数器，可在锁外读取)
+    metrics: Arc<Metrics>,
+    /// 时钟 (默认为系统时钟)
+    clock: Arc<dyn Clock>,
+    /// Worker ID租约 (Lease策略)，生成器释放时一起释放
+    lease: Option<WorkerLease>,
 }
 
 impl SnowflakeIdWorker {
@@ -113,7 +381,46 @@ impl SnowflakeIdWorker {
     /// - Ok(SnowflakeIdWorker): 成功创建的生成器实例
     /// - Err(SnowflakeError): 创建失败的错误信息
     pub fn new(config: Option<SnowflakeConfig>) -> Result<Self, SnowflakeError> {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+
+    /// 使用指定时钟创建ID生成器 (测试中可以注入模拟时钟)
+    pub fn with_clock(config: Option<SnowflakeConfig>, clock: Arc<dyn Clock>) -> Result<Self, SnowflakeError> {
+        let worker = Self::create(config, clock, true)?;
+        // 环境变量、配置映射、IP和主机名都可能给出超出位数的Worker ID，
+        // 移位后会进入时间戳部分，与其他节点的ID重复
+        let bits = worker.config.worker_id_bits;
+        if bits < 8 && worker.worker_id >> bits != 0 {
+            return Err(SnowflakeError::ConfigError(format!(
+                "Worker ID {} (来自{}) 超出{}位能表示的范围",
+                worker.worker_id,
+                worker.strategy.as_str(),
+                bits
+            )));
+        }
+        Ok(worker)
+    }
+
+    /// 按创建生成器时的优先级确定Worker ID，但不获取租约 (用于检查配置)
+    ///
+    /// 返回 (Worker ID, 分配方式)。设置了租约目录时Worker ID要到获取租约时才能确定，
+    /// 返回 (None, WorkerIdStrategy::Lease)，不会创建目录或锁文件。
+    /// 不检查Worker ID是否超出 `worker_id_bits` 的范围 (由调用方报告)，创建生成器时超出范围会返回错误
+    pub fn resolve_worker_id(
+        config: Option<SnowflakeConfig>,
+    ) -> Result<(Option<u8>, WorkerIdStrategy), SnowflakeError> {
+        let worker = Self::create(config, Arc::new(SystemClock), false)?;
+        let worker_id = (worker.strategy != WorkerIdStrategy::Lease).then_some(worker.worker_id);
+        Ok((worker_id, worker.strategy))
+    }
+
+    fn create(
+        config: Option<SnowflakeConfig>,
+        clock: Arc<dyn Clock>,
+        acquire_lease: bool,
+    ) -> Result<Self, SnowflakeError> {
         let config = config.unwrap_or_default();
+        config.check_layout()?;
         
         // 计算各种位移量和掩码
         // Worker ID位移 = 序列号位数 (序列号在最右边)
@@ -123,76 +430,95 @@ impl SnowflakeIdWorker {
         // 序列号掩码 = 2^序列号位数 - 1 (用于限制序列号范围)
         let sequence_mask = (1u64 << config.sequence_bits) - 1;
         
-        // 计算基准时间戳 (上海时区 2025-03-08 00:00:00)
-        let shanghai_offset = FixedOffset::east_opt(8 * 3600)
-            .ok_or_else(|| SnowflakeError::ConfigError("无法创建上海时区".to_string()))?;
-        let epoch_dt = match shanghai_offset.with_ymd_and_hms(2025, 3, 8, 0, 0, 0) {
-            LocalResult::Single(dt) => Ok(dt),
-            _ => Err(SnowflakeError::ConfigError("无效的基准日期".to_string())),
-        }?;
-        let twepoch = epoch_dt.timestamp_millis() as u64;
-        
         // 创建生成器实例
         let mut worker = Self {
             config,
             worker_id_shift,
             timestamp_shift,
             sequence_mask,
-            twepoch,
+            twepoch: TWEPOCH_MS,
             sequence: 0,           // 序列号从0开始
+            tick_start: 0,
+            rng_state: Self::random_seed(),
             last_timestamp: -1,    // 上次时间戳初始化为-1
             worker_id: 0,          // Worker ID稍后初始化
+            strategy: WorkerIdStrategy::Ip,
+            borrowing: false,
+            borrow_ahead_ms: 0,
+            exhausted_tick: -1,
+            stats: SequenceStats::default(),
+            metrics: Arc::new(Metrics::new()),
+            clock,
+            lease: None,
         };
         
         // 初始化Worker ID (这是关键步骤，决定这台机器的唯一标识)
-        worker.init_worker_id()?;
+        worker.init_worker_id(acquire_lease)?;
         Ok(worker)
     }
     
     /// 初始化Worker ID
     /// 按照优先级依次尝试不同的方式获取Worker ID:
     /// 1. 环境变量 (最高优先级)
-    /// 2. 数据中心+机器ID配置  
-    /// 3. IP段自动分配 (最低优先级)
-    fn init_worker_id(&mut self) -> Result<(), SnowflakeError> {
+    /// 2. 租约目录中的文件锁
+    /// 3. 数据中心+机器ID配置  
+    /// 4. IP段自动分配 (最低优先级)
+    ///
+    /// acquire_lease为false时遇到租约目录只记录分配方式，不获取租约
+    fn init_worker_id(&mut self, acquire_lease: bool) -> Result<(), SnowflakeError> {
         // 方式1: 从环境变量获取 (最高优先级)
         // 用法: export SNOWFLAKE_WORKER_ID=50
         if let Ok(worker_id_str) = env::var("SNOWFLAKE_WORKER_ID") {
             if let Ok(worker_id) = worker_id_str.parse::<u8>() {
                 self.worker_id = worker_id;
-                println!("✅ 使用环境变量Worker ID: {}", worker_id);
+                self.strategy = WorkerIdStrategy::Env;
+                info!(worker_id, strategy = "env", "使用环境变量Worker ID");
                 return Ok(());
             }
+            warn!(value = %worker_id_str, "环境变量SNOWFLAKE_WORKER_ID不是合法的Worker ID，已忽略");
         }
         
-        // 方式2: 从配置文件获取数据中心+机器ID
+        // 方式2: 在租约目录中获取空闲的Worker ID (同一台机器上的多个进程)
+        // 用法: export SNOWFLAKE_WORKER_LEASE_DIR=/var/run/snowflake
+        if let Ok(dir) = env::var(LEASE_DIR_ENV) {
+            if !acquire_lease {
+                self.strategy = WorkerIdStrategy::Lease;
+                return Ok(());
+            }
+            let max_worker_id = ((1u16 << self.config.worker_id_bits.min(8)) - 1) as u8;
+            let lease = WorkerLease::acquire(&dir, max_worker_id)?;
+            self.worker_id = lease.worker_id();
+            self.strategy = WorkerIdStrategy::Lease;
+            info!(worker_id = self.worker_id, strategy = "lease", path = %lease.path().display(), "使用租约Worker ID");
+            self.lease = Some(lease);
+            return Ok(());
+        }
+
+        // 方式3: 从配置文件获取数据中心+机器ID
         // 检查snowflake.toml中的datacenter_id和machine_id配置
         if let Some(worker_id) = self.try_config_mapping()? {
             self.worker_id = worker_id;
-            println!("✅ 使用配置文件映射Worker ID: {}", worker_id);
+            self.strategy = WorkerIdStrategy::Config;
+            info!(worker_id, strategy = "config", "使用配置文件映射Worker ID");
             return Ok(());
         }
         
-        // 方式3: 基于IP段自动分配 (最后备选)
+        // 方式4: 基于IP段自动分配 (最后备选)
         // 根据本机IP地址自动计算Worker ID
-        self.worker_id = self.generate_ip_based_worker_id()?;
-        println!("✅ 使用IP段自动分配Worker ID: {}", self.worker_id);
+        let (worker_id, strategy) = self.generate_ip_based_worker_id()?;
+        self.worker_id = worker_id;
+        self.strategy = strategy;
+        info!(worker_id, strategy = strategy.as_str(), "使用IP段自动分配Worker ID");
         Ok(())
     }
     
     /// 尝试从配置文件获取数据中心+机器ID配置
     /// 查找snowflake.toml文件，解析数据中心和机器ID
     fn try_config_mapping(&self) -> Result<Option<u8>, SnowflakeError> {
-        // 定义配置文件查找路径 (按优先级排序)
-        let config_paths = [
-            "snowflake.toml",           // 当前目录
-            "/etc/snowflake.toml"       // 系统目录
-        ];
-        
         // 依次尝试读取配置文件
-        for path in &config_paths {
+        for path in &CONFIG_PATHS {
             if let Ok(content) = fs::read_to_string(path) {
-                println!("📄 找到配置文件: {}", path);
+                debug!(path, "找到配置文件");
                 return self.parse_config(&content);
             }
         }
@@ -204,47 +530,12 @@ impl SnowflakeIdWorker {
     /// 解析配置文件内容
     /// 只支持数据中心+机器ID配置方式
     fn parse_config(&self, content: &str) -> Result<Option<u8>, SnowflakeError> {
-        let mut datacenter_id: Option<u8> = None;
-        let mut machine_id: Option<u8> = None;
-        
-        // 逐行解析配置文件
-        for line in content.lines() {
-            let line = line.trim();
-            // 跳过空行和注释行
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-            
-            // 解析 datacenter_id = 1
-            if line.starts_with("datacenter_id") {
-                if let Some(value) = line.split('=').nth(1) {
-                    datacenter_id = value.trim().parse::<u8>().ok();
-                }
-            }
-            // 解析 machine_id = 5  
-            else if line.starts_with("machine_id") {
-                if let Some(value) = line.split('=').nth(1) {
-                    machine_id = value.trim().parse::<u8>().ok();
-                }
-            }
-        }
-        
-        // 使用数据中心+机器ID组合计算
-        if let (Some(dc_id), Some(m_id)) = (datacenter_id, machine_id) {
-            // Worker ID = (数据中心ID << 6) | 机器ID
-            // 高2位存储数据中心ID，低6位存储机器ID
-            let worker_id = ((dc_id & 0x03) << 6) | (m_id & 0x3F);
-            println!("🏢 数据中心+机器ID: DC{} + M{} → Worker ID {}", dc_id, m_id, worker_id);
-            return Ok(Some(worker_id));
-        }
-        
-        // 配置文件中没有找到适用的配置
-        Ok(None)
+        Ok(parse_worker_id_mapping(content))
     }
     
     /// 基于IP段自动分配Worker ID
     /// 这是最后的备选方案，根据本机IP地址计算Worker ID
-    fn generate_ip_based_worker_id(&self) -> Result<u8, SnowflakeError> {
+    fn generate_ip_based_worker_id(&self) -> Result<(u8, WorkerIdStrategy), SnowflakeError> {
         let ip_str = self.get_local_ip()?;
         
         // 尝试解析IPv4地址
@@ -254,8 +545,8 @@ impl SnowflakeIdWorker {
             // 公式: ((第3段 & 0x0F) << 4) | (第4段 & 0x0F)
             // 例如: 192.168.1.100 → ((1 & 15) << 4) | (100 & 15) = 16 + 4 = 20
             let worker_id = ((octets[2] & 0x0F) << 4) | (octets[3] & 0x0F);
-            println!("🌐 IP段计算: {} → Worker ID {}", ip_str, worker_id);
-            return Ok(worker_id);
+            debug!(ip = %ip_str, worker_id, "IP段计算Worker ID");
+            return Ok((worker_id, WorkerIdStrategy::Ip));
         }
         
         // 如果IP解析失败，使用简单的主机名计算作为最后备选
@@ -266,8 +557,8 @@ impl SnowflakeIdWorker {
         
         // 使用主机名长度和首字符简单计算Worker ID
         let worker_id = (hostname.len() % 256) as u8;
-        println!("🔗 主机名计算: {} → Worker ID {}", hostname, worker_id);
-        Ok(worker_id)
+        warn!(hostname = %hostname, worker_id, "IP解析失败，使用主机名计算Worker ID");
+        Ok((worker_id, WorkerIdStrategy::Hostname))
     }
     
     /// 快速获取本机IP地址
@@ -288,10 +579,7 @@ impl SnowflakeIdWorker {
     /// 获取当前时间戳 (毫秒)
     /// 返回从基准时间(2025-03-08)开始的毫秒数
     fn time_gen(&self) -> Result<u64, SnowflakeError> {
-        let now_ms = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|e| SnowflakeError::ClockBackward(format!("系统时钟错误: {}", e)))?
-            .as_millis() as u64;
+        let now_ms = self.clock.now_ms()?;
         
         // 检查当前时间是否在基准时间之后
         if now_ms >= self.twepoch {
@@ -307,35 +595,48 @@ impl SnowflakeIdWorker {
         // 获取当前时间戳
         let mut timestamp = self.time_gen()? as i64;
         
-        // 检查时钟回拨问题
-        if timestamp < self.last_timestamp {
-            let diff = (self.last_timestamp - timestamp) as u64;
-            
-            // 如果回拨时间在容忍范围内，等待时钟追上
-            if diff <= self.config.max_backward_ms {
-                println!("⏰ 检测到时钟回拨{}ms，等待中...", diff);
-                thread::sleep(Duration::from_millis(diff + 1));
-                timestamp = self.time_gen()? as i64;
+        // 借用了未来时间戳时，系统时钟在借用上限内落后于last_timestamp是预期行为，继续沿用借用的毫秒；
+        // 落后超过借用上限说明借用期间时钟被回拨，按时钟回拨处理
+        if self.borrowing {
+            let max_ahead_ms = self.borrow_ahead_ms as i64;
+            if timestamp > self.last_timestamp || self.last_timestamp - timestamp > max_ahead_ms {
+                self.borrowing = false;
             } else {
-                // 时钟回拨超出容忍范围，抛出错误
-                return Err(SnowflakeError::ClockBackward(format!(
-                    "时钟回拨过大: {}ms，超出容忍范围{}ms", diff, self.config.max_backward_ms
-                )));
+                timestamp = self.last_timestamp;
             }
         }
         
+        // 检查时钟回拨问题
+        // 回拨时间在容忍范围内时等待时钟追上，超出时返回错误
+        if timestamp < self.last_timestamp {
+            let metrics = &self.metrics;
+            let now_ms = clock::wait_out_backward(
+                self.clock.as_ref(),
+                timestamp as u64 + self.twepoch,
+                self.last_timestamp as u64 + self.twepoch,
+                self.config.max_backward_ms,
+                |diff, rejected| metrics.record_clock_backward(diff, rejected),
+            )?;
+            timestamp = (now_ms - self.twepoch) as i64;
+        }
+        
         // 处理序列号逻辑
         if timestamp == self.last_timestamp {
             // 同一毫秒内，序列号递增
-            self.sequence = (self.sequence + 1) & self.sequence_mask;
+            let sequence = (self.sequence + 1) & self.sequence_mask;
             
-            // 如果序列号用尽 (达到4096)，等待下一毫秒
-            if self.sequence == 0 {
-                timestamp = self.wait_next_millis(self.last_timestamp)?;
+            // 如果序列号用尽 (达到4096)，按策略处理
+            if sequence == self.tick_start {
+                timestamp = self.on_sequence_exhausted()?;
+                self.sequence = self.next_tick_start();
+                self.stats.active_ticks += 1;
+            } else {
+                self.sequence = sequence;
             }
         } else {
-            // 不同毫秒，序列号重置为0
-            self.sequence = 0;
+            // 不同毫秒，序列号重置为起始值 (默认为0)
+            self.sequence = self.next_tick_start();
+            self.stats.active_ticks += 1;
         }
         
         // 更新上次时间戳
@@ -347,26 +648,204 @@ impl SnowflakeIdWorker {
             | ((self.worker_id as u64) << self.worker_id_shift) // Worker ID左移到中位
             | self.sequence;                                     // 序列号在低位
         
+        self.metrics.record_id();
         Ok(id)
     }
     
+    /// 计算新一毫秒的序列号起始值
+    fn next_tick_start(&mut self) -> u64 {
+        self.tick_start = match self.config.sequence_start {
+            SequenceStart::Zero => 0,
+            SequenceStart::Random => self.next_random() & self.sequence_mask,
+            SequenceStart::Rotating => (self.tick_start + 1) & self.sequence_mask,
+        };
+        self.tick_start
+    }
+    
+    /// 生成下一个伪随机数 (xorshift64*)
+    /// 只用于打散序列号起始值，不需要密码学强度
+    fn next_random(&mut self) -> u64 {
+        let mut x = self.rng_state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.rng_state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+    
+    /// 生成随机数种子
+    /// 混合当前纳秒时间和进程ID，保证同一台机器上的多个进程种子不同
+    fn random_seed() -> u64 {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        // splitmix64 混合，且保证种子非0 (xorshift的0状态不会变化)
+        let mut z = nanos ^ ((std::process::id() as u64) << 32) ^ 0x9E37_79B9_7F4A_7C15;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        (z ^ (z >> 31)) | 1
+    }
+    
+    /// 处理序列号用尽
+    /// 根据配置的策略返回新的时间戳，或返回SequenceExhausted错误
+    fn on_sequence_exhausted(&mut self) -> Result<i64, SnowflakeError> {
+        // 同一毫秒只统计一次 (Error策略下同一毫秒会多次触发)
+        if self.exhausted_tick != self.last_timestamp {
+            self.exhausted_tick = self.last_timestamp;
+            self.stats.exhausted_ticks += 1;
+        }
+        
+        match self.config.sequence_exhausted_policy {
+            SequenceExhaustedPolicy::Spin => self.wait_next_millis(self.last_timestamp, false),
+            SequenceExhaustedPolicy::Sleep => self.wait_next_millis(self.last_timestamp, true),
+            SequenceExhaustedPolicy::Error => {
+                // 序列号保持在起始值的前一个值，同一毫秒内后续调用继续报错，不会重复发放
+                self.stats.rejected_count += 1;
+                Err(SnowflakeError::SequenceExhausted(format!(
+                    "第{}毫秒内的{}个序列号已用尽",
+                    self.last_timestamp,
+                    self.sequence_mask + 1
+                )))
+            }
+            SequenceExhaustedPolicy::Borrow(max_ahead_ms) => {
+                let next = self.last_timestamp + 1;
+                let now = self.time_gen()? as i64;
+                let ahead = (next - now).max(0) as u64;
+                
+                // 超出借用上限时，等待系统时钟追到可借用的范围内
+                if ahead > max_ahead_ms {
+                    self.wait_next_millis(next - 1 - max_ahead_ms as i64, false)?;
+                }
+                
+                let now = self.time_gen()? as i64;
+                if now >= next {
+                    // 等待期间系统时钟已经追上，无需借用
+                    return Ok(now);
+                }
+                let ahead = (next - now) as u64;
+                self.borrowing = true;
+                self.borrow_ahead_ms = max_ahead_ms;
+                self.stats.borrowed_ticks += 1;
+                self.stats.max_borrow_ahead_ms = self.stats.max_borrow_ahead_ms.max(ahead);
+                Ok(next)
+            }
+        }
+    }
+    
     /// 等待到下一毫秒
     /// 当同一毫秒内序列号用尽时调用
-    fn wait_next_millis(&self, last_timestamp: i64) -> Result<i64, SnowflakeError> {
+    fn wait_next_millis(&mut self, last_timestamp: i64, sleep: bool) -> Result<i64, SnowflakeError> {
+        let start = std::time::Instant::now();
         let mut timestamp = self.time_gen()? as i64;
         // 循环等待，直到时间戳发生变化
         while timestamp <= last_timestamp {
-            thread::yield_now(); // 让出CPU时间片，提高效率
+            if sleep {
+                // 休眠到预计的下一毫秒，节省CPU
+                self.clock.sleep(Duration::from_millis((last_timestamp - timestamp + 1) as u64));
+            } else {
+                thread::yield_now(); // 让出CPU时间片，提高效率
+            }
             timestamp = self.time_gen()? as i64;
         }
+        self.stats.wait_count += 1;
+        self.stats.wait_micros += start.elapsed().as_micros() as u64;
         Ok(timestamp)
     }
     
+    /// 获取序列号容量统计
+    pub fn sequence_stats(&self) -> SequenceStats {
+        self.stats
+    }
+    
+    /// 获取运行指标 (可在锁外持有并读取)
+    pub fn metrics(&self) -> Arc<Metrics> {
+        Arc::clone(&self.metrics)
+    }
+    
+    /// 获取当前指标快照
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot::new(&self.metrics, self.stats, self.worker_id, self.strategy)
+    }
+    
+    /// 当前配置 (重新加载后反映最新的值)
+    pub fn config(&self) -> &SnowflakeConfig {
+        &self.config
+    }
+    
     /// 获取当前Worker ID
-    #[allow(dead_code)]
     pub fn get_worker_id(&self) -> u8 {
         self.worker_id
     }
+    
+    /// 获取Worker ID的分配方式
+    pub fn get_worker_id_strategy(&self) -> WorkerIdStrategy {
+        self.strategy
+    }
+    
+    /// 按当前生成器的位布局和基准时间解析ID
+    pub fn decode(&self, id: u64) -> DecodedId {
+        self.config.decode(id)
+    }
+
+    /// 指定时间可能生成的最小ID (见 `SnowflakeConfig::min_id_for`)
+    pub fn min_id_for<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> Result<u64, SnowflakeError> {
+        self.config.min_id_for(datetime)
+    }
+
+    /// 指定时间可能生成的最大ID (见 `SnowflakeConfig::max_id_for`)
+    pub fn max_id_for<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> Result<u64, SnowflakeError> {
+        self.config.max_id_for(datetime)
+    }
+    
+    /// 获取时钟状态
+    pub fn clock_state(&self) -> ClockState {
+        let system_ms = self.clock.now_ms().unwrap_or(0);
+        let last_ms = (self.last_timestamp >= 0).then(|| self.last_timestamp as u64 + self.twepoch);
+        ClockState {
+            system_ms,
+            last_ms,
+            behind_ms: last_ms.map_or(0, |last| last.saturating_sub(system_ms)),
+            borrowing: self.borrowing,
+        }
+    }
+}
+
+/// 解析snowflake.toml内容中的数据中心+机器ID映射
+///
+/// Worker ID = ((datacenter_id & 0x03) << 6) | (machine_id & 0x3F)，
+/// 两项都存在且是0-255的整数时返回Some，否则返回None (改用其他分配方式)
+pub fn parse_worker_id_mapping(content: &str) -> Option<u8> {
+    let mut datacenter_id: Option<u8> = None;
+    let mut machine_id: Option<u8> = None;
+    
+    for (_, key, value) in config_entries(content) {
+        match key {
+            "datacenter_id" => datacenter_id = value.parse::<u8>().ok(),
+            "machine_id" => machine_id = value.parse::<u8>().ok(),
+            _ => {}
+        }
+    }
+    
+    // 使用数据中心+机器ID组合计算
+    let (dc_id, m_id) = (datacenter_id?, machine_id?);
+    // Worker ID = (数据中心ID << 6) | 机器ID
+    // 高2位存储数据中心ID，低6位存储机器ID
+    let worker_id = ((dc_id & 0x03) << 6) | (m_id & 0x3F);
+    debug!(datacenter_id = dc_id, machine_id = m_id, worker_id, "数据中心+机器ID映射");
+    Some(worker_id)
+}
+
+/// 逐行读取snowflake.toml中的 `键 = 值`
+///
+/// 跳过空行和注释行，去掉行尾的 `# 注释` 和值两侧的引号。
+/// 返回 (行号, 键, 值)，行号从1开始。Worker ID映射和 `reload::FileConfig` 都用它解析
+pub(crate) fn config_entries(content: &str) -> impl Iterator<Item = (usize, &str, &str)> {
+    content.lines().enumerate().filter_map(|(index, line)| {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let (key, value) = line.split_once('=')?;
+        Some((index + 1, key.trim(), value.trim().trim_matches('"')))
+    })
 }
 
 // ============================================================================
@@ -382,12 +861,51 @@ static GLOBAL_CONFIG: Lazy<Arc<Mutex<SnowflakeConfig>>> = Lazy::new(|| {
 
 /// 全局共享的Worker实例
 /// 所有线程共享一个生成器，使用全局锁保证线程安全
+/// 首次使用时读取全局配置创建，因此 `set_global_config` 需在第一次生成ID之前调用。
+/// 配置文件 (`CONFIG_PATHS`) 中出现的项优先于全局配置，见 `reload::startup_config`
 static GLOBAL_WORKER: Lazy<Arc<Mutex<SnowflakeIdWorker>>> = Lazy::new(|| {
+    // init_global_worker预先创建的Worker优先
+    if let Some(worker) = PENDING_GLOBAL_WORKER.lock().unwrap().take() {
+        return Arc::new(Mutex::new(worker));
+    }
+    let config = reload::startup_config(GLOBAL_CONFIG.lock().unwrap().clone());
     Arc::new(Mutex::new(
-        SnowflakeIdWorker::new(None).expect("无法创建全局Worker")
+        SnowflakeIdWorker::new(Some(config)).expect("无法创建全局Worker")
     ))
 });
 
+/// 预先创建、等待成为全局Worker的实例
+/// 创建失败时可以返回错误，而不是在第一次生成ID时panic
+static PENDING_GLOBAL_WORKER: Mutex<Option<SnowflakeIdWorker>> = Mutex::new(None);
+
+/// 使用指定配置显式创建全局Worker
+///
+/// 与第一次生成ID时隐式创建不同，Worker ID分配失败时返回错误而不是panic，
+/// 服务启动时调用可以尽早发现问题并退出。配置文件中出现的项覆盖 `config` (见 `reload::startup_config`)。
+///
+/// 返回:
+/// - Ok(true): 全局Worker已按该配置创建
+/// - Ok(false): 全局Worker已经存在 (已初始化或已生成过ID)，配置未生效
+/// - Err(SnowflakeError): 创建Worker失败 (如租约目录不可用)，之后仍可重试
+pub fn init_global_worker(config: SnowflakeConfig) -> Result<bool, SnowflakeError> {
+    static INIT_LOCK: Mutex<()> = Mutex::new(());
+    let _guard = INIT_LOCK.lock().unwrap();
+    if Lazy::get(&GLOBAL_WORKER).is_some() {
+        return Ok(false);
+    }
+    let config = reload::startup_config(config);
+    let worker = SnowflakeIdWorker::new(Some(config.clone()))?;
+    *PENDING_GLOBAL_WORKER.lock().unwrap() = Some(worker);
+    Lazy::force(&GLOBAL_WORKER);
+    // 其他线程抢先创建了全局Worker时，预先创建的实例没有被使用 (丢弃时释放租约)，
+    // 全局配置也保持不变
+    let created = PENDING_GLOBAL_WORKER.lock().unwrap().take().is_none();
+    if created {
+        set_global_config(config);
+    }
+    Ok(created)
+}
+
 // ============================================================================
 // 公共API接口
 // ============================================================================
@@ -402,7 +920,24 @@ static GLOBAL_WORKER: Lazy<Arc<Mutex<SnowflakeIdWorker>>> = Lazy::new(|| {
 /// - Err(SnowflakeError): 生成失败的错误信息
 pub fn get_next_id() -> Result<u64, SnowflakeError> {
     // 全局锁模式: 所有线程竞争同一个锁
-    GLOBAL_WORKER.lock().unwrap().next_id()
+    let start = std::time::Instant::now();
+    let mut worker = GLOBAL_WORKER.lock().unwrap();
+    worker.metrics.record_lock_wait(start.elapsed());
+    worker.next_id()
+}
+
+/// 批量获取ID
+/// 
+/// 只获取一次全局锁，连续生成count个ID，适合发号服务等批量场景
+/// 
+/// 返回:
+/// - Ok(Vec<u64>): 按生成顺序排列的ID
+/// - Err(SnowflakeError): 任意一个ID生成失败时返回错误，已生成的ID被丢弃
+pub fn get_next_ids(count: usize) -> Result<Vec<u64>, SnowflakeError> {
+    let start = std::time::Instant::now();
+    let mut worker = GLOBAL_WORKER.lock().unwrap();
+    worker.metrics.record_lock_wait(start.elapsed());
+    (0..count).map(|_| worker.next_id()).collect()
 }
 
 /// 设置全局配置
@@ -415,9 +950,81 @@ pub fn set_global_config(config: SnowflakeConfig) {
     *GLOBAL_CONFIG.lock().unwrap() = config;
 }
 
+/// 获取全局Worker的序列号容量统计
+/// 
+/// 返回:
+/// - SequenceStats: 用尽毫秒数、等待次数、借用次数等计数
+pub fn get_sequence_stats() -> SequenceStats {
+    GLOBAL_WORKER.lock().unwrap().sequence_stats()
+}
+
+/// 全局生成器当前使用的配置
+///
+/// 全局Worker已经创建时以它为准 (实际生效的位布局、重新加载后的策略)，
+/// 否则为 `set_global_config` 设置的配置。不会触发全局Worker的创建。
+/// 所有按全局布局解析ID、计算ID边界的接口都通过它读取配置。
+pub(crate) fn global_config() -> SnowflakeConfig {
+    match Lazy::get(&GLOBAL_WORKER) {
+        Some(worker) => worker.lock().unwrap().config().clone(),
+        None => GLOBAL_CONFIG.lock().unwrap().clone(),
+    }
+}
+
+/// 解析ID (使用全局生成器的位布局和基准时间，见 `global_config`)
+pub fn decode_id(id: u64) -> DecodedId {
+    global_config().decode(id)
+}
+
+/// 按全局生成器的配置计算指定时间可能生成的最小ID
+/// 见 `SnowflakeConfig::min_id_for`
+pub fn min_id_for<Tz: TimeZone>(datetime: &DateTime<Tz>) -> Result<u64, SnowflakeError> {
+    global_config().min_id_for(datetime)
+}
+
+/// 按全局生成器的配置计算指定时间可能生成的最大ID
+/// 见 `SnowflakeConfig::max_id_for`
+pub fn max_id_for<Tz: TimeZone>(datetime: &DateTime<Tz>) -> Result<u64, SnowflakeError> {
+    global_config().max_id_for(datetime)
+}
+
+/// 获取全局Worker的Worker ID和分配方式
+pub fn get_worker_info() -> (u8, WorkerIdStrategy) {
+    let worker = GLOBAL_WORKER.lock().unwrap();
+    (worker.get_worker_id(), worker.get_worker_id_strategy())
+}
+
+/// 获取全局Worker的时钟状态
+pub fn get_clock_state() -> ClockState {
+    GLOBAL_WORKER.lock().unwrap().clock_state()
+}
+
+/// 获取全局Worker的指标快照
+pub fn get_metrics() -> MetricsSnapshot {
+    GLOBAL_WORKER.lock().unwrap().metrics_snapshot()
+}
+
+/// 渲染全局Worker的指标 (Prometheus文本格式)
+/// 
+/// 可直接作为任意HTTP服务 `/metrics` 接口的响应体，
+/// Content-Type 为 `text/plain; version=0.0.4`
+pub fn render_prometheus() -> String {
+    get_metrics().render_prometheus()
+}
+
+/// 初始化日志输出
+/// 诊断日志统一写到stderr，不干扰stdout上的程序输出
+/// 日志级别通过RUST_LOG环境变量控制，默认info
+pub fn init_logging() {
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .try_init();
+}
+
 /// 简单演示程序
 /// 展示雪花算法的基本功能和性能
-#[allow(dead_code)]
 pub fn demo() {
     println!("=== 🔒 全局锁雪花算法演示 ===");
     
@@ -452,14 +1059,18 @@ pub fn demo() {
     
     // 显示配置信息
     println!("\n⚙️  配置信息:");
-    let config = GLOBAL_CONFIG.lock().unwrap();
+    let config = global_config();
     println!("模式: 🔒 全局锁模式 (所有线程竞争)");
     println!("IP获取模式: ✅ 开启");
     println!("时钟回拨容忍: {}ms", config.max_backward_ms);
+    println!("序列号用尽策略: {:?}", config.sequence_exhausted_policy);
+    println!("序列号起始值: {:?}", config.sequence_start);
+    
+    // 显示序列号容量统计
+    let stats = get_sequence_stats();
+    println!("\n📈 序列号容量统计:");
+    println!("活跃毫秒数: {}", stats.active_ticks);
+    println!("用尽毫秒数: {} ({:.2}%)", stats.exhausted_ticks,
+        stats.exhausted_ticks as f64 / stats.active_ticks.max(1) as f64 * 100.0);
+    println!("等待次数: {}，累计等待: {}μs", stats.wait_count, stats.wait_micros);
 }
-
-/// 主函数入口
-#[allow(dead_code)]
-fn main() {
-    demo();
-}
\ No newline at end of file
@@ -2,24 +2,67 @@
 全局锁雪花算法压力测试
 
 测试项目:
-1. 大规模多线程并发测试
-2. 长时间耐久性测试  
-3. 分布式唯一性验证
-4. 性能基准测试
+1. 大规模多线程并发测试 (concurrency)
+2. 长时间耐久性测试 (endurance)
+3. 大量ID的吞吐测试 (memory)
+
+每项测试都可以在多种生成器模式下运行并对比:
+- global: `get_next_id()`，所有线程竞争全局锁
+- batch: `get_next_ids()`，每次加锁生成一批
+- worker: 共享的独立 `Mutex<SnowflakeIdWorker>`
+
+每次调用的延迟记录在直方图中 (p50/p99/p999/max)，锁等待时间来自生成器指标
+(global/batch) 或测试程序自己的计时 (worker)。`--report` 把结果写成JSON文件，
+便于对比不同版本的性能。
+
+唯一性校验 (所有测试项目):
+同一个生成器发出的ID严格递增，每个线程拿到的ID序列也严格递增。工作线程只比较相邻ID，
+并把ID按块发给校验线程；校验线程对各线程的有序序列做k路归并，相邻相等即为重复。
+所有线程都已发出的ID之下的部分可以立即归并并丢弃，因此内存占用只与线程数和块大小有关，
+耐久性测试可以校验数十亿个ID。
 
 作者: zdrawai团队
 版本: 2.1.0 - 全局锁简化版
 */
 
-use std::collections::HashSet;
-use std::sync::{Arc, Mutex, atomic::{AtomicU64, Ordering}};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::{Mutex, atomic::{AtomicBool, AtomicU64, Ordering}};
+use std::sync::mpsc::{self, SyncSender};
 use std::thread;
 use std::time::{Duration, Instant};
 
-#[path = "snowflake.rs"]
-mod snowflake;
+use chrono::Local;
+use serde_json::{json, Value};
+use snowflake::{
+    get_metrics, get_next_id, get_next_ids, init_logging, set_global_config, SnowflakeConfig,
+    SnowflakeError, SnowflakeIdWorker,
+};
+
+/// 用法说明
+const USAGE: &str = "\
+用法: stress_test [选项]
 
-use snowflake::{get_next_id, set_global_config, SnowflakeConfig};
+选项:
+  --tests LIST                  要运行的测试 (默认 concurrency,endurance,memory)
+  --modes LIST                  生成器模式 global,batch,worker (默认 global)
+  --threads LIST                并发测试的线程数 (默认 8,16,32,64,128)
+  --ids-per-thread N            并发测试每线程生成的ID数 (默认 50000)
+  --batch-size N                batch模式每次生成的ID数 (默认 100)
+  --pause-ms N                  两轮并发测试之间的间隔 (默认 300)
+  --endurance-secs N            耐久性测试时长 (默认 30)
+  --endurance-threads N         耐久性测试线程数 (默认 16)
+  --report-interval-secs N      耐久性测试输出速率的间隔 (默认 5)
+  --memory-threads N            吞吐测试线程数 (默认 32)
+  --memory-ids-per-thread N     吞吐测试每线程生成的ID数 (默认 100000)
+  --report FILE                 把结果写入JSON文件
+  --no-verify                   不做唯一性校验 (只测吞吐)
+  -h, --help                    显示帮助
+
+发现重复ID或生成错误时退出码为1，参数错误时为2。";
 
 /// 格式化数字
 fn format_number(n: u64) -> String {
@@ -34,261 +77,942 @@ fn format_number(n: u64) -> String {
     result.chars().rev().collect()
 }
 
+// ============================================================================
+// 命令行参数
+// ============================================================================
+
+/// 测试项目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TestKind {
+    Concurrency,
+    Endurance,
+    Memory,
+}
+
+impl TestKind {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "concurrency" => Ok(TestKind::Concurrency),
+            "endurance" => Ok(TestKind::Endurance),
+            "memory" => Ok(TestKind::Memory),
+            other => Err(format!("未知测试: {}", other)),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            TestKind::Concurrency => "concurrency",
+            TestKind::Endurance => "endurance",
+            TestKind::Memory => "memory",
+        }
+    }
+}
+
+/// 生成器模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// get_next_id() 全局锁
+    Global,
+    /// get_next_ids() 批量生成
+    Batch,
+    /// 共享的独立 Mutex<SnowflakeIdWorker>
+    Worker,
+}
+
+impl Mode {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "global" => Ok(Mode::Global),
+            "batch" => Ok(Mode::Batch),
+            "worker" => Ok(Mode::Worker),
+            other => Err(format!("未知模式: {}", other)),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Mode::Global => "global",
+            Mode::Batch => "batch",
+            Mode::Worker => "worker",
+        }
+    }
+}
+
+/// 测试参数
+#[derive(Debug, Clone)]
+struct Options {
+    tests: Vec<TestKind>,
+    modes: Vec<Mode>,
+    threads: Vec<usize>,
+    ids_per_thread: usize,
+    batch_size: usize,
+    pause_ms: u64,
+    endurance_secs: u64,
+    endurance_threads: usize,
+    report_interval_secs: u64,
+    memory_threads: usize,
+    memory_ids_per_thread: usize,
+    report: Option<String>,
+    verify: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            tests: vec![TestKind::Concurrency, TestKind::Endurance, TestKind::Memory],
+            modes: vec![Mode::Global],
+            threads: vec![8, 16, 32, 64, 128],
+            ids_per_thread: 50_000,
+            batch_size: 100,
+            pause_ms: 300,
+            endurance_secs: 30,
+            endurance_threads: 16,
+            report_interval_secs: 5,
+            memory_threads: 32,
+            memory_ids_per_thread: 100_000,
+            report: None,
+            verify: true,
+        }
+    }
+}
+
+impl Options {
+    /// 解析命令行参数，返回None表示只需显示帮助
+    fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let mut options = Options::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--no-verify" => {
+                    options.verify = false;
+                    continue;
+                }
+                _ => {}
+            }
+            let value = iter.next().ok_or_else(|| format!("{} 缺少参数值", arg))?;
+            match arg.as_str() {
+                "--tests" => options.tests = parse_list(value, TestKind::parse)?,
+                "--modes" => options.modes = parse_list(value, Mode::parse)?,
+                "--threads" => options.threads = parse_list(value, |v| parse_positive(arg, v))?,
+                "--ids-per-thread" => options.ids_per_thread = parse_positive(arg, value)?,
+                "--batch-size" => options.batch_size = parse_positive(arg, value)?,
+                "--pause-ms" => options.pause_ms = parse_number(arg, value)?,
+                "--endurance-secs" => options.endurance_secs = parse_positive(arg, value)?,
+                "--endurance-threads" => options.endurance_threads = parse_positive(arg, value)?,
+                "--report-interval-secs" => options.report_interval_secs = parse_positive(arg, value)?,
+                "--memory-threads" => options.memory_threads = parse_positive(arg, value)?,
+                "--memory-ids-per-thread" => options.memory_ids_per_thread = parse_positive(arg, value)?,
+                "--report" => options.report = Some(value.clone()),
+                other => return Err(format!("未知参数: {}", other)),
+            }
+        }
+        Ok(Some(options))
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "tests": self.tests.iter().map(TestKind::as_str).collect::<Vec<_>>(),
+            "modes": self.modes.iter().map(Mode::as_str).collect::<Vec<_>>(),
+            "threads": self.threads,
+            "ids_per_thread": self.ids_per_thread,
+            "batch_size": self.batch_size,
+            "endurance_secs": self.endurance_secs,
+            "endurance_threads": self.endurance_threads,
+            "memory_threads": self.memory_threads,
+            "memory_ids_per_thread": self.memory_ids_per_thread,
+            "verify": self.verify,
+        })
+    }
+}
+
+/// 解析逗号分隔的列表
+fn parse_list<T>(value: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Vec<T>, String> {
+    let items = value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(parse)
+        .collect::<Result<Vec<T>, String>>()?;
+    if items.is_empty() {
+        return Err(format!("列表不能为空: {:?}", value));
+    }
+    Ok(items)
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{} 必须为非负整数: {}", name, value))
+}
+
+fn parse_positive<T: std::str::FromStr + Default + PartialEq>(name: &str, value: &str) -> Result<T, String> {
+    let n: T = parse_number(name, value)?;
+    if n == T::default() {
+        return Err(format!("{} 必须大于0", name));
+    }
+    Ok(n)
+}
+
+// ============================================================================
+// 延迟统计
+// ============================================================================
+
+/// 每个2的幂区间内的子桶位数 (32个子桶，相对误差约3%)
+const SUB_BUCKET_BITS: u32 = 5;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
+
+/// 延迟直方图 (纳秒，对数分桶)
+/// 每个线程各自记录，结束后合并，记录时不需要同步
+#[derive(Debug, Clone)]
+struct Histogram {
+    counts: Vec<u64>,
+    count: u64,
+    sum_ns: u128,
+    max_ns: u64,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            counts: vec![0; ((64 - SUB_BUCKET_BITS + 1) as u64 * SUB_BUCKETS) as usize],
+            count: 0,
+            sum_ns: 0,
+            max_ns: 0,
+        }
+    }
+
+    fn bucket(ns: u64) -> usize {
+        if ns < SUB_BUCKETS {
+            return ns as usize;
+        }
+        let shift = 63 - ns.leading_zeros() - SUB_BUCKET_BITS;
+        (((shift + 1) as u64 * SUB_BUCKETS) + ((ns >> shift) & (SUB_BUCKETS - 1))) as usize
+    }
+
+    /// 桶内的最大值
+    fn bucket_upper(index: usize) -> u64 {
+        let index = index as u64;
+        if index < SUB_BUCKETS {
+            return index;
+        }
+        let shift = index / SUB_BUCKETS - 1;
+        let sub = index % SUB_BUCKETS;
+        ((SUB_BUCKETS + sub + 1) << shift) - 1
+    }
+
+    fn record(&mut self, duration: Duration) {
+        let ns = duration.as_nanos().min(u64::MAX as u128) as u64;
+        self.counts[Self::bucket(ns)] += 1;
+        self.count += 1;
+        self.sum_ns += ns as u128;
+        self.max_ns = self.max_ns.max(ns);
+    }
+
+    fn merge(&mut self, other: &Histogram) {
+        for (a, b) in self.counts.iter_mut().zip(&other.counts) {
+            *a += b;
+        }
+        self.count += other.count;
+        self.sum_ns += other.sum_ns;
+        self.max_ns = self.max_ns.max(other.max_ns);
+    }
+
+    /// 分位数 (纳秒)，q取0.0-1.0
+    fn percentile(&self, q: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let target = ((q * self.count as f64).ceil() as u64).clamp(1, self.count);
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                return Self::bucket_upper(index).min(self.max_ns);
+            }
+        }
+        self.max_ns
+    }
+
+    fn mean_ns(&self) -> u64 {
+        if self.count == 0 { 0 } else { (self.sum_ns / self.count as u128) as u64 }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "calls": self.count,
+            "mean": self.mean_ns(),
+            "p50": self.percentile(0.50),
+            "p99": self.percentile(0.99),
+            "p999": self.percentile(0.999),
+            "max": self.max_ns,
+        })
+    }
+}
+
+/// 锁等待统计
+#[derive(Debug, Clone, Copy, Default)]
+struct LockWait {
+    acquisitions: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl LockWait {
+    fn record(&mut self, wait: Duration) {
+        self.acquisitions += 1;
+        self.total += wait;
+        self.max = self.max.max(wait);
+    }
+
+    fn merge(&mut self, other: &LockWait) {
+        self.acquisitions += other.acquisitions;
+        self.total += other.total;
+        self.max = self.max.max(other.max);
+    }
+
+    fn mean(&self) -> Duration {
+        if self.acquisitions == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.total.as_nanos() / self.acquisitions as u128) as u64)
+        }
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "acquisitions": self.acquisitions,
+            "total_ns": self.total.as_nanos() as u64,
+            "mean_ns": self.mean().as_nanos() as u64,
+            "max_ns": self.max.as_nanos() as u64,
+        })
+    }
+}
+
+// ============================================================================
+// 生成器
+// ============================================================================
+
+/// 测试使用的生成器
+enum Target {
+    Global,
+    Batch(usize),
+    Worker(Box<Mutex<SnowflakeIdWorker>>),
+}
+
+impl Target {
+    fn new(mode: Mode, options: &Options) -> Result<Self, SnowflakeError> {
+        Ok(match mode {
+            Mode::Global => Target::Global,
+            Mode::Batch => Target::Batch(options.batch_size),
+            Mode::Worker => Target::Worker(Box::new(Mutex::new(SnowflakeIdWorker::new(Some(SnowflakeConfig::default()))?))),
+        })
+    }
+
+    /// 调用一次生成器 (batch模式最多生成max个)，结果记录到stats
+    fn generate(&self, max: usize, stats: &mut ThreadStats) {
+        let start = Instant::now();
+        match self {
+            Target::Global => {
+                let result = get_next_id();
+                stats.latency.record(start.elapsed());
+                stats.add(result.map(|id| vec![id]));
+            }
+            Target::Batch(size) => {
+                let result = get_next_ids((*size).min(max));
+                stats.latency.record(start.elapsed());
+                stats.add(result);
+            }
+            Target::Worker(worker) => {
+                let mut guard = worker.lock().unwrap();
+                let wait = start.elapsed();
+                let result = guard.next_id();
+                drop(guard);
+                stats.latency.record(start.elapsed());
+                stats.lock_wait.record(wait);
+                stats.add(result.map(|id| vec![id]));
+            }
+        }
+    }
+
+    /// global/batch模式的锁等待来自全局生成器的指标，worker模式由测试程序计时
+    fn uses_global_metrics(&self) -> bool {
+        !matches!(self, Target::Worker(_))
+    }
+}
+
+// ============================================================================
+// 唯一性校验
+// ============================================================================
+
+/// 工作线程每次发给校验线程的ID数
+const VERIFY_CHUNK: usize = 8192;
+
+/// 每个工作线程最多积压的块数 (校验线程跟不上时工作线程会等待，避免内存无限增长)
+const VERIFY_BACKLOG_PER_THREAD: usize = 16;
+
+/// 工作线程发给校验线程的消息
+enum VerifyMessage {
+    /// 某个线程按生成顺序的一块ID
+    Chunk(usize, Vec<u64>),
+    /// 某个线程已结束
+    Done(usize),
+}
+
+/// 唯一性校验结果
+#[derive(Debug, Clone, Copy, Default)]
+struct Verification {
+    /// 已归并的ID数
+    checked: u64,
+    /// 重复ID数
+    duplicates: u64,
+    /// 归并结果出现逆序的次数 (某个线程的ID没有严格递增时出现，此时重复可能漏检)
+    out_of_order: u64,
+    /// 校验线程同时缓存的最大ID数
+    max_buffered: usize,
+}
+
+/// 流式k路归并校验
+///
+/// 线程t之后发来的ID都大于它最后发来的ID，因此不大于所有未结束线程最后发来的ID的最小值
+/// (水位) 的ID已经不会再有更小的ID插入，可以按顺序出堆并与前一个ID比较。
+struct StreamVerifier {
+    /// 每个线程尚未归并的ID
+    queues: Vec<VecDeque<u64>>,
+    /// 每个线程最后发来的ID
+    last_received: Vec<Option<u64>>,
+    /// 线程是否还会发来ID
+    open: Vec<bool>,
+    /// 每个非空队列的队首 (ID, 线程)
+    heap: BinaryHeap<Reverse<(u64, usize)>>,
+    buffered: usize,
+    last_merged: Option<u64>,
+    result: Verification,
+}
+
+impl StreamVerifier {
+    fn new(threads: usize) -> Self {
+        Self {
+            queues: vec![VecDeque::new(); threads],
+            last_received: vec![None; threads],
+            open: vec![true; threads],
+            heap: BinaryHeap::with_capacity(threads),
+            buffered: 0,
+            last_merged: None,
+            result: Verification::default(),
+        }
+    }
+
+    fn push(&mut self, thread: usize, chunk: Vec<u64>) {
+        let Some(&last) = chunk.last() else {
+            return;
+        };
+        self.last_received[thread] = Some(last);
+        let queue = &mut self.queues[thread];
+        if queue.is_empty() {
+            self.heap.push(Reverse((chunk[0], thread)));
+        }
+        self.buffered += chunk.len();
+        queue.extend(chunk);
+        self.result.max_buffered = self.result.max_buffered.max(self.buffered);
+        self.drain();
+    }
+
+    fn close(&mut self, thread: usize) {
+        self.open[thread] = false;
+        self.drain();
+    }
+
+    /// 所有未结束线程最后发来的ID的最小值，有线程还没发来任何ID时为None
+    fn watermark(&self) -> Option<u64> {
+        let mut bound = u64::MAX;
+        for (thread, &open) in self.open.iter().enumerate() {
+            if open {
+                bound = bound.min(self.last_received[thread]?);
+            }
+        }
+        Some(bound)
+    }
+
+    fn drain(&mut self) {
+        let Some(bound) = self.watermark() else {
+            return;
+        };
+        while let Some(&Reverse((id, thread))) = self.heap.peek() {
+            if id > bound {
+                break;
+            }
+            self.heap.pop();
+            let queue = &mut self.queues[thread];
+            queue.pop_front();
+            if let Some(&next) = queue.front() {
+                self.heap.push(Reverse((next, thread)));
+            }
+            self.buffered -= 1;
+
+            self.result.checked += 1;
+            match self.last_merged {
+                Some(last) if id == last => self.result.duplicates += 1,
+                Some(last) if id < last => self.result.out_of_order += 1,
+                _ => self.last_merged = Some(id),
+            }
+        }
+    }
+}
+
+/// 单个线程的统计
+struct ThreadStats {
+    thread: usize,
+    /// 校验通道 (--no-verify 时为None)
+    verifier: Option<SyncSender<VerifyMessage>>,
+    /// 尚未发给校验线程的ID
+    chunk: Vec<u64>,
+    last_id: Option<u64>,
+    /// 后一个ID不大于前一个ID的次数
+    regressions: u64,
+    generated: u64,
+    errors: u64,
+    latency: Histogram,
+    lock_wait: LockWait,
+}
+
+impl ThreadStats {
+    fn new(thread: usize, verifier: Option<SyncSender<VerifyMessage>>) -> Self {
+        Self {
+            thread,
+            chunk: Vec::with_capacity(if verifier.is_some() { VERIFY_CHUNK } else { 0 }),
+            verifier,
+            last_id: None,
+            regressions: 0,
+            generated: 0,
+            errors: 0,
+            latency: Histogram::new(),
+            lock_wait: LockWait::default(),
+        }
+    }
+
+    fn add(&mut self, result: Result<Vec<u64>, SnowflakeError>) {
+        match result {
+            Ok(ids) => {
+                self.generated += ids.len() as u64;
+                for id in ids {
+                    if self.last_id.is_some_and(|last| id <= last) {
+                        self.regressions += 1;
+                    }
+                    self.last_id = Some(id);
+                    if self.verifier.is_some() {
+                        self.chunk.push(id);
+                    }
+                }
+                if self.chunk.len() >= VERIFY_CHUNK {
+                    self.flush();
+                }
+            }
+            Err(e) => {
+                if self.errors == 0 {
+                    eprintln!("   ID生成错误: {}", e);
+                }
+                self.errors += 1;
+            }
+        }
+    }
+
+    /// 把缓存的ID发给校验线程
+    fn flush(&mut self) {
+        if let Some(verifier) = &self.verifier {
+            let chunk = std::mem::replace(&mut self.chunk, Vec::with_capacity(VERIFY_CHUNK));
+            // 校验线程只会在所有线程结束后退出，发送不会失败
+            let _ = verifier.send(VerifyMessage::Chunk(self.thread, chunk));
+        }
+    }
+
+    /// 线程结束: 发出剩余的ID
+    fn finish(&mut self) {
+        self.flush();
+        if let Some(verifier) = self.verifier.take() {
+            let _ = verifier.send(VerifyMessage::Done(self.thread));
+        }
+    }
+}
+
 /// 测试结果
 #[derive(Debug)]
 pub struct TestResult {
+    pub test: &'static str,
+    pub mode: &'static str,
     pub total_ids: u64,
-    pub unique_ids: u64,
+    /// 唯一性校验结果 (--no-verify 时为None)
+    verification: Option<Verification>,
+    /// 各线程内ID没有严格递增的次数
+    pub regressions: u64,
+    pub errors: u64,
     pub duration: Duration,
     pub thread_count: usize,
-    pub ids_per_thread: usize,
+    latency: Histogram,
+    lock_wait: LockWait,
 }
 
 impl TestResult {
+    fn duplicates(&self) -> u64 {
+        self.verification.map_or(0, |v| v.duplicates)
+    }
+
+    fn passed(&self) -> bool {
+        self.duplicates() == 0 && self.regressions == 0 && self.errors == 0
+    }
+
+    fn rate(&self) -> f64 {
+        self.total_ids as f64 / self.duration.as_secs_f64()
+    }
+
     fn print_report(&self) {
-        let rate = self.total_ids as f64 / self.duration.as_secs_f64();
-        let duplicate_rate = (self.total_ids - self.unique_ids) as f64 / self.total_ids as f64 * 100.0;
-        
         println!("================================================================================");
-        println!("📊 压力测试结果");
+        println!("📊 {} 测试结果 ({} 模式)", self.test, self.mode);
         println!("================================================================================");
         println!("🔢 ID统计:");
         println!("   总生成数: {}", format_number(self.total_ids));
-        println!("   唯一数量: {}", format_number(self.unique_ids));
-        println!("   重复数量: {}", format_number(self.total_ids - self.unique_ids));
-        println!("   唯一率: {:.8}%", (self.unique_ids as f64 / self.total_ids as f64) * 100.0);
-        
+        if let Some(verification) = self.verification {
+            let unique_ids = verification.checked - verification.duplicates;
+            println!("   唯一数量: {}", format_number(unique_ids));
+            println!("   重复数量: {}", format_number(verification.duplicates));
+            println!("   唯一率: {:.8}%", (unique_ids as f64 / self.total_ids as f64) * 100.0);
+            println!("   校验缓存峰值: {} 个ID", format_number(verification.max_buffered as u64));
+        }
+        if self.regressions > 0 {
+            println!("   线程内ID回退: {}", format_number(self.regressions));
+        }
+        if self.errors > 0 {
+            println!("   生成错误: {}", format_number(self.errors));
+        }
+
         println!("\n⚡ 性能统计:");
         println!("   测试耗时: {:?}", self.duration);
-        println!("   生成速率: {} IDs/秒", format_number(rate as u64));
+        println!("   生成速率: {} IDs/秒", format_number(self.rate() as u64));
         println!("   线程数量: {}", self.thread_count);
-        println!("   每线程数: {}", format_number(self.ids_per_thread as u64));
-        
-        if self.unique_ids == self.total_ids {
-            println!("\n✅ 无重复ID - 唯一性验证通过!");
-        } else {
-            println!("\n⚠️  检测到重复ID!");
-            println!("   重复率: {:.8}%", duplicate_rate);
+        println!(
+            "   调用延迟: p50 {:?}  p99 {:?}  p999 {:?}  max {:?}",
+            Duration::from_nanos(self.latency.percentile(0.50)),
+            Duration::from_nanos(self.latency.percentile(0.99)),
+            Duration::from_nanos(self.latency.percentile(0.999)),
+            Duration::from_nanos(self.latency.max_ns)
+        );
+        println!(
+            "   锁等待:   {} 次，平均 {:?}，最大 {:?}",
+            format_number(self.lock_wait.acquisitions),
+            self.lock_wait.mean(),
+            self.lock_wait.max
+        );
+
+        if self.verification.is_some() {
+            if self.duplicates() == 0 {
+                println!("\n✅ 无重复ID - 唯一性验证通过!");
+            } else {
+                println!("\n⚠️  检测到重复ID!");
+                println!("   重复率: {:.8}%", self.duplicates() as f64 / self.total_ids as f64 * 100.0);
+            }
         }
         println!("================================================================================");
     }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "test": self.test,
+            "mode": self.mode,
+            "threads": self.thread_count,
+            "total_ids": self.total_ids,
+            "unique_ids": self.verification.map(|v| v.checked - v.duplicates),
+            "duplicates": self.duplicates(),
+            "regressions": self.regressions,
+            "errors": self.errors,
+            "verification": self.verification.map(|v| json!({
+                "checked": v.checked,
+                "out_of_order": v.out_of_order,
+                "max_buffered": v.max_buffered,
+            })),
+            "duration_ms": self.duration.as_secs_f64() * 1000.0,
+            "ids_per_sec": self.rate(),
+            "latency_ns": self.latency.to_json(),
+            "lock_wait": self.lock_wait.to_json(),
+        })
+    }
+}
+
+/// 单个线程出错次数达到该值时提前结束 (例如序列号用尽策略为Error时)
+const MAX_ERRORS_PER_THREAD: u64 = 1000;
+
+/// 测试控制: 结束标志和已生成的ID数 (用于输出实时速率)
+#[derive(Debug)]
+struct Control {
+    running: AtomicBool,
+    generated: AtomicU64,
 }
 
-/// 运行并发测试
-fn run_concurrency_test(thread_count: usize, ids_per_thread: usize) -> TestResult {
-    let total_counter = Arc::new(AtomicU64::new(0));
-    let all_ids = Arc::new(Mutex::new(Vec::new()));
-    
+impl Control {
+    fn new() -> Self {
+        Self {
+            running: AtomicBool::new(true),
+            generated: AtomicU64::new(0),
+        }
+    }
+}
+
+/// 在thread_count个线程上运行生成器，直到每个线程生成quota个ID或control.running变为false
+///
+/// 参数:
+/// - verify: 是否在校验线程中检查唯一性
+/// - quota: 每个线程最多生成的ID数 (batch模式据此控制最后一批的大小)
+fn run_threads(
+    test: TestKind,
+    mode: Mode,
+    target: Target,
+    thread_count: usize,
+    verify: bool,
+    quota: usize,
+    control: &Control,
+) -> TestResult {
+    let target = &target;
+    let metrics_before = get_metrics();
     let start_time = Instant::now();
-    
-    let handles: Vec<_> = (0..thread_count)
-        .map(|thread_id| {
-            let total_counter = Arc::clone(&total_counter);
-            let all_ids = Arc::clone(&all_ids);
-            
-            thread::spawn(move || {
-                let mut local_ids = Vec::with_capacity(ids_per_thread);
-                
-                for _ in 0..ids_per_thread {
-                    match get_next_id() {
-                        Ok(id) => {
-                            local_ids.push(id);
-                            total_counter.fetch_add(1, Ordering::Relaxed);
-                        }
-                        Err(e) => {
-                            eprintln!("线程 {} ID生成错误: {}", thread_id, e);
-                        }
+
+    let (stats, duration, verification) = thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel(thread_count * VERIFY_BACKLOG_PER_THREAD);
+        let verifier = verify.then(|| {
+            scope.spawn(move || {
+                let mut verifier = StreamVerifier::new(thread_count);
+                for message in receiver {
+                    match message {
+                        VerifyMessage::Chunk(thread, chunk) => verifier.push(thread, chunk),
+                        VerifyMessage::Done(thread) => verifier.close(thread),
                     }
                 }
-                
-                {
-                    let mut global_ids = all_ids.lock().unwrap();
-                    global_ids.extend(local_ids);
-                }
-                
-                println!("   线程 {} 完成: {} 个ID", thread_id, ids_per_thread);
+                verifier.result
             })
-        })
-        .collect();
-    
-    for handle in handles {
-        handle.join().unwrap();
-    }
-    
-    let duration = start_time.elapsed();
-    let total_ids = total_counter.load(Ordering::Relaxed);
-    
-    println!("   🕐 检查唯一性...");
-    let unique_count = {
-        let ids = all_ids.lock().unwrap();
-        let unique_ids: HashSet<u64> = ids.iter().cloned().collect();
-        unique_ids.len() as u64
-    };
-    
+        });
+
+        let handles: Vec<_> = (0..thread_count)
+            .map(|thread| {
+                let sender = verify.then(|| sender.clone());
+                scope.spawn(move || {
+                    let mut stats = ThreadStats::new(thread, sender);
+                    while control.running.load(Ordering::Relaxed)
+                        && (stats.generated as usize) < quota
+                        && stats.errors < MAX_ERRORS_PER_THREAD
+                    {
+                        let before = stats.generated;
+                        target.generate(quota - stats.generated as usize, &mut stats);
+                        control.generated.fetch_add(stats.generated - before, Ordering::Relaxed);
+                    }
+                    stats.finish();
+                    stats
+                })
+            })
+            .collect();
+        drop(sender);
+
+        let stats: Vec<ThreadStats> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+        let duration = start_time.elapsed();
+        if verify {
+            println!("   🕐 等待唯一性校验完成...");
+        }
+        (stats, duration, verifier.map(|handle| handle.join().unwrap()))
+    });
+
+    let mut latency = Histogram::new();
+    let mut lock_wait = LockWait::default();
+    let mut total_ids = 0;
+    let mut regressions = 0;
+    let mut errors = 0;
+    for thread_stats in &stats {
+        latency.merge(&thread_stats.latency);
+        lock_wait.merge(&thread_stats.lock_wait);
+        total_ids += thread_stats.generated;
+        regressions += thread_stats.regressions;
+        errors += thread_stats.errors;
+    }
+    if target.uses_global_metrics() {
+        // 全局生成器在加锁后记录等待时间; 最大值是进程启动以来的最大值
+        let metrics_after = get_metrics();
+        lock_wait = LockWait {
+            acquisitions: metrics_after.lock_acquisitions - metrics_before.lock_acquisitions,
+            total: metrics_after.lock_wait_total.saturating_sub(metrics_before.lock_wait_total),
+            max: metrics_after.lock_wait_max,
+        };
+    }
+
     TestResult {
+        test: test.as_str(),
+        mode: mode.as_str(),
         total_ids,
-        unique_ids: unique_count,
+        verification,
+        regressions,
+        errors,
         duration,
         thread_count,
-        ids_per_thread,
+        latency,
+        lock_wait,
     }
 }
 
+// ============================================================================
+// 测试项目
+// ============================================================================
+
 /// 极高并发测试
-pub fn extreme_concurrency_test() {
-    println!("🚀 极高并发压力测试");
-    
-    let test_cases = vec![8, 16, 32, 64, 128];
-    let ids_per_thread = 50_000;
-    
-    for &thread_count in &test_cases {
+fn extreme_concurrency_test(options: &Options, mode: Mode) -> Result<Vec<TestResult>, SnowflakeError> {
+    println!("🚀 极高并发压力测试 ({} 模式)", mode.as_str());
+    let control = Control::new();
+
+    let mut results = Vec::new();
+    for &thread_count in &options.threads {
         println!("\n📈 测试 {} 线程并发...", thread_count);
-        let result = run_concurrency_test(thread_count, ids_per_thread);
+        let target = Target::new(mode, options)?;
+        let result = run_threads(
+            TestKind::Concurrency,
+            mode,
+            target,
+            thread_count,
+            options.verify,
+            options.ids_per_thread,
+            &control,
+        );
         result.print_report();
-        
-        thread::sleep(Duration::from_millis(300));
+        results.push(result);
+
+        thread::sleep(Duration::from_millis(options.pause_ms));
     }
+    Ok(results)
 }
 
 /// 长时间耐久性测试
-pub fn endurance_test() {
-    println!("\n🕐 长时间耐久性测试 (30秒)");
-    
-    let thread_count = 16;
-    let batch_size = 1000;
-    let test_duration = Duration::from_secs(30);
-    
-    let total_counter = Arc::new(AtomicU64::new(0));
-    let running = Arc::new(std::sync::atomic::AtomicBool::new(true));
-    
-    let start_time = Instant::now();
-    
-    // 统计线程
-    let stats_counter = Arc::clone(&total_counter);
-    let stats_running = Arc::clone(&running);
-    let stats_handle = thread::spawn(move || {
-        let mut last_count = 0;
-        while stats_running.load(Ordering::Relaxed) {
-            thread::sleep(Duration::from_secs(5));
-            let current_count = stats_counter.load(Ordering::Relaxed);
-            let rate = (current_count - last_count) / 5;
-            println!("   📊 当前速率: {} IDs/秒, 累计: {}", rate, current_count);
-            last_count = current_count;
-        }
-    });
-    
-    // 工作线程
-    let handles: Vec<_> = (0..thread_count)
-        .map(|thread_id| {
-            let total_counter = Arc::clone(&total_counter);
-            let running = Arc::clone(&running);
-            
-            thread::spawn(move || {
-                let mut local_count = 0;
-                
-                while running.load(Ordering::Relaxed) {
-                    for _ in 0..batch_size {
-                        if !running.load(Ordering::Relaxed) {
-                            break;
-                        }
-                        
-                        if get_next_id().is_ok() {
-                            local_count += 1;
-                            total_counter.fetch_add(1, Ordering::Relaxed);
-                        }
-                    }
-                    thread::yield_now();
-                }
-                
-                println!("   线程 {} 完成: {} 个ID", thread_id, local_count);
-            })
-        })
-        .collect();
-    
-    thread::sleep(test_duration);
-    running.store(false, Ordering::Relaxed);
-    
-    for handle in handles {
-        handle.join().unwrap();
-    }
-    
-    stats_handle.join().unwrap();
-    
-    let duration = start_time.elapsed();
-    let total_ids = total_counter.load(Ordering::Relaxed);
-    
-    println!("\n📊 耐久性测试结果:");
-    println!("   测试时间: {:?}", duration);
-    println!("   生成总数: {}", format_number(total_ids));
-    println!("   平均速率: {:.0} IDs/秒", total_ids as f64 / duration.as_secs_f64());
-}
-
-/// 内存使用测试
-pub fn memory_usage_test() {
-    println!("\n💾 内存使用监控测试");
-    
-    let thread_count = 32;
-    let ids_per_thread = 100_000;
-    
-    println!("   配置: {} 线程 × {} IDs = {} 总数", 
-        thread_count, ids_per_thread, thread_count * ids_per_thread);
-    
-    let start_time = Instant::now();
-    
-    let handles: Vec<_> = (0..thread_count)
-        .map(|thread_id| {
-            thread::spawn(move || {
-                let mut count = 0;
-                for _ in 0..ids_per_thread {
-                    if get_next_id().is_ok() {
-                        count += 1;
-                    }
+fn endurance_test(options: &Options, mode: Mode) -> Result<TestResult, SnowflakeError> {
+    println!("\n🕐 长时间耐久性测试 ({}秒, {} 模式)", options.endurance_secs, mode.as_str());
+
+    let target = Target::new(mode, options)?;
+    let control = Control::new();
+    let interval = options.report_interval_secs;
+
+    let result = thread::scope(|scope| {
+        // 统计线程: 定期输出速率，到时间后通知工作线程结束
+        scope.spawn(|| {
+            let deadline = Instant::now() + Duration::from_secs(options.endurance_secs);
+            let mut next_report = Instant::now() + Duration::from_secs(interval);
+            let mut last_count = 0;
+            while Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(50).min(deadline.saturating_duration_since(Instant::now())));
+                if Instant::now() >= next_report {
+                    let current_count = control.generated.load(Ordering::Relaxed);
+                    let rate = (current_count - last_count) / interval;
+                    println!("   📊 当前速率: {} IDs/秒, 累计: {}", format_number(rate), format_number(current_count));
+                    last_count = current_count;
+                    next_report += Duration::from_secs(interval);
                 }
-                (thread_id, count)
-            })
-        })
-        .collect();
-    
-    let mut total_ids = 0;
-    for handle in handles {
-        let (thread_id, count) = handle.join().unwrap();
-        total_ids += count;
-        
-        if thread_id % 8 == 0 {
-            println!("   线程 {} 完成: {} IDs", thread_id, count);
+            }
+            control.running.store(false, Ordering::Relaxed);
+        });
+
+        run_threads(TestKind::Endurance, mode, target, options.endurance_threads, options.verify, usize::MAX, &control)
+    });
+
+    result.print_report();
+    Ok(result)
+}
+
+/// 大量ID的吞吐测试
+fn memory_usage_test(options: &Options, mode: Mode) -> Result<TestResult, SnowflakeError> {
+    println!("\n💾 内存使用监控测试 ({} 模式)", mode.as_str());
+    println!(
+        "   配置: {} 线程 × {} IDs = {} 总数",
+        options.memory_threads,
+        options.memory_ids_per_thread,
+        options.memory_threads * options.memory_ids_per_thread
+    );
+
+    let target = Target::new(mode, options)?;
+    let control = Control::new();
+    let result = run_threads(
+        TestKind::Memory,
+        mode,
+        target,
+        options.memory_threads,
+        options.verify,
+        options.memory_ids_per_thread,
+        &control,
+    );
+    result.print_report();
+    println!("   💡 提示: 唯一性校验只缓存尚未归并的ID，内存占用与ID总数无关");
+    Ok(result)
+}
+
+/// 把所有结果写入JSON报告
+fn write_report(path: &str, options: &Options, results: &[TestResult]) -> Result<(), String> {
+    let report = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "started_at": Local::now().to_rfc3339(),
+        "parallelism": thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        "options": options.to_json(),
+        "passed": results.iter().all(TestResult::passed),
+        "results": results.iter().map(TestResult::to_json).collect::<Vec<_>>(),
+    });
+    let text = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    fs::write(path, text + "\n").map_err(|e| format!("无法写入报告 {}: {}", path, e))
+}
+
+fn run(options: &Options) -> Result<Vec<TestResult>, SnowflakeError> {
+    let mut results = Vec::new();
+    for &mode in &options.modes {
+        for &test in &options.tests {
+            match test {
+                TestKind::Concurrency => results.extend(extreme_concurrency_test(options, mode)?),
+                TestKind::Endurance => results.push(endurance_test(options, mode)?),
+                TestKind::Memory => results.push(memory_usage_test(options, mode)?),
+            }
         }
     }
-    
-    let duration = start_time.elapsed();
-    
-    println!("\n📊 内存测试结果:");
-    println!("   总ID数: {}", format_number(total_ids));
-    println!("   耗时: {:?}", duration);
-    println!("   速率: {:.0} IDs/秒", total_ids as f64 / duration.as_secs_f64());
-    println!("   💡 提示: 此测试不存储ID，内存使用最小");
+    Ok(results)
 }
 
 fn main() {
-    println!("🧪 全局锁雪花算法压力测试 v2.1");
+    init_logging();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match Options::parse(&args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(msg) => {
+            eprintln!("{}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+
+    println!("🧪 全局锁雪花算法压力测试 v{}", env!("CARGO_PKG_VERSION"));
     println!("==================================================");
-    
+
     // 使用默认配置（全局锁模式）
-    let config = SnowflakeConfig {
-        ..Default::default()
-    };
-    set_global_config(config);
-    
+    set_global_config(SnowflakeConfig::default());
     println!("✅ 全局锁模式已启用");
-    
-    // 1. 极高并发测试
-    extreme_concurrency_test();
-    
-    // 2. 耐久性测试
-    endurance_test();
-    
-    // 3. 内存使用测试
-    memory_usage_test();
-    
-    println!("\n🎉 所有压力测试完成!");
-    println!("📝 建议在生产环境定期运行此类测试");
-}
\ No newline at end of file
+
+    let results = match run(&options) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("错误: {}", e);
+            process::exit(1);
+        }
+    };
+
+    if let Some(path) = &options.report {
+        if let Err(msg) = write_report(path, &options, &results) {
+            eprintln!("错误: {}", msg);
+            process::exit(1);
+        }
+        println!("\n📝 报告已写入 {}", path);
+    }
+
+    if results.iter().all(TestResult::passed) {
+        println!("\n🎉 所有压力测试完成!");
+        println!("📝 建议在生产环境定期运行此类测试");
+    } else {
+        println!("\n⚠️  部分测试出现重复ID或生成错误");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,76 @@
+//! 异步生成器接口测试
+
+use snowflake::{AsyncIdGenerator, SequentialIdGenerator};
+
+#[tokio::test]
+async fn sequential_generator_is_async() {
+    let generator = SequentialIdGenerator::new(10);
+    assert_eq!(AsyncIdGenerator::next_id(&generator).await.unwrap(), 10);
+    assert_eq!(AsyncIdGenerator::next_ids(&generator, 3).await.unwrap(), vec![11, 12, 13]);
+}
+
+#[cfg(feature = "tokio")]
+mod blocking {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use snowflake::{AsyncIdGenerator, BlockingIdGenerator, IdGenerator, SequentialIdGenerator, SnowflakeError};
+
+    /// 每次生成都阻塞一段时间的生成器 (模拟网络请求或等待下一毫秒)
+    struct SlowGenerator {
+        delay: Duration,
+        calls: AtomicUsize,
+    }
+
+    impl IdGenerator for SlowGenerator {
+        fn next_id(&self) -> Result<u64, SnowflakeError> {
+            thread::sleep(self.delay);
+            Ok(self.calls.fetch_add(1, Ordering::SeqCst) as u64 + 1)
+        }
+    }
+
+    fn slow(delay: Duration) -> SlowGenerator {
+        SlowGenerator {
+            delay,
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn blocking_generator_does_not_stall_runtime() {
+        let generator = BlockingIdGenerator::new(slow(Duration::from_millis(300)));
+        let started = Instant::now();
+        let pending = tokio::spawn({
+            let generator = generator.clone();
+            async move { generator.next_id().await }
+        });
+
+        // 单线程运行时: 如果生成器在工作线程上阻塞，这个定时器要等到300ms后才会触发
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(started.elapsed() < Duration::from_millis(200), "{:?}", started.elapsed());
+
+        assert_eq!(pending.await.unwrap().unwrap(), 1);
+        assert_eq!(generator.next_ids(2).await.unwrap(), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn shared_generator_serves_sync_and_async_callers() {
+        let shared = Arc::new(SequentialIdGenerator::new(1));
+        let generator = BlockingIdGenerator::from_arc(Arc::clone(&shared));
+
+        assert_eq!(IdGenerator::next_id(shared.as_ref()).unwrap(), 1);
+        assert_eq!(AsyncIdGenerator::next_id(&generator).await.unwrap(), 2);
+        assert_eq!(IdGenerator::next_id(generator.inner().as_ref()).unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn errors_are_passed_through() {
+        let generator = BlockingIdGenerator::new(SequentialIdGenerator::new(u64::MAX));
+        match generator.next_id().await {
+            Err(SnowflakeError::SequenceExhausted(_)) => {}
+            other => panic!("期望序列号用尽, 实际: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! ID审计测试
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use snowflake::audit::{AuditConfig, Auditor, DumpFormat};
+use snowflake::{SnowflakeError, TWEPOCH_MS};
+
+/// 审计时间: 基准时间之后1000秒
+const NOW_MS: u64 = TWEPOCH_MS + 1_000_000;
+
+/// 按默认布局 (8位Worker ID、12位序列号) 拼出ID
+fn id(offset_ms: u64, worker_id: u64, sequence: u64) -> u64 {
+    offset_ms << 20 | worker_id << 12 | sequence
+}
+
+fn text(ids: &[u64]) -> String {
+    ids.iter().map(|id| format!("{}\n", id)).collect()
+}
+
+fn auditor(chunk_size: usize) -> Auditor {
+    Auditor::new(Some(AuditConfig {
+        chunk_size,
+        now_ms: Some(NOW_MS),
+        ..Default::default()
+    }))
+    .unwrap()
+}
+
+/// 每个测试独立的临时目录
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("snowflake-audit-test-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn finds_duplicates_across_spilled_runs() {
+    // 两个节点各10000个ID，其中节点b误用了节点a的Worker ID生成了一段重复的ID
+    let a: Vec<u64> = (0..10_000).map(|i| id(1000 + i / 10, 1, i % 10)).collect();
+    let mut b: Vec<u64> = (0..10_000).map(|i| id(1000 + i / 10, 2, i % 10)).collect();
+    b[5000..5100].copy_from_slice(&a[2000..2100]);
+
+    // 缓冲区很小: 产生几百个有序段，需要多轮合并
+    let mut auditor = auditor(50);
+    auditor.add_reader("a", text(&a).as_bytes(), DumpFormat::Text).unwrap();
+    auditor.add_reader("b", text(&b).as_bytes(), DumpFormat::Text).unwrap();
+    let report = auditor.finish().unwrap();
+
+    assert_eq!(report.total_ids, 20_000);
+    assert_eq!(report.distinct_ids, 19_900);
+    assert_eq!(report.duplicate_ids, 100);
+    assert_eq!(report.duplicate_occurrences, 100);
+    assert_eq!(report.duplicates.len(), 10);
+    assert_eq!(report.duplicates[0].id, a[2000]);
+    assert_eq!(report.duplicates[0].nodes, vec!["a", "b"]);
+    assert!(!report.is_clean());
+
+    // 同样的数据全部在内存中处理，结果相同
+    let mut in_memory = self::auditor(1_000_000);
+    in_memory.add_reader("a", text(&a).as_bytes(), DumpFormat::Text).unwrap();
+    in_memory.add_reader("b", text(&b).as_bytes(), DumpFormat::Text).unwrap();
+    assert_eq!(in_memory.finish().unwrap(), report);
+}
+
+#[test]
+fn reports_workers_regressions_and_future_ids() {
+    let mut node = Vec::new();
+    node.extend((0..100).map(|i| id(2000 + i, 7, 0)));
+    // 时钟回拨50ms后继续生成
+    node.extend((0..100).map(|i| id(2050 + i, 7, 1)));
+    // 同一节点的另一个Worker ID (例如重启后重新分配)
+    node.extend((0..10).map(|i| id(3000 + i, 9, 0)));
+    // 时钟跳到一小时之后
+    node.push(id(NOW_MS - TWEPOCH_MS + 3_600_000, 9, 0));
+
+    let mut auditor = auditor(64);
+    auditor.add_reader("node-1", text(&node).as_bytes(), DumpFormat::Text).unwrap();
+    let report = auditor.finish().unwrap();
+
+    assert_eq!(report.duplicate_ids, 0);
+    let workers: Vec<(u64, u64)> = report.nodes[0].workers.iter().map(|w| (w.worker_id, w.ids)).collect();
+    assert_eq!(workers, vec![(7, 200), (9, 11)]);
+    assert_eq!(report.nodes[0].workers[0].first_ms, TWEPOCH_MS + 2000);
+
+    assert_eq!(report.non_monotonic.len(), 1);
+    let regression = &report.non_monotonic[0];
+    assert_eq!((regression.node.as_str(), regression.worker_id), ("node-1", 7));
+    assert_eq!(regression.regressions, 1);
+    assert_eq!(regression.max_backward_ms, 49);
+    assert_eq!(regression.first, (id(2099, 7, 0), id(2050, 7, 1)));
+
+    assert_eq!(report.future_ids, 1);
+    assert_eq!(report.future_samples[0].node, "node-1");
+    assert_eq!(report.future_samples[0].timestamp_ms, NOW_MS + 3_600_000);
+}
+
+#[test]
+fn detects_sequence_saturation() {
+    // Worker 3在一毫秒内用满4096个序列号，下一毫秒只用了一半
+    let mut ids: Vec<u64> = (0..4096).map(|seq| id(500, 3, seq)).collect();
+    ids.extend((0..2048).map(|seq| id(501, 3, seq)));
+    // 其他Worker ID在同一毫秒的ID不计入Worker 3
+    ids.extend((0..100).map(|seq| id(500, 4, seq)));
+
+    let mut auditor = auditor(1000);
+    auditor.add_reader("node", text(&ids).as_bytes(), DumpFormat::Text).unwrap();
+    let report = auditor.finish().unwrap();
+
+    assert_eq!(report.ids_per_ms_limit, 4096);
+    assert_eq!(report.max_ids_per_ms, 4096);
+    assert_eq!(report.saturated_ticks, 1);
+    assert_eq!(report.saturated_samples[0].worker_id, 3);
+    assert_eq!(report.saturated_samples[0].timestamp_ms, TWEPOCH_MS + 500);
+    assert!(report.is_clean());
+}
+
+#[test]
+fn reads_text_and_binary_dumps() {
+    let dir = temp_dir("formats");
+    let ids: Vec<u64> = (0..1000).map(|i| id(100 + i, 5, 0)).collect();
+
+    let binary: Vec<u8> = ids[..500].iter().flat_map(|id| id.to_be_bytes()).chain([0xff, 0xff]).collect();
+    fs::write(dir.join("node-a.bin"), binary).unwrap();
+    let text = format!("# 导出\n\n{}not-an-id\n{},extra\n", self::text(&ids[500..999]), ids[999]);
+    fs::write(dir.join("node-b.txt"), text).unwrap();
+
+    let mut auditor = Auditor::new(Some(AuditConfig {
+        chunk_size: 100,
+        temp_dir: dir.clone(),
+        now_ms: Some(NOW_MS),
+        ..Default::default()
+    }))
+    .unwrap();
+    for name in ["node-a.bin", "node-b.txt"] {
+        let path = dir.join(name);
+        auditor.add_dump(name, &path, DumpFormat::from_path(&path)).unwrap();
+    }
+    let report = auditor.finish().unwrap();
+
+    assert_eq!(report.total_ids, 1000);
+    assert_eq!(report.distinct_ids, 1000);
+    assert_eq!(report.nodes[0].invalid_records, 1); // 末尾不完整的2字节
+    assert_eq!(report.nodes[1].invalid_records, 1);
+    assert!(report.non_monotonic.is_empty());
+
+    // 临时文件已清理
+    let left: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(left.len(), 2);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unreadable_dumps_are_io_errors() {
+    let dir = temp_dir("io");
+    let mut auditor = auditor(100);
+
+    // 打开失败
+    let missing = dir.join("missing.txt");
+    match auditor.add_dump("a", &missing, DumpFormat::Text) {
+        Err(SnowflakeError::IoError(msg)) => assert!(msg.contains("missing.txt"), "{}", msg),
+        other => panic!("应返回IoError: {:?}", other),
+    }
+
+    // 读取失败 (目录可以打开但不能读取)，错误中带上路径
+    let unreadable = dir.join("unreadable");
+    fs::create_dir(&unreadable).unwrap();
+    match auditor.add_dump("b", &unreadable, DumpFormat::Binary) {
+        Err(SnowflakeError::IoError(msg)) => assert!(msg.contains("unreadable"), "{}", msg),
+        other => panic!("应返回IoError: {:?}", other),
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
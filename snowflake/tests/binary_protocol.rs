@@ -0,0 +1,196 @@
+//! 二进制发号协议端到端测试 (本机回环地址)
+
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use snowflake::proto::{self, BinaryClient};
+use snowflake::SnowflakeError;
+
+const MAX_COUNT: u32 = 1000;
+
+/// 在随机端口启动服务端，返回监听地址
+fn start_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || proto::serve(listener, MAX_COUNT));
+    addr
+}
+
+#[test]
+fn single_and_batch_requests() {
+    let mut client = BinaryClient::connect(start_server()).unwrap();
+
+    let first = client.next_id().unwrap();
+    let batch = client.next_ids(MAX_COUNT).unwrap();
+
+    assert_eq!(batch.len(), MAX_COUNT as usize);
+    assert!(batch.windows(2).all(|w| w[0] < w[1]));
+    assert!(first < batch[0]);
+}
+
+#[test]
+fn pipelined_requests_keep_order() {
+    let mut client = BinaryClient::connect(start_server()).unwrap();
+
+    let counts = [1u32, 7, 300, 2, 50];
+    for &count in &counts {
+        client.send(count).unwrap();
+    }
+    assert_eq!(client.pending(), counts.len());
+
+    let mut all = Vec::new();
+    for &count in &counts {
+        let ids = client.recv().unwrap();
+        assert_eq!(ids.len(), count as usize);
+        all.extend(ids);
+    }
+    assert_eq!(client.pending(), 0);
+    assert!(all.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn invalid_count_is_rejected_and_connection_stays_usable() {
+    let mut client = BinaryClient::connect(start_server()).unwrap();
+
+    for count in [0, MAX_COUNT + 1] {
+        match client.next_ids(count) {
+            Err(SnowflakeError::ProtocolError(_)) => {}
+            other => panic!("期望协议错误, 实际: {:?}", other),
+        }
+    }
+    assert_eq!(client.next_ids(3).unwrap().len(), 3);
+}
+
+#[test]
+fn next_ids_refuses_to_skip_pipelined_responses() {
+    let mut client = BinaryClient::connect(start_server()).unwrap();
+
+    client.send(5).unwrap();
+    match client.next_ids(2) {
+        Err(SnowflakeError::ProtocolError(_)) => {}
+        other => panic!("期望协议错误, 实际: {:?}", other),
+    }
+    // 被拒绝的请求没有发出，流水线中的响应仍然按顺序对应
+    assert_eq!(client.pending(), 1);
+    assert_eq!(client.recv().unwrap().len(), 5);
+    assert_eq!(client.next_ids(2).unwrap().len(), 2);
+}
+
+#[test]
+fn max_count_is_capped_by_response_size() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || proto::serve(listener, u32::MAX));
+    let mut client = BinaryClient::connect(addr).unwrap();
+
+    // 超过响应帧能容纳的数量直接拒绝，不会生成后再发送一个客户端无法接收的响应
+    match client.next_ids(proto::MAX_COUNT + 1) {
+        Err(SnowflakeError::ProtocolError(msg)) => assert!(msg.contains(&proto::MAX_COUNT.to_string()), "{}", msg),
+        other => panic!("期望协议错误, 实际: {:?}", other),
+    }
+    assert_eq!(client.next_ids(3).unwrap().len(), 3);
+}
+
+#[test]
+fn malformed_frame_gets_error_response() {
+    let addr = start_server();
+    let mut stream = TcpStream::connect(addr).unwrap();
+
+    // 长度前缀声明了8字节负载，超过请求的固定长度
+    stream.write_all(&8u32.to_be_bytes()).unwrap();
+    stream.write_all(&[0u8; 8]).unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).unwrap();
+    let len = u32::from_be_bytes(response[..4].try_into().unwrap()) as usize;
+    assert_eq!(response.len(), 4 + len);
+    assert_eq!(response[4], proto::Status::ProtocolError as u8);
+}
+
+/// 启动一个按 `reply` 返回ID数量的假服务端 (参数为请求的数量)，返回监听地址
+fn start_fake_server(reply: fn(u32) -> u32) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 8];
+        while stream.read_exact(&mut request).is_ok() {
+            let count = reply(u32::from_be_bytes(request[4..].try_into().unwrap()));
+            let mut frame = (1 + 8 * count).to_be_bytes().to_vec();
+            frame.push(proto::Status::Ok as u8);
+            for id in 0..count as u64 {
+                frame.extend_from_slice(&(id + 1).to_be_bytes());
+            }
+            stream.write_all(&frame).unwrap();
+        }
+    });
+    addr
+}
+
+#[test]
+fn wrong_number_of_ids_is_a_protocol_error() {
+    for reply in [|count| count - 1, |count| count + 1] {
+        let mut client = BinaryClient::connect(start_fake_server(reply)).unwrap();
+        match client.next_ids(3) {
+            Err(SnowflakeError::ProtocolError(msg)) => assert!(msg.contains('3'), "{}", msg),
+            other => panic!("期望协议错误, 实际: {:?}", other),
+        }
+        // 响应帧本身完整，连接没有失效: 下一个请求照常收到 (同样不符的) 响应
+        assert_eq!(client.pending(), 0);
+        assert!(matches!(client.next_ids(3), Err(SnowflakeError::ProtocolError(_))));
+    }
+}
+
+#[test]
+fn read_timeout_poisons_the_client() {
+    // 接受连接但从不响应
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        thread::sleep(Duration::from_secs(5));
+        drop(stream);
+    });
+
+    let mut client = BinaryClient::connect_timeout(addr, Duration::from_millis(100)).unwrap();
+    assert!(matches!(client.next_ids(1), Err(SnowflakeError::NetworkError(_))));
+
+    // 之后的调用都提示重新连接，而不是报告未读取的流水线响应
+    for _ in 0..2 {
+        match client.next_ids(1) {
+            Err(SnowflakeError::NetworkError(msg)) => assert!(msg.contains("重新连接"), "{}", msg),
+            other => panic!("期望网络错误, 实际: {:?}", other),
+        }
+    }
+    assert!(client.send(1).is_err());
+    assert!(client.recv().is_err());
+}
+
+#[test]
+fn concurrent_clients_get_unique_ids() {
+    let addr = start_server();
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let addr = addr.clone();
+            thread::spawn(move || {
+                let mut client = BinaryClient::connect(addr).unwrap();
+                for _ in 0..20 {
+                    client.send(100).unwrap();
+                }
+                (0..20).flat_map(|_| client.recv().unwrap()).collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    for handle in handles {
+        for id in handle.join().unwrap() {
+            assert!(seen.insert(id), "重复ID: {}", id);
+        }
+    }
+    assert_eq!(seen.len(), 8 * 20 * 100);
+}
@@ -0,0 +1,262 @@
+//! 命令行工具测试
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use serde_json::Value;
+
+/// 运行snowflake命令行工具
+fn run(args: &[&str], stdin: Option<&str>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_snowflake"))
+        .args(args)
+        .env("SNOWFLAKE_WORKER_ID", "42")
+        .env("RUST_LOG", "off")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = child.stdin.take().unwrap();
+    if let Some(text) = stdin {
+        input.write_all(text.as_bytes()).unwrap();
+    }
+    drop(input);
+    child.wait_with_output().unwrap()
+}
+
+/// 在指定目录运行 (读取该目录的snowflake.toml)，不设置SNOWFLAKE_WORKER_ID
+fn run_in(dir: &Path, args: &[&str], envs: &[(&str, &str)]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_snowflake"))
+        .args(args)
+        .current_dir(dir)
+        .env_remove("SNOWFLAKE_WORKER_ID")
+        .env_remove("SNOWFLAKE_WORKER_LEASE_DIR")
+        .envs(envs.iter().copied())
+        .env("RUST_LOG", "off")
+        .output()
+        .unwrap()
+}
+
+/// 临时目录，写入指定内容的snowflake.toml
+fn config_dir(name: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("snowflake-cli-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("snowflake.toml"), content).unwrap();
+    dir
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn gen_outputs_requested_count_and_format() {
+    let output = run(&["gen", "-n", "5"], None);
+    assert!(output.status.success());
+    let ids: Vec<u64> = stdout(&output).lines().map(|l| l.parse().unwrap()).collect();
+    assert_eq!(ids.len(), 5);
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+
+    let output = run(&["gen", "-n", "3", "--format", "base62"], None);
+    assert!(stdout(&output).lines().all(|l| l.len() == 11));
+
+    let output = run(&["gen", "-n", "3", "--format", "json"], None);
+    let json: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(json["count"], 3);
+    // ID输出为十进制字符串，避免JavaScript的Number丢失精度
+    let json_ids: Vec<u64> = json["ids"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|id| id.as_str().unwrap().parse().unwrap())
+        .collect();
+    assert_eq!(json_ids.len(), 3);
+    assert!(json_ids.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn decode_reads_arguments_and_stdin() {
+    let generated = stdout(&run(&["gen", "-n", "3"], None));
+
+    let output = run(&["decode", "--json"], Some(&generated));
+    assert!(output.status.success());
+    let decoded: Vec<Value> = stdout(&output)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(decoded.len(), 3);
+    for (value, id) in decoded.iter().zip(generated.lines()) {
+        assert_eq!(value["id"], id);
+        assert_eq!(value["worker_id"], 42);
+    }
+
+    let output = run(&["decode", "--format", "hex", "0000000000101001"], None);
+    assert_eq!(stdout(&output), "1052673\t2025-03-08T00:00:00.001+08:00\t1\t1\n");
+
+    // 无效输入: 其余ID照常输出，退出码为1
+    let output = run(&["decode", "--json", "123", "abc"], None);
+    assert_eq!(output.status.code(), Some(1));
+    let lines: Vec<Value> = stdout(&output)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines[0]["sequence"], 123);
+    assert_eq!(lines[1]["input"], "abc");
+}
+
+#[test]
+fn config_check_reports_worker_and_layout() {
+    let output = run(&["config", "check", "--json"], None);
+    assert!(output.status.success());
+    let report: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["ok"], true);
+    assert_eq!(report["worker_id"], 42);
+    assert_eq!(report["strategy"], "env");
+    assert_eq!(report["layout"]["timestamp_bits"], 43);
+    assert_eq!(report["layout"]["sequence_bits"], 12);
+}
+
+#[test]
+fn config_check_uses_file_layout_without_taking_a_lease() {
+    // 6位Worker ID放不下映射出的69
+    let dir = config_dir("layout", "datacenter_id = 1\nmachine_id = 5\nworker_id_bits = 6\nsequence_bits = 14\n");
+    let output = run_in(&dir, &["config", "check", "--json"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    let report: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["ok"], false);
+    assert_eq!(report["worker_id"], 69);
+    assert_eq!(report["strategy"], "config");
+    assert_eq!(report["layout"]["worker_id_bits"], 6);
+    assert_eq!(report["layout"]["sequence_bits"], 14);
+    assert!(report["problems"][0].as_str().unwrap().contains("69"));
+
+    // 位布局无效
+    fs::write(dir.join("snowflake.toml"), "worker_id_bits = 8\nsequence_bits = 60\n").unwrap();
+    let output = run_in(&dir, &["config", "check", "--json"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    let report: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["ok"], false);
+
+    // 租约目录: 只报告分配方式，不创建目录也不占用Worker ID
+    fs::write(dir.join("snowflake.toml"), "max_backward_ms = 50\n").unwrap();
+    let lease_dir = dir.join("leases");
+    let lease_env = [("SNOWFLAKE_WORKER_LEASE_DIR", lease_dir.to_str().unwrap())];
+    let output = run_in(&dir, &["config", "check", "--json"], &lease_env);
+    assert!(output.status.success());
+    let report: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["strategy"], "lease");
+    assert_eq!(report["worker_id"], Value::Null);
+    assert_eq!(report["max_backward_ms"], 50);
+    assert!(!lease_dir.exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn decode_and_bounds_use_file_layout() {
+    let dir = config_dir("decode", "worker_id_bits = 4\nsequence_bits = 16\n");
+    // 时间戳1ms，Worker ID 3，序列号5
+    let id = (1u64 << 20) | (3 << 16) | 5;
+    let output = run_in(&dir, &["decode", &id.to_string()], &[]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), format!("{}\t2025-03-08T00:00:00.001+08:00\t3\t5\n", id));
+
+    let output = run_in(
+        &dir,
+        &["bounds", "--from", "2025-03-08T00:00:00+08:00", "--to", "2025-03-08T00:00:00.001+08:00", "--json"],
+        &[],
+    );
+    let bounds: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(bounds["max_id"], ((2u64 << 20) - 1).to_string());
+
+    fs::write(dir.join("snowflake.toml"), "sequence_bits = 20\nworker_id_bits = 8\n").unwrap();
+    let output = run_in(
+        &dir,
+        &["bounds", "--from", "2025-03-08T00:00:00+08:00", "--to", "2025-03-08T00:00:00.001+08:00", "--json"],
+        &[],
+    );
+    let bounds: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(bounds["max_id"], ((2u64 << 28) - 1).to_string());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn bounds_and_usage_errors() {
+    let output = run(
+        &["bounds", "--from", "2025-03-08T00:00:00+08:00", "--to", "2025-03-08T00:00:00.001+08:00", "--json"],
+        None,
+    );
+    assert!(output.status.success());
+    let bounds: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(bounds["min_id"], "0");
+    assert_eq!(bounds["max_id"], ((2u64 << 20) - 1).to_string());
+
+    // 早于基准时间: 执行失败
+    let output = run(&["bounds", "--from", "0", "--to", "now"], None);
+    assert_eq!(output.status.code(), Some(1));
+
+    // 参数错误
+    assert_eq!(run(&[], None).status.code(), Some(2));
+    assert_eq!(run(&["gen", "-n", "0"], None).status.code(), Some(2));
+    assert_eq!(run(&["gen", "--format", "xml"], None).status.code(), Some(2));
+    assert_eq!(run(&["bounds", "--from", "now"], None).status.code(), Some(2));
+}
+
+#[test]
+fn audit_exits_with_failure_on_duplicates() {
+    let dir = std::env::temp_dir().join(format!("snowflake-cli-audit-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let generated = stdout(&run(&["gen", "-n", "100"], None));
+    let a = dir.join("a.txt");
+    let b = dir.join("b.txt");
+    std::fs::write(&a, &generated).unwrap();
+    std::fs::write(&b, generated.lines().next().unwrap()).unwrap();
+
+    let output = run(&["audit", "--json", a.to_str().unwrap()], None);
+    assert!(output.status.success());
+    let report: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["clean"], true);
+    assert_eq!(report["nodes"][0]["name"], "a");
+    assert_eq!(report["nodes"][0]["workers"][0]["worker_id"], 42);
+
+    let node_b = format!("node-b={}", b.display());
+    let output = run(&["audit", "--json", a.to_str().unwrap(), &node_b], None);
+    assert_eq!(output.status.code(), Some(1));
+    let report: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["duplicates"]["ids"], 1);
+    assert_eq!(report["duplicates"]["samples"][0]["nodes"], serde_json::json!(["a", "node-b"]));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn audit_takes_layout_from_file_and_options() {
+    // 4位Worker ID、16位序列号: 同一毫秒内Worker ID 3的两个ID
+    let dir = config_dir("audit", "worker_id_bits = 4\nsequence_bits = 16\n");
+    let ids = [(1u64 << 20) | (3 << 16), (1u64 << 20) | (3 << 16) | 1];
+    let dump = dir.join("node.txt");
+    fs::write(&dump, format!("{}\n{}\n", ids[0], ids[1])).unwrap();
+    let dump = dump.to_str().unwrap();
+
+    let output = run_in(&dir, &["audit", "--json", dump], &[]);
+    assert!(output.status.success());
+    let report: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["nodes"][0]["workers"][0]["worker_id"], 3);
+    assert_eq!(report["saturation"]["ids_per_ms_limit"], 1 << 16);
+
+    // 选项优先于配置文件
+    let output = run_in(&dir, &["audit", "--json", "--worker-id-bits", "8", "--sequence-bits", "12", dump], &[]);
+    let report: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["nodes"][0]["workers"][0]["worker_id"], 48);
+    assert_eq!(report["saturation"]["ids_per_ms_limit"], 1 << 12);
+
+    // 无效布局
+    let output = run_in(&dir, &["audit", "--sequence-bits", "60", dump], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(run_in(&dir, &["audit", "--sequence-bits", "x", dump], &[]).status.code(), Some(2));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,258 @@
+//! 时钟跳变场景: 回拨、跳跃、冻结、闰秒平滑下各恢复策略的表现
+
+use snowflake::id128::{Id128Config, UlidGenerator, UuidV7Generator};
+use snowflake::simulation::{ClockScenario, ScenarioOutcome, SimulatedClock};
+use snowflake::{
+    AtomicIdWorker, MetricsSnapshot, SequenceExhaustedPolicy, SnowflakeConfig, SnowflakeError, SnowflakeIdWorker,
+    TWEPOCH_MS,
+};
+
+const START_MS: u64 = TWEPOCH_MS + 60_000;
+
+/// 在新的模拟时钟上用指定配置的生成器执行场景
+fn run(scenario: &ClockScenario, max_backward_ms: u64, policy: SequenceExhaustedPolicy) -> (ScenarioOutcome, MetricsSnapshot) {
+    let clock = SimulatedClock::new(START_MS);
+    let config = SnowflakeConfig {
+        max_backward_ms,
+        sequence_exhausted_policy: policy,
+        ..Default::default()
+    };
+    let mut worker = SnowflakeIdWorker::with_clock(Some(config), clock.clone()).unwrap();
+    let outcome = scenario.run(&clock, || worker.next_id());
+    (outcome, worker.metrics_snapshot())
+}
+
+fn timestamps(outcome: &ScenarioOutcome) -> Vec<u64> {
+    let config = SnowflakeConfig::default();
+    outcome.ids.iter().map(|&id| config.decode(id).timestamp_ms).collect()
+}
+
+#[test]
+fn every_strategy_stays_unique_and_ordered() {
+    let policies = [
+        SequenceExhaustedPolicy::Spin,
+        SequenceExhaustedPolicy::Sleep,
+        SequenceExhaustedPolicy::Error,
+        SequenceExhaustedPolicy::Borrow(5),
+    ];
+    for scenario in ClockScenario::standard() {
+        for max_backward_ms in [0, 10, 10_000] {
+            for policy in policies {
+                let (outcome, metrics) = run(&scenario, max_backward_ms, policy);
+                let case = format!("{} max_backward_ms={} {:?}", scenario.name, max_backward_ms, policy);
+                assert!(!outcome.gave_up, "{}", case);
+                assert!(outcome.is_strictly_increasing(), "{}", case);
+                // 指标与场景中观察到的结果一致
+                assert_eq!(metrics.ids_generated, outcome.ids.len() as u64, "{}", case);
+                assert_eq!(metrics.clock_backward_errors, outcome.clock_backward_errors() as u64, "{}", case);
+                assert_eq!(metrics.sequence.rejected_count, outcome.sequence_exhausted_errors() as u64, "{}", case);
+            }
+        }
+    }
+}
+
+#[test]
+fn small_step_back_is_waited_out() {
+    let (outcome, metrics) = run(&ClockScenario::ntp_step_back(5), 10, SequenceExhaustedPolicy::Spin);
+    assert!(outcome.errors.is_empty());
+    assert_eq!(metrics.clock_backward_events, 1);
+    assert!((1..=5).contains(&metrics.clock_backward_max_ms), "{}", metrics.clock_backward_max_ms);
+
+    // 不容忍回拨时同样的场景会报错，直到时钟追上
+    let (outcome, metrics) = run(&ClockScenario::ntp_step_back(5), 0, SequenceExhaustedPolicy::Spin);
+    assert!((1..=5).contains(&outcome.clock_backward_errors()), "{}", outcome.clock_backward_errors());
+    assert_eq!(metrics.clock_backward_errors, metrics.clock_backward_events);
+}
+
+#[test]
+fn step_back_beyond_tolerance_errors_until_within_tolerance() {
+    let (outcome, metrics) = run(&ClockScenario::ntp_step_back(50), 10, SequenceExhaustedPolicy::Spin);
+    let errors = outcome.clock_backward_errors() as u64;
+    // 每次重试前进1ms，回拨幅度降到10ms以内后改为等待
+    assert!((35..=50).contains(&errors), "{}", errors);
+    assert_eq!(outcome.errors.iter().map(|e| e.step).max(), Some(3));
+    assert_eq!(metrics.clock_backward_events, errors + 1);
+    assert!(metrics.clock_backward_max_ms >= 45);
+}
+
+#[test]
+fn five_second_step_back() {
+    let (outcome, _) = run(&ClockScenario::ntp_step_back(5_000), 10, SequenceExhaustedPolicy::Spin);
+    assert!(outcome.clock_backward_errors() > 4_900);
+
+    // 容忍度足够大时一次等待5秒，不会报错
+    let (outcome, metrics) = run(&ClockScenario::ntp_step_back(5_000), 10_000, SequenceExhaustedPolicy::Spin);
+    assert!(outcome.errors.is_empty());
+    assert_eq!(metrics.clock_backward_events, 1);
+    let ts = timestamps(&outcome);
+    assert!(ts[1000] >= ts[999]);
+}
+
+#[test]
+fn forward_leap_is_followed_without_errors() {
+    let (outcome, metrics) = run(&ClockScenario::forward_leap(3_600_000), 10, SequenceExhaustedPolicy::Spin);
+    assert!(outcome.errors.is_empty());
+    assert_eq!(metrics.clock_backward_events, 0);
+    let ts = timestamps(&outcome);
+    assert!(ts[1000] - ts[999] >= 3_600_000);
+}
+
+#[test]
+fn frozen_clock_by_exhaustion_policy() {
+    let scenario = ClockScenario::frozen(100);
+
+    // 自旋/休眠: 用尽冻结那一毫秒的序列号后等待时钟恢复
+    for policy in [SequenceExhaustedPolicy::Spin, SequenceExhaustedPolicy::Sleep] {
+        let (outcome, metrics) = run(&scenario, 10, policy);
+        assert!(outcome.errors.is_empty(), "{:?}", policy);
+        assert!(metrics.sequence.exhausted_ticks >= 1, "{:?}", policy);
+        assert!(metrics.sequence.wait_count >= 1, "{:?}", policy);
+    }
+
+    // Error: 冻结期间返回SequenceExhausted
+    let (outcome, metrics) = run(&scenario, 10, SequenceExhaustedPolicy::Error);
+    assert!(outcome.sequence_exhausted_errors() > 50);
+    assert_eq!(outcome.errors.len(), outcome.sequence_exhausted_errors());
+    assert_eq!(metrics.sequence.rejected_count, outcome.sequence_exhausted_errors() as u64);
+
+    // Borrow: 借用未来毫秒，不需要等待
+    let (outcome, metrics) = run(&scenario, 10, SequenceExhaustedPolicy::Borrow(5));
+    assert!(outcome.errors.is_empty());
+    assert!(metrics.sequence.borrowed_ticks >= 1);
+    assert_eq!(metrics.sequence.wait_count, 0);
+    assert!(metrics.sequence.max_borrow_ahead_ms <= 5);
+}
+
+#[test]
+fn step_back_after_saturated_tick_by_exhaustion_policy() {
+    let scenario = ClockScenario::saturated_step_back(50);
+    // 回拨发生在哪一步: Generate(12_000) 之后的 Generate(1000)
+    let after_step = scenario.steps.len() - 1;
+
+    for policy in [
+        SequenceExhaustedPolicy::Spin,
+        SequenceExhaustedPolicy::Sleep,
+        SequenceExhaustedPolicy::Error,
+        SequenceExhaustedPolicy::Borrow(5),
+    ] {
+        // 超出容忍度: 每个策略都报告回拨，直到时钟回到容忍范围内
+        let (outcome, metrics) = run(&scenario, 10, policy);
+        assert!(outcome.is_strictly_increasing(), "{:?}", policy);
+        assert!(metrics.sequence.exhausted_ticks >= 1, "{:?}", policy);
+        let errors = outcome.errors.iter().filter(|e| e.step == after_step).count();
+        assert!(errors > 0 && errors == outcome.clock_backward_errors(), "{:?}: {}", policy, errors);
+        assert!(metrics.clock_backward_max_ms >= 45, "{:?}: {}", policy, metrics.clock_backward_max_ms);
+
+        // 容忍度足够大: 等待时钟追上，不报错
+        let (outcome, metrics) = run(&scenario, 10_000, policy);
+        assert_eq!(outcome.clock_backward_errors(), 0, "{:?}", policy);
+        assert_eq!(metrics.clock_backward_events, 1, "{:?}", policy);
+        assert!(outcome.is_strictly_increasing(), "{:?}", policy);
+    }
+
+    // 自旋/休眠: 回拨前已经等过冻结的时钟
+    for policy in [SequenceExhaustedPolicy::Spin, SequenceExhaustedPolicy::Sleep] {
+        let (outcome, metrics) = run(&scenario, 10, policy);
+        assert_eq!(outcome.sequence_exhausted_errors(), 0, "{:?}", policy);
+        assert!(metrics.sequence.wait_count >= 1, "{:?}", policy);
+        assert_eq!(metrics.sequence.borrowed_ticks, 0, "{:?}", policy);
+    }
+
+    // Error: 冻结期间返回SequenceExhausted，回拨后返回ClockBackward
+    let (outcome, metrics) = run(&scenario, 10, SequenceExhaustedPolicy::Error);
+    assert!(outcome.sequence_exhausted_errors() > 0);
+    assert!(outcome.errors[..outcome.sequence_exhausted_errors()].iter().all(|e| e.step < after_step));
+    assert_eq!(metrics.sequence.rejected_count, outcome.sequence_exhausted_errors() as u64);
+    assert_eq!(metrics.sequence.wait_count, 0);
+
+    // Borrow: 回拨时正借用未来毫秒 (12000个ID跨3个毫秒，借用上限内不等待)，
+    // 回拨超过借用上限后退出借用并按回拨处理
+    let (outcome, metrics) = run(&scenario, 10, SequenceExhaustedPolicy::Borrow(5));
+    assert_eq!(outcome.sequence_exhausted_errors(), 0);
+    assert!(metrics.sequence.borrowed_ticks >= 2, "{}", metrics.sequence.borrowed_ticks);
+    assert!(metrics.sequence.max_borrow_ahead_ms >= 2, "{}", metrics.sequence.max_borrow_ahead_ms);
+    assert_eq!(metrics.sequence.wait_count, 0);
+    // 回拨后的ID不会落回借用过的毫秒之前
+    let ts = timestamps(&outcome);
+    assert!(ts[13_000] >= ts[12_999]);
+}
+
+#[test]
+fn leap_smear_never_goes_backward() {
+    let scenario = ClockScenario::leap_smear(100, 2_000);
+    let clock = SimulatedClock::new(START_MS);
+    let mut worker = SnowflakeIdWorker::with_clock(None, clock.clone()).unwrap();
+    let outcome = scenario.run(&clock, || worker.next_id());
+
+    assert!(outcome.errors.is_empty());
+    assert!(outcome.is_strictly_increasing());
+    assert_eq!(worker.metrics_snapshot().clock_backward_events, 0);
+    // 平滑结束后报告时间比真实时间慢100ms
+    assert_eq!(clock.real_ms() - clock.reported_ms(), 100);
+}
+
+#[test]
+fn atomic_worker_carries_forward_within_tolerance() {
+    let run_atomic = |scenario: &ClockScenario| {
+        let clock = SimulatedClock::new(START_MS);
+        let worker = AtomicIdWorker::with_clock(None, 1, clock.clone()).unwrap();
+        scenario.run(&clock, || worker.next_id())
+    };
+
+    for scenario in ClockScenario::standard() {
+        let outcome = run_atomic(&scenario);
+        assert!(!outcome.gave_up, "{}", scenario.name);
+        assert!(outcome.is_strictly_increasing(), "{}", scenario.name);
+        assert_eq!(outcome.sequence_exhausted_errors(), 0, "{}", scenario.name);
+    }
+
+    // 容忍范围内的回拨: 在上次的基础上继续递增，不报错也不等待
+    assert!(run_atomic(&ClockScenario::ntp_step_back(5)).errors.is_empty());
+    assert!(run_atomic(&ClockScenario::ntp_step_back(50)).clock_backward_errors() > 0);
+}
+
+#[test]
+fn atomic_worker_leads_by_max_backward_ms_regardless_of_policy() {
+    // 每毫秒16个序列号，时钟冻结时只能靠进位借用
+    let config = SnowflakeConfig {
+        sequence_bits: 4,
+        max_backward_ms: 10,
+        sequence_exhausted_policy: SequenceExhaustedPolicy::Borrow(2),
+        ..Default::default()
+    };
+    let clock = SimulatedClock::new(START_MS);
+    let worker = AtomicIdWorker::with_clock(Some(config), 1, clock.clone()).unwrap();
+    clock.freeze(std::time::Duration::from_secs(1));
+    let tick = clock.reported_ms();
+
+    // Borrow(2)不生效: 当前毫秒加上领先的10个毫秒，共176个ID不需要等待
+    let ids: Vec<u64> = (0..176).map(|_| worker.next_id().unwrap()).collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(worker.decode(ids[175]).timestamp_ms, tick + 10);
+    assert_eq!(clock.reported_ms(), tick);
+}
+
+#[test]
+fn atomic_worker_rejects_invalid_layouts() {
+    for (worker_id_bits, sequence_bits) in [(8, 0), (0, 12), (9, 12), (200, 100)] {
+        let config = SnowflakeConfig { worker_id_bits, sequence_bits, ..Default::default() };
+        let result = AtomicIdWorker::new(Some(config), 0);
+        assert!(matches!(result, Err(SnowflakeError::ConfigError(_))), "{} {}", worker_id_bits, sequence_bits);
+    }
+}
+
+#[test]
+fn uuid_v7_and_ulid_share_backward_handling() {
+    for scenario in ClockScenario::standard() {
+        let clock = SimulatedClock::new(START_MS);
+        let generator = UuidV7Generator::with_clock(None, clock.clone());
+        let outcome = scenario.run(&clock, || generator.next_uuid());
+        assert!(outcome.is_strictly_increasing(), "{}", scenario.name);
+
+        let clock = SimulatedClock::new(START_MS);
+        let generator = UlidGenerator::with_clock(Some(Id128Config { max_backward_ms: 10_000 }), clock.clone());
+        let outcome = scenario.run(&clock, || generator.next_ulid());
+        assert!(outcome.is_strictly_increasing(), "{}", scenario.name);
+        assert!(outcome.errors.is_empty(), "{}", scenario.name);
+    }
+}
@@ -0,0 +1,63 @@
+//! 监听配置文件变化 (reload feature)
+#![cfg(feature = "reload")]
+
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use snowflake::reload::ConfigWatcher;
+use snowflake::SnowflakeIdWorker;
+
+/// 等待条件成立，最多5秒
+fn wait_for(mut condition: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+#[test]
+fn reloads_when_file_changes() {
+    env::remove_var("SNOWFLAKE_WORKER_ID");
+    env::remove_var("SNOWFLAKE_WORKER_LEASE_DIR");
+    let dir = env::temp_dir().join(format!("snowflake-watch-test-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("snowflake.toml");
+    fs::write(&path, "datacenter_id = 0\nmachine_id = 1\nmax_backward_ms = 20\n").unwrap();
+
+    let worker = Arc::new(Mutex::new(SnowflakeIdWorker::new(None).unwrap()));
+    // 启动时立即加载一次
+    let watcher = ConfigWatcher::start(&path, worker.clone()).unwrap();
+    assert_eq!(worker.lock().unwrap().get_worker_id(), 1);
+    assert_eq!(worker.lock().unwrap().config().max_backward_ms, 20);
+
+    // 修改文件: 安全的修改和Worker ID切换生效
+    fs::write(&path, "datacenter_id = 0\nmachine_id = 2\nmax_backward_ms = 99\n").unwrap();
+    assert!(wait_for(|| {
+        let worker = worker.lock().unwrap();
+        worker.get_worker_id() == 2 && worker.config().max_backward_ms == 99
+    }));
+
+    // 内容有误时保持原配置; 位布局的修改被拒绝
+    fs::write(&path, "max_backward_ms = abc\n").unwrap();
+    thread::sleep(Duration::from_millis(200));
+    fs::write(&path, "sequence_bits = 16\nmax_backward_ms = 7\n").unwrap();
+    assert!(wait_for(|| worker.lock().unwrap().config().max_backward_ms == 7));
+    assert_eq!(worker.lock().unwrap().config().sequence_bits, 12);
+    assert_eq!(worker.lock().unwrap().get_worker_id(), 2);
+
+    // 停止监听后不再重新加载
+    drop(watcher);
+    fs::write(&path, "max_backward_ms = 1\n").unwrap();
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(worker.lock().unwrap().config().max_backward_ms, 7);
+    fs::remove_dir_all(&dir).unwrap();
+}
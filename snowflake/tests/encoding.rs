@@ -0,0 +1,187 @@
+//! ID字符串编码测试
+
+use snowflake::encoding::{
+    decode_base32, decode_base32_check, decode_base62, decode_hex, encode_base32, encode_base32_check,
+    encode_base62, encode_hex, DecodeError, Encoding,
+};
+use snowflake::get_next_id;
+
+const ENCODINGS: [Encoding; 4] = [
+    Encoding::Base62,
+    Encoding::Base32,
+    Encoding::Hex,
+    Encoding::Base32Check,
+];
+
+/// Crockford base32字母表
+const BASE32_ALPHABET: &str = "0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// 确定性的伪随机数 (xorshift64)，覆盖整个u64范围
+fn sample_values(count: usize) -> Vec<u64> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut values = vec![0, 1, 61, 62, u32::MAX as u64, u64::MAX - 1, u64::MAX];
+    for _ in 0..count {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        values.push(state);
+    }
+    values
+}
+
+#[test]
+fn round_trips_exactly() {
+    let mut values = sample_values(10_000);
+    values.extend((0..100).map(|_| get_next_id().unwrap()));
+
+    for encoding in ENCODINGS {
+        for &id in &values {
+            let encoded = encoding.encode(id);
+            assert_eq!(encoded.len(), encoding.width(), "{:?} {}", encoding, id);
+            assert_eq!(encoding.decode(&encoded), Ok(id), "{:?} {}", encoding, encoded);
+        }
+    }
+}
+
+#[test]
+fn lexicographic_order_matches_numeric_order() {
+    let mut values = sample_values(2_000);
+    values.sort_unstable();
+
+    for encoding in ENCODINGS {
+        let encoded: Vec<String> = values.iter().map(|&id| encoding.encode(id)).collect();
+        let mut sorted = encoded.clone();
+        sorted.sort();
+        assert_eq!(encoded, sorted, "{:?}", encoding);
+    }
+}
+
+#[test]
+fn known_values() {
+    assert_eq!(encode_base62(0), "00000000000");
+    assert_eq!(encode_base62(61), "0000000000z");
+    assert_eq!(encode_base62(u64::MAX), "LygHa16AHYF");
+    assert_eq!(encode_base32(0), "0000000000000");
+    assert_eq!(encode_base32(u64::MAX), "FZZZZZZZZZZZZ");
+    assert_eq!(encode_hex(0xdead_beef), "00000000deadbeef");
+    assert_eq!(encode_hex(u64::MAX), "ffffffffffffffff");
+}
+
+#[test]
+fn base32_and_hex_are_case_insensitive() {
+    let id = 0x0123_4567_89ab_cdef;
+    assert_eq!(decode_base32(&encode_base32(id).to_lowercase()), Ok(id));
+    assert_eq!(decode_hex(&encode_hex(id).to_uppercase()), Ok(id));
+
+    // Crockford规范: I/L 读作1，O 读作0
+    assert_eq!(decode_base32("000000000000I"), Ok(1));
+    assert_eq!(decode_base32("000000000000l"), Ok(1));
+    assert_eq!(decode_base32("O000000000001"), Ok(1));
+}
+
+#[test]
+fn rejects_invalid_input() {
+    assert_eq!(
+        decode_base62("abc"),
+        Err(DecodeError::InvalidLength { expected: 11, actual: 3 })
+    );
+    assert_eq!(
+        decode_hex(""),
+        Err(DecodeError::InvalidLength { expected: 16, actual: 0 })
+    );
+    assert_eq!(
+        decode_base62("0000000000-"),
+        Err(DecodeError::InvalidChar { ch: '-', position: 10 })
+    );
+    assert_eq!(
+        decode_base32("000000000000U"),
+        Err(DecodeError::InvalidChar { ch: 'U', position: 12 })
+    );
+    assert_eq!(
+        decode_hex("000000000000000g"),
+        Err(DecodeError::InvalidChar { ch: 'g', position: 15 })
+    );
+    // 非ASCII字符按字符计数
+    assert_eq!(
+        decode_hex("00000000000000中0"),
+        Err(DecodeError::InvalidChar { ch: '中', position: 14 })
+    );
+
+    // 定长编码能表示的范围超过u64
+    assert_eq!(decode_base62("LygHa16AHYG"), Err(DecodeError::Overflow));
+    assert_eq!(decode_base62("zzzzzzzzzzz"), Err(DecodeError::Overflow));
+    assert_eq!(decode_base32("G000000000000"), Err(DecodeError::Overflow));
+}
+
+#[test]
+fn check_digit_detects_single_substitutions() {
+    for id in sample_values(200) {
+        let encoded = encode_base32_check(id);
+        assert_eq!(decode_base32_check(&encoded), Ok(id));
+        assert_eq!(decode_base32_check(&encoded.to_lowercase()), Ok(id));
+
+        for position in 0..encoded.len() {
+            for replacement in BASE32_ALPHABET.chars() {
+                let mut typo: Vec<char> = encoded.chars().collect();
+                if typo[position] == replacement {
+                    continue;
+                }
+                typo[position] = replacement;
+                let typo: String = typo.into_iter().collect();
+                assert!(
+                    matches!(decode_base32_check(&typo), Err(DecodeError::CheckDigitMismatch { .. })),
+                    "{} -> {}",
+                    encoded,
+                    typo
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn check_digit_detects_adjacent_transpositions() {
+    let mut total = 0;
+    let mut detected = 0;
+    for id in sample_values(2_000) {
+        let encoded: Vec<char> = encode_base32_check(id).chars().collect();
+        for position in 0..encoded.len() - 1 {
+            if encoded[position] == encoded[position + 1] {
+                continue;
+            }
+            let mut swapped = encoded.clone();
+            swapped.swap(position, position + 1);
+            let swapped: String = swapped.into_iter().collect();
+            total += 1;
+            if decode_base32_check(&swapped).is_err() {
+                detected += 1;
+            }
+        }
+    }
+    // Luhn mod N只漏掉一对特定数值 (0和31) 的调换
+    assert!(detected * 100 >= total * 99, "{}/{}", detected, total);
+}
+
+#[test]
+fn check_digit_errors_are_specific() {
+    let encoded = encode_base32_check(123_456_789);
+    assert_eq!(encoded.len(), 14);
+
+    let mut typo = encoded.clone();
+    typo.replace_range(5..6, if &encoded[5..6] == "1" { "2" } else { "1" });
+    match decode_base32_check(&typo) {
+        Err(DecodeError::CheckDigitMismatch { actual, .. }) => {
+            assert_eq!(Some(actual), encoded.chars().last());
+        }
+        other => panic!("期望校验位错误: {:?}", other),
+    }
+
+    assert_eq!(
+        decode_base32_check(&encoded[..13]),
+        Err(DecodeError::InvalidLength { expected: 14, actual: 13 })
+    );
+    assert_eq!(
+        decode_base32_check("0000000000000U"),
+        Err(DecodeError::InvalidChar { ch: 'U', position: 13 })
+    );
+}
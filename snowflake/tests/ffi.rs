@@ -0,0 +1,108 @@
+//! C接口: 头文件与ffi.rs一致，C程序能链接动态库并正确调用
+
+#![cfg(unix)]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn manifest_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// 用cbindgen根据ffi.rs生成头文件内容
+fn generate_header() -> String {
+    let config = cbindgen::Config::from_file(manifest_dir().join("cbindgen.toml")).unwrap();
+    let bindings = cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(manifest_dir().join("ffi.rs"))
+        .generate()
+        .expect("无法生成头文件");
+    let mut header = Vec::new();
+    bindings.write(&mut header);
+    String::from_utf8(header).unwrap()
+}
+
+#[test]
+fn header_is_up_to_date() {
+    let path = manifest_dir().join("include/snowflake.h");
+    let generated = generate_header();
+    if env::var_os("SNOWFLAKE_UPDATE_HEADER").is_some() {
+        fs::write(&path, &generated).unwrap();
+        return;
+    }
+    let committed = fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "include/snowflake.h 与 ffi.rs 不一致，运行 SNOWFLAKE_UPDATE_HEADER=1 cargo test --test ffi 重新生成"
+    );
+}
+
+/// 构建动态库，返回其所在目录 (与测试程序使用同一个target目录和profile)
+fn build_cdylib() -> PathBuf {
+    // target/<profile>/deps/ffi-xxxx
+    let profile_dir = env::current_exe().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    let target_dir = profile_dir.parent().unwrap();
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo
+        .args(["build", "--lib", "--manifest-path"])
+        .arg(manifest_dir().join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(target_dir);
+    if profile_dir.file_name().unwrap() == "release" {
+        cargo.arg("--release");
+    }
+    let output = cargo.output().expect("无法运行cargo");
+    assert!(output.status.success(), "构建动态库失败:\n{}", String::from_utf8_lossy(&output.stderr));
+    profile_dir
+}
+
+#[test]
+fn c_program_links_and_runs() {
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    // 找不到编译器时失败而不是跳过，否则C接口没有被测试也会显示通过
+    if let Err(e) = Command::new(&cc).arg("--version").output() {
+        panic!("无法运行C编译器 {} ({})，请安装cc或用环境变量CC指定编译器", cc, e);
+    }
+
+    let lib_dir = build_cdylib();
+    let work_dir = env::temp_dir().join(format!("snowflake-ffi-{}", std::process::id()));
+    fs::create_dir_all(&work_dir).unwrap();
+    let program = work_dir.join("ffi_test");
+
+    let output = Command::new(&cc)
+        .arg(manifest_dir().join("tests/ffi/test.c"))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir().join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lsnowflake")
+        .arg("-lpthread")
+        .arg("-o")
+        .arg(&program)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "编译C测试程序失败:\n{}", String::from_utf8_lossy(&output.stderr));
+
+    // 在临时目录中运行，避免读取仓库中的snowflake.toml
+    let output = Command::new(&program)
+        .current_dir(&work_dir)
+        .env("SNOWFLAKE_WORKER_ID", "42")
+        .env_remove("SNOWFLAKE_WORKER_LEASE_DIR")
+        .output()
+        .unwrap();
+    let _ = fs::remove_dir_all(&work_dir);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "C测试程序失败:\n{}\n{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("all checks passed"), "{}", stdout);
+}
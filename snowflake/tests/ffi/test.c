@@ -0,0 +1,163 @@
+/*
+ * C接口测试程序 (由 tests/ffi.rs 编译运行)
+ *
+ * 需要设置环境变量 SNOWFLAKE_WORKER_ID=42。
+ * 全部检查通过时输出 "all checks passed" 并返回0，否则输出失败的检查并返回1。
+ */
+
+#define _POSIX_C_SOURCE 200809L
+
+#include <pthread.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <time.h>
+
+#include "snowflake.h"
+
+#define WORKER_ID 42
+#define THREADS 4
+#define IDS_PER_THREAD 20000
+
+static int failures = 0;
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            printf("FAILED %s:%d: %s\n", __FILE__, __LINE__, #cond);       \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+static uint64_t now_ms(void) {
+    struct timespec ts;
+    clock_gettime(CLOCK_REALTIME, &ts);
+    return (uint64_t)ts.tv_sec * 1000 + (uint64_t)ts.tv_nsec / 1000000;
+}
+
+static void check_invalid_arguments(void) {
+    char message[256];
+    SnowflakeInitConfig config;
+
+    CHECK(snowflake_next_id(NULL) == SNOWFLAKE_STATUS_INVALID_ARGUMENT);
+    CHECK(snowflake_last_error(message, sizeof message) > 0);
+    CHECK(strstr(message, "out") != NULL);
+    CHECK(snowflake_decode(1, NULL) == SNOWFLAKE_STATUS_INVALID_ARGUMENT);
+    CHECK(snowflake_next_ids(NULL, 0) == SNOWFLAKE_STATUS_OK);
+
+    /* 无效配置不会创建全局Worker，之后仍可以正常初始化 */
+    CHECK(snowflake_config_default(&config) == SNOWFLAKE_STATUS_OK);
+    config.sequence_bits = 60;
+    CHECK(snowflake_init(&config) == SNOWFLAKE_STATUS_CONFIG_ERROR);
+    CHECK(snowflake_config_default(&config) == SNOWFLAKE_STATUS_OK);
+    config.exhausted_policy = 99;
+    CHECK(snowflake_init(&config) == SNOWFLAKE_STATUS_INVALID_ARGUMENT);
+
+    /* 截断: 返回完整长度，缓冲区以'\0'结尾 */
+    size_t length = snowflake_last_error(NULL, 0);
+    CHECK(length > 4);
+    CHECK(snowflake_last_error(message, 4) == length);
+    CHECK(strlen(message) == 3);
+}
+
+static void check_init(void) {
+    SnowflakeInitConfig config;
+    CHECK(snowflake_config_default(&config) == SNOWFLAKE_STATUS_OK);
+    CHECK(config.worker_id_bits == 8);
+    CHECK(config.sequence_bits == 12);
+    CHECK(config.exhausted_policy == SNOWFLAKE_POLICY_SPIN);
+    CHECK(config.sequence_start == SNOWFLAKE_SEQUENCE_START_ZERO);
+
+    config.exhausted_policy = SNOWFLAKE_POLICY_BORROW;
+    config.borrow_ahead_ms = 5;
+    CHECK(snowflake_init(&config) == SNOWFLAKE_STATUS_OK);
+    CHECK(snowflake_init(NULL) == SNOWFLAKE_STATUS_ALREADY_INITIALIZED);
+
+    uint8_t worker_id = 0;
+    CHECK(snowflake_worker_id(&worker_id) == SNOWFLAKE_STATUS_OK);
+    CHECK(worker_id == WORKER_ID);
+}
+
+static void check_sequential(void) {
+    uint64_t previous = 0;
+    for (int i = 0; i < 10000; i++) {
+        uint64_t id = 0;
+        CHECK(snowflake_next_id(&id) == SNOWFLAKE_STATUS_OK);
+        CHECK(id > previous);
+        previous = id;
+    }
+
+    uint64_t batch[256];
+    CHECK(snowflake_next_ids(batch, 256) == SNOWFLAKE_STATUS_OK);
+    CHECK(batch[0] > previous);
+    for (int i = 1; i < 256; i++) {
+        CHECK(batch[i] > batch[i - 1]);
+    }
+}
+
+static void check_decode(void) {
+    uint64_t before = now_ms();
+    uint64_t id = 0;
+    CHECK(snowflake_next_id(&id) == SNOWFLAKE_STATUS_OK);
+
+    SnowflakeDecodedId decoded;
+    CHECK(snowflake_decode(id, &decoded) == SNOWFLAKE_STATUS_OK);
+    CHECK(decoded.id == id);
+    CHECK(decoded.worker_id == WORKER_ID);
+    CHECK(decoded.sequence < 4096);
+    /* Borrow策略最多领先5ms */
+    CHECK(decoded.timestamp_ms + 1 >= before);
+    CHECK(decoded.timestamp_ms <= now_ms() + 5);
+    CHECK(id == (((decoded.timestamp_ms - 1741363200000ULL) << 20) | (decoded.worker_id << 12) | decoded.sequence));
+}
+
+static void *generate(void *arg) {
+    uint64_t *ids = arg;
+    for (int i = 0; i < IDS_PER_THREAD; i++) {
+        if (snowflake_next_id(&ids[i]) != SNOWFLAKE_STATUS_OK) {
+            ids[i] = 0;
+        }
+    }
+    return NULL;
+}
+
+static int compare_ids(const void *a, const void *b) {
+    uint64_t x = *(const uint64_t *)a, y = *(const uint64_t *)b;
+    return (x > y) - (x < y);
+}
+
+static void check_threads(void) {
+    size_t total = (size_t)THREADS * IDS_PER_THREAD;
+    uint64_t *ids = calloc(total, sizeof *ids);
+    pthread_t threads[THREADS];
+    for (int t = 0; t < THREADS; t++) {
+        CHECK(pthread_create(&threads[t], NULL, generate, ids + (size_t)t * IDS_PER_THREAD) == 0);
+    }
+    for (int t = 0; t < THREADS; t++) {
+        pthread_join(threads[t], NULL);
+    }
+
+    qsort(ids, total, sizeof *ids, compare_ids);
+    CHECK(ids[0] != 0);
+    size_t duplicates = 0;
+    for (size_t i = 1; i < total; i++) {
+        duplicates += ids[i] == ids[i - 1];
+    }
+    CHECK(duplicates == 0);
+    free(ids);
+}
+
+int main(void) {
+    check_invalid_arguments();
+    check_init();
+    check_sequential();
+    check_decode();
+    check_threads();
+
+    if (failures != 0) {
+        printf("%d checks failed\n", failures);
+        return 1;
+    }
+    printf("all checks passed\n");
+    return 0;
+}
@@ -0,0 +1,113 @@
+//! HTTP接口 (server feature): 直接调用Router，不监听端口
+#![cfg(feature = "server")]
+
+use std::env;
+use std::fs;
+use std::sync::Once;
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tower::ServiceExt;
+
+/// 每毫秒只有16个序列号，用尽时报错 (由临时目录中的snowflake.toml设置)
+const CONFIG: &str = "sequence_bits = 4\nsequence_exhausted_policy = \"error\"\n";
+const MAX_COUNT: usize = 100;
+
+/// 会用尽当前毫秒序列号的测试与其他测试互斥
+static EXHAUST: Mutex<()> = Mutex::const_new(());
+
+/// 在临时目录中运行，全局Worker按其中的snowflake.toml创建
+fn setup() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let dir = env::temp_dir().join(format!("snowflake-http-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("snowflake.toml"), CONFIG).unwrap();
+        env::set_current_dir(&dir).unwrap();
+        env::set_var("SNOWFLAKE_WORKER_ID", "42");
+        env::remove_var("SNOWFLAKE_WORKER_LEASE_DIR");
+        assert!(snowflake::init_global_worker(Default::default()).unwrap());
+    });
+}
+
+async fn get(uri: &str) -> (StatusCode, Value) {
+    setup();
+    let response = snowflake::http::app(MAX_COUNT)
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn id_and_decode_use_global_worker() {
+    let _guard = EXHAUST.lock().await;
+    let (status, body) = get("/id").await;
+    assert_eq!(status, StatusCode::OK);
+    let id = body["id"].as_u64().unwrap();
+
+    let (status, decoded) = get(&format!("/decode/{}", id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(decoded["worker_id"], 42);
+    // 按配置文件中的4位序列号解析
+    assert_eq!(decoded["sequence"].as_u64().unwrap(), id & 0xF);
+
+    let (status, body) = get("/decode/abc").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("abc"));
+}
+
+#[tokio::test]
+async fn ids_enforces_count_limits() {
+    let _guard = EXHAUST.lock().await;
+    let (status, body) = get("/ids?count=10").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["count"], 10);
+    let ids: Vec<u64> = body["ids"].as_array().unwrap().iter().map(|v| v.as_u64().unwrap()).collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+
+    let (_, body) = get("/ids").await;
+    assert_eq!(body["count"], 1);
+
+    for uri in ["/ids?count=0", "/ids?count=101"] {
+        let (status, body) = get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert_eq!(body["error"], "count必须在1到100之间");
+    }
+    let (status, _) = get("/ids?count=abc").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn generation_errors_map_to_503() {
+    let _guard = EXHAUST.lock().await;
+    // 100个ID需要至少7个毫秒，同一次加锁内必然用尽序列号
+    let (status, body) = get("/ids?count=100").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!body["error"].as_str().unwrap().is_empty());
+    // 等到下一毫秒再释放，其他测试不受影响
+    tokio::time::sleep(Duration::from_millis(2)).await;
+}
+
+#[tokio::test]
+async fn health_and_metrics() {
+    let (status, body) = get("/health").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["worker_id"], 42);
+    assert_eq!(body["strategy"], "env");
+
+    setup();
+    let response = snowflake::http::app(MAX_COUNT)
+        .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(String::from_utf8(body.to_vec()).unwrap().contains("snowflake_ids_generated_total"));
+}
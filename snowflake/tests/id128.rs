@@ -0,0 +1,154 @@
+//! UUIDv7、ULID和UUIDv8转换测试
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use snowflake::encoding::DecodeError;
+use snowflake::id128::{
+    snowflake_from_uuid_v8, snowflake_to_uuid_v8, Id128Config, Ulid, UlidGenerator, UuidV7Generator,
+};
+use snowflake::{get_next_id, Clock, SnowflakeError};
+use uuid::{Uuid, Variant};
+
+/// 手动控制的时钟: sleep直接推进时间
+#[derive(Debug)]
+struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    fn new(ms: u64) -> Arc<Self> {
+        Arc::new(Self(AtomicU64::new(ms)))
+    }
+
+    fn set(&self, ms: u64) {
+        self.0.store(ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> Result<u64, SnowflakeError> {
+        Ok(self.0.load(Ordering::SeqCst))
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.0.fetch_add(duration.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+const START_MS: u64 = 1_750_000_000_000;
+
+#[test]
+fn uuid_v7_layout_and_monotonicity() {
+    let generator = UuidV7Generator::default();
+    let mut last = Uuid::nil();
+    for _ in 0..10_000 {
+        let uuid = generator.next_uuid().unwrap();
+        assert_eq!(uuid.get_version_num(), 7);
+        assert_eq!(uuid.get_variant(), Variant::RFC4122);
+        assert!(uuid > last, "{} <= {}", uuid, last);
+        // 字符串顺序与数值顺序一致
+        assert!(uuid.to_string() > last.to_string());
+        last = uuid;
+    }
+}
+
+#[test]
+fn uuid_v7_is_monotonic_within_frozen_millisecond() {
+    let clock = ManualClock::new(START_MS);
+    let generator = UuidV7Generator::with_clock(None, clock.clone());
+    let first = generator.next_uuid().unwrap();
+    let mut last = first;
+    for _ in 0..1000 {
+        let uuid = generator.next_uuid().unwrap();
+        assert_eq!(uuid.as_u128() - last.as_u128(), 1);
+        last = uuid;
+    }
+    assert_eq!((first.as_u128() >> 80) as u64, START_MS);
+    assert_eq!((last.as_u128() >> 80) as u64, START_MS);
+}
+
+#[test]
+fn clock_backward_is_shared_with_snowflake_policy() {
+    let clock = ManualClock::new(START_MS);
+    let config = Some(Id128Config { max_backward_ms: 10 });
+    let uuids = UuidV7Generator::with_clock(config.clone(), clock.clone());
+    let ulids = UlidGenerator::with_clock(config, clock.clone());
+    let uuid = uuids.next_uuid().unwrap();
+    let ulid = ulids.next_ulid().unwrap();
+
+    // 容忍范围内: 等待时钟追上后继续递增
+    clock.set(START_MS - 5);
+    assert!(uuids.next_uuid().unwrap() > uuid);
+    assert!(ulids.next_ulid().unwrap() > ulid);
+    assert!(clock.now_ms().unwrap() > START_MS);
+
+    // 超出容忍范围: 返回时钟回拨错误
+    clock.set(START_MS - 1000);
+    assert!(matches!(uuids.next_uuid(), Err(SnowflakeError::ClockBackward(_))));
+    assert!(matches!(ulids.next_ulid(), Err(SnowflakeError::ClockBackward(_))));
+}
+
+#[test]
+fn ulid_round_trips_and_sorts() {
+    let clock = ManualClock::new(START_MS);
+    let generator = UlidGenerator::with_clock(None, clock.clone());
+    let mut ulids = Vec::new();
+    for i in 0..2000 {
+        if i % 100 == 0 {
+            clock.set(START_MS + i);
+        }
+        ulids.push(generator.next_ulid().unwrap());
+    }
+
+    for window in ulids.windows(2) {
+        assert!(window[0] < window[1]);
+        assert!(window[0].to_string() < window[1].to_string());
+    }
+    for ulid in &ulids {
+        let s = ulid.to_string();
+        assert_eq!(s.len(), 26);
+        assert_eq!(s.parse::<Ulid>(), Ok(*ulid));
+        assert_eq!(s.to_lowercase().parse::<Ulid>(), Ok(*ulid));
+    }
+    assert_eq!(ulids[0].timestamp_ms(), START_MS);
+}
+
+#[test]
+fn ulid_parsing_errors() {
+    assert_eq!(Ulid::from_u128(u128::MAX).to_string(), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+    assert_eq!(Ulid::from_u128(0).to_string(), "00000000000000000000000000");
+    assert_eq!(
+        "0123".parse::<Ulid>(),
+        Err(DecodeError::InvalidLength { expected: 26, actual: 4 })
+    );
+    assert_eq!(
+        "0000000000000000000000000U".parse::<Ulid>(),
+        Err(DecodeError::InvalidChar { ch: 'U', position: 25 })
+    );
+    assert_eq!("80000000000000000000000000".parse::<Ulid>(), Err(DecodeError::Overflow));
+}
+
+#[test]
+fn snowflake_round_trips_through_uuid_v8() {
+    let mut ids = vec![0, 1, 0xf, 0x10, u32::MAX as u64, i64::MAX as u64, u64::MAX];
+    ids.extend((0..1000).map(|_| get_next_id().unwrap()));
+
+    for &id in &ids {
+        let uuid = snowflake_to_uuid_v8(id);
+        assert_eq!(uuid.get_version_num(), 8);
+        assert_eq!(uuid.get_variant(), Variant::RFC4122);
+        assert_eq!(snowflake_from_uuid_v8(&uuid), Some(id));
+    }
+
+    // 顺序保持一致
+    ids.sort_unstable();
+    for window in ids.windows(2) {
+        assert!(snowflake_to_uuid_v8(window[0]) < snowflake_to_uuid_v8(window[1]));
+    }
+
+    // 其他UUID不能被误认为雪花ID
+    let v7 = UuidV7Generator::default().next_uuid().unwrap();
+    assert_eq!(snowflake_from_uuid_v8(&v7), None);
+    let tampered = Uuid::from_u128(snowflake_to_uuid_v8(42).as_u128() | 1);
+    assert_eq!(snowflake_from_uuid_v8(&tampered), None);
+}
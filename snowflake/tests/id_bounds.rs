@@ -0,0 +1,67 @@
+//! 时间范围ID边界测试
+
+use chrono::{Duration, TimeZone, Utc};
+use snowflake::{
+    decode_id, get_next_id, max_id_for, min_id_for, SnowflakeConfig, SnowflakeError, TWEPOCH_MS,
+};
+
+#[test]
+fn generated_ids_fall_within_bounds() {
+    let before = Utc::now();
+    let ids: Vec<u64> = (0..1000).map(|_| get_next_id().unwrap()).collect();
+    let after = Utc::now();
+
+    let min = min_id_for(&before).unwrap();
+    let max = max_id_for(&after).unwrap();
+    for id in ids {
+        assert!(min <= id && id <= max, "{} 不在 [{}, {}] 内", id, min, max);
+    }
+
+    // 前一毫秒的最大ID紧挨着当前毫秒的最小ID
+    let id = get_next_id().unwrap();
+    let ms = decode_id(id).timestamp_ms as i64;
+    let at = Utc.timestamp_millis_opt(ms).unwrap();
+    assert!(min_id_for(&at).unwrap() <= id && id <= max_id_for(&at).unwrap());
+    assert_eq!(max_id_for(&(at - Duration::milliseconds(1))).unwrap() + 1, min_id_for(&at).unwrap());
+}
+
+#[test]
+fn bounds_follow_layout() {
+    let config = SnowflakeConfig {
+        worker_id_bits: 10,
+        sequence_bits: 10,
+        ..Default::default()
+    };
+    let epoch = Utc.timestamp_millis_opt(TWEPOCH_MS as i64).unwrap();
+    assert_eq!(config.min_id_for(&epoch).unwrap(), 0);
+    assert_eq!(config.max_id_for(&epoch).unwrap(), (1 << 20) - 1);
+
+    // 亚毫秒部分向下取整
+    let t = epoch + Duration::milliseconds(5) + Duration::microseconds(999);
+    assert_eq!(config.min_id_for(&t).unwrap(), 5 << 20);
+    assert_eq!(config.max_id_for(&t).unwrap(), (6 << 20) - 1);
+
+    // 时区不影响结果
+    let shanghai = chrono::FixedOffset::east_opt(8 * 3600).unwrap();
+    assert_eq!(
+        config.min_id_for(&t.with_timezone(&shanghai)).unwrap(),
+        config.min_id_for(&t).unwrap()
+    );
+}
+
+#[test]
+fn rejects_times_outside_layout() {
+    let config = SnowflakeConfig::default();
+    let epoch = Utc.timestamp_millis_opt(TWEPOCH_MS as i64).unwrap();
+
+    let before = epoch - Duration::milliseconds(1);
+    assert!(matches!(config.min_id_for(&before), Err(SnowflakeError::TimeOutOfRange(_))));
+    assert!(matches!(config.max_id_for(&before), Err(SnowflakeError::TimeOutOfRange(_))));
+
+    // 默认布局: 63 - 8 - 12 = 43位时间戳
+    assert_eq!(config.timestamp_bits(), 43);
+    let end = Utc.timestamp_millis_opt(config.max_timestamp_ms() as i64).unwrap();
+    assert_eq!(config.max_id_for(&end).unwrap(), i64::MAX as u64);
+    let past_end = end + Duration::milliseconds(1);
+    assert!(matches!(config.min_id_for(&past_end), Err(SnowflakeError::TimeOutOfRange(_))));
+}
@@ -0,0 +1,29 @@
+//! Worker ID文件租约测试
+
+use std::env;
+use std::fs;
+use std::process;
+
+use snowflake::lease::WorkerLease;
+
+#[test]
+fn leases_distinct_ids_and_releases_on_drop() {
+    let dir = env::temp_dir().join(format!("snowflake-lease-test-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let first = WorkerLease::acquire(&dir, 2).unwrap();
+    let second = WorkerLease::acquire(&dir, 2).unwrap();
+    let third = WorkerLease::acquire(&dir, 2).unwrap();
+    assert_eq!((first.worker_id(), second.worker_id(), third.worker_id()), (0, 1, 2));
+    assert_eq!(fs::read_to_string(second.path()).unwrap().trim(), process::id().to_string());
+
+    // 所有Worker ID都被占用
+    assert!(WorkerLease::acquire(&dir, 2).is_err());
+
+    // 释放后可以重新租到同一个Worker ID
+    drop(second);
+    assert_eq!(WorkerLease::acquire(&dir, 2).unwrap().worker_id(), 1);
+
+    drop((first, third));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,120 @@
+//! 诊断日志: 通过tracing输出结构化字段，库代码不写stdout
+
+use std::env;
+use std::io::{self, Write};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use snowflake::simulation::SimulatedClock;
+use snowflake::{SnowflakeError, SnowflakeIdWorker, TWEPOCH_MS};
+use tracing::Level;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// 测试会修改Worker ID相关的环境变量，创建生成器时需要互斥
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// 收集日志输出
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Captured {
+    type Writer = Captured;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// 在当前线程收集f执行期间的日志 (debug及以上)
+fn capture<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(captured.clone())
+        .with_ansi(false)
+        .with_max_level(Level::DEBUG)
+        .finish();
+    let result = tracing::subscriber::with_default(subscriber, f);
+    let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    (result, logs)
+}
+
+/// 包含指定级别和全部字段的日志行
+fn has_line(logs: &str, level: &str, fields: &[&str]) -> bool {
+    logs.lines().any(|line| line.contains(level) && fields.iter().all(|field| line.contains(field)))
+}
+
+#[test]
+fn worker_id_assignment_logs_strategy() {
+    let _env = ENV_LOCK.lock().unwrap();
+    env::remove_var(snowflake::lease::LEASE_DIR_ENV);
+    let clock = SimulatedClock::new(TWEPOCH_MS + 60_000);
+
+    env::set_var("SNOWFLAKE_WORKER_ID", "42");
+    let (worker, logs) = capture(|| SnowflakeIdWorker::with_clock(None, clock.clone()).unwrap());
+    assert_eq!(worker.get_worker_id(), 42);
+    assert!(has_line(&logs, "INFO", &["worker_id=42", "strategy=\"env\""]), "{}", logs);
+
+    // 无效的环境变量: 警告后改用下一种方式 (空的租约目录，租到Worker ID 0)
+    let lease_dir = env::temp_dir().join(format!("snowflake-logging-lease-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&lease_dir);
+    env::set_var("SNOWFLAKE_WORKER_ID", "abc");
+    env::set_var(snowflake::lease::LEASE_DIR_ENV, &lease_dir);
+    let (worker, logs) = capture(|| SnowflakeIdWorker::with_clock(None, clock.clone()).unwrap());
+    env::remove_var("SNOWFLAKE_WORKER_ID");
+    env::remove_var(snowflake::lease::LEASE_DIR_ENV);
+    assert_eq!(worker.get_worker_id(), 0);
+    assert!(has_line(&logs, "WARN", &["value=abc"]), "{}", logs);
+    assert!(has_line(&logs, "INFO", &["worker_id=0", "strategy=\"lease\""]), "{}", logs);
+    drop(worker);
+    let _ = std::fs::remove_dir_all(&lease_dir);
+}
+
+#[test]
+fn clock_backward_logs_diff() {
+    let _env = ENV_LOCK.lock().unwrap();
+    env::set_var("SNOWFLAKE_WORKER_ID", "1");
+    let clock = SimulatedClock::new(TWEPOCH_MS + 60_000);
+    let mut worker = SnowflakeIdWorker::with_clock(None, clock.clone()).unwrap();
+    env::remove_var("SNOWFLAKE_WORKER_ID");
+    worker.next_id().unwrap();
+
+    // 容忍范围内: 警告并等待
+    clock.step(-5);
+    let (result, logs) = capture(|| worker.next_id());
+    assert!(result.is_ok());
+    assert!(has_line(&logs, "WARN", &["diff_ms=5", "max_backward_ms=10"]), "{}", logs);
+
+    // 超出容忍范围: 错误
+    clock.step(-50);
+    let (result, logs) = capture(|| worker.next_id());
+    assert!(matches!(result, Err(SnowflakeError::ClockBackward(_))));
+    assert!(has_line(&logs, "ERROR", &["max_backward_ms=10"]), "{}", logs);
+    assert!(!has_line(&logs, "WARN", &[]), "{}", logs);
+}
+
+#[test]
+fn cli_keeps_logs_off_stdout() {
+    let output = Command::new(env!("CARGO_BIN_EXE_snowflake"))
+        .args(["gen", "-n", "3"])
+        .env("SNOWFLAKE_WORKER_ID", "42")
+        .env("RUST_LOG", "debug")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.lines().all(|line| line.parse::<u64>().is_ok()), "{}", stdout);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("使用环境变量Worker ID"), "{}", stderr);
+}
@@ -0,0 +1,112 @@
+//! loom模型检查: 在所有线程交错下生成的ID都不重复
+//!
+//! 运行: RUSTFLAGS="--cfg loom" cargo test --release --test loom
+#![cfg(loom)]
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use loom::sync::{Arc, Mutex};
+use loom::thread;
+use snowflake::{
+    AtomicIdWorker, Clock, SequenceExhaustedPolicy, SnowflakeConfig, SnowflakeError, SnowflakeIdWorker, TWEPOCH_MS,
+};
+
+/// 每读取N次前进1毫秒的时钟
+///
+/// 每次模型执行都重新创建，读取顺序由loom的调度决定，因此同一调度下结果确定。
+/// 序列号用尽后的等待依靠读取推进时间，不会无限自旋。
+#[derive(Debug)]
+struct TickingClock {
+    reads: AtomicU64,
+    reads_per_ms: u64,
+}
+
+impl TickingClock {
+    fn new(reads_per_ms: u64) -> std::sync::Arc<Self> {
+        std::sync::Arc::new(Self {
+            reads: AtomicU64::new(0),
+            reads_per_ms,
+        })
+    }
+}
+
+impl Clock for TickingClock {
+    fn now_ms(&self) -> Result<u64, SnowflakeError> {
+        let reads = self.reads.fetch_add(1, Ordering::SeqCst);
+        Ok(TWEPOCH_MS + 1_000 + reads / self.reads_per_ms)
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.reads
+            .fetch_add(duration.as_millis() as u64 * self.reads_per_ms, Ordering::SeqCst);
+    }
+}
+
+/// 每毫秒只有2个序列号，少量ID就能覆盖序列号用尽和跨毫秒的交错
+fn config(max_backward_ms: u64) -> SnowflakeConfig {
+    SnowflakeConfig {
+        sequence_bits: 1,
+        max_backward_ms,
+        sequence_exhausted_policy: SequenceExhaustedPolicy::Sleep,
+        ..Default::default()
+    }
+}
+
+/// 两个线程各生成两个ID，主线程生成一个，检查全部不重复且每个线程内严格递增
+fn check_unique(next_id: impl Fn() -> u64 + Send + Sync + 'static) {
+    let next_id = Arc::new(next_id);
+    let handles: Vec<_> = (0..2)
+        .map(|_| {
+            let next_id = next_id.clone();
+            thread::spawn(move || {
+                let first = next_id();
+                let second = next_id();
+                assert!(first < second, "线程内ID回退: {} >= {}", first, second);
+                [first, second]
+            })
+        })
+        .collect();
+
+    let mut ids = vec![next_id()];
+    for handle in handles {
+        ids.extend(handle.join().unwrap());
+    }
+    let unique: HashSet<u64> = ids.iter().copied().collect();
+    assert_eq!(unique.len(), ids.len(), "重复ID: {:?}", ids);
+}
+
+fn model(f: impl Fn() + Sync + Send + 'static) {
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound = Some(3);
+    builder.check(f);
+}
+
+#[test]
+fn atomic_worker_borrowing_ahead() {
+    // 时钟冻结 (读取次数远多于ID数)，5个ID需要借用未来2毫秒
+    model(|| {
+        let worker = AtomicIdWorker::with_clock(Some(config(3)), 1, TickingClock::new(1_000)).unwrap();
+        check_unique(move || worker.next_id().unwrap());
+    });
+}
+
+#[test]
+fn atomic_worker_waiting_for_clock() {
+    // 不允许领先时钟: 序列号用尽后自旋等待时钟前进
+    model(|| {
+        let worker = AtomicIdWorker::with_clock(Some(config(0)), 1, TickingClock::new(2)).unwrap();
+        check_unique(move || worker.next_id().unwrap());
+    });
+}
+
+#[test]
+fn mutex_worker() {
+    std::env::set_var("SNOWFLAKE_WORKER_ID", "1");
+    model(|| {
+        let worker = SnowflakeIdWorker::with_clock(Some(config(10)), TickingClock::new(2)).unwrap();
+        let worker = Mutex::new(worker);
+        check_unique(move || worker.lock().unwrap().next_id().unwrap());
+    });
+}
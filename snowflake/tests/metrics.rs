@@ -0,0 +1,119 @@
+//! Prometheus指标渲染
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use snowflake::simulation::SimulatedClock;
+use snowflake::{
+    get_next_ids, render_prometheus, MetricsSnapshot, SequenceStats, SnowflakeIdWorker, WorkerIdStrategy,
+    TWEPOCH_MS,
+};
+
+/// 取指标的值 (不带标签的样本行)
+fn sample(text: &str, name: &str) -> f64 {
+    text.lines()
+        .find_map(|line| line.strip_prefix(name)?.strip_prefix(' '))
+        .unwrap_or_else(|| panic!("缺少指标 {}:\n{}", name, text))
+        .parse()
+        .unwrap()
+}
+
+/// 检查文本格式: 每个样本之前有HELP和TYPE，名称不重复，值是数字
+fn assert_well_formed(text: &str) {
+    let mut typed = HashSet::new();
+    let mut sampled = HashSet::new();
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("# TYPE ") {
+            let (name, kind) = rest.split_once(' ').unwrap();
+            assert!(matches!(kind, "counter" | "gauge"), "{}", line);
+            assert!(typed.insert(name.to_string()), "重复的TYPE: {}", line);
+            continue;
+        }
+        if line.starts_with("# HELP ") {
+            continue;
+        }
+        let (series, value) = line.rsplit_once(' ').unwrap();
+        let name = series.split('{').next().unwrap();
+        assert!(typed.contains(name), "样本之前没有TYPE: {}", line);
+        assert!(sampled.insert(series.to_string()), "重复的样本: {}", line);
+        assert!(value.parse::<f64>().is_ok(), "{}", line);
+        if name.ends_with("_total") {
+            assert!(text.contains(&format!("# TYPE {} counter", name)), "{}", line);
+        }
+    }
+    assert_eq!(typed.len(), sampled.len());
+}
+
+#[test]
+fn renders_snapshot_fields() {
+    let snapshot = MetricsSnapshot {
+        worker_id: 7,
+        strategy: WorkerIdStrategy::Lease,
+        ids_generated: 1234,
+        sequence: SequenceStats {
+            active_ticks: 10,
+            exhausted_ticks: 3,
+            wait_count: 2,
+            wait_micros: 1500,
+            rejected_count: 1,
+            borrowed_ticks: 4,
+            max_borrow_ahead_ms: 2,
+        },
+        clock_backward_events: 5,
+        clock_backward_errors: 1,
+        clock_backward_max_ms: 250,
+        lock_acquisitions: 99,
+        lock_wait_total: Duration::from_millis(20),
+        lock_wait_max: Duration::from_micros(500),
+    };
+    let text = snapshot.render_prometheus();
+    assert_well_formed(&text);
+
+    assert!(text.contains("# HELP snowflake_ids_generated_total 成功生成的ID总数\n"));
+    assert!(text.contains("# TYPE snowflake_ids_generated_total counter\nsnowflake_ids_generated_total 1234\n"));
+    assert_eq!(sample(&text, "snowflake_sequence_active_ticks_total"), 10.0);
+    assert_eq!(sample(&text, "snowflake_sequence_exhausted_ticks_total"), 3.0);
+    assert_eq!(sample(&text, "snowflake_sequence_exhausted_waits_total"), 2.0);
+    assert_eq!(sample(&text, "snowflake_sequence_exhausted_wait_seconds_total"), 0.0015);
+    assert_eq!(sample(&text, "snowflake_sequence_exhausted_rejections_total"), 1.0);
+    assert_eq!(sample(&text, "snowflake_sequence_borrowed_ticks_total"), 4.0);
+    assert_eq!(sample(&text, "snowflake_clock_backward_events_total"), 5.0);
+    assert_eq!(sample(&text, "snowflake_clock_backward_errors_total"), 1.0);
+    assert_eq!(sample(&text, "snowflake_clock_backward_max_ms"), 250.0);
+    assert!(text.contains("# TYPE snowflake_clock_backward_max_ms gauge\n"));
+    assert_eq!(sample(&text, "snowflake_lock_acquisitions_total"), 99.0);
+    assert_eq!(sample(&text, "snowflake_lock_wait_seconds_total"), 0.02);
+    assert_eq!(sample(&text, "snowflake_lock_wait_max_seconds"), 0.0005);
+    assert_eq!(sample(&text, "snowflake_worker_id"), 7.0);
+    assert!(text.ends_with("snowflake_worker_info{worker_id=\"7\",strategy=\"lease\"} 1\n"), "{}", text);
+}
+
+#[test]
+fn worker_counters_show_up_in_output() {
+    let clock = SimulatedClock::new(TWEPOCH_MS + 60_000);
+    let mut worker = SnowflakeIdWorker::with_clock(None, clock.clone()).unwrap();
+    for _ in 0..100 {
+        worker.next_id().unwrap();
+    }
+    clock.step(-5);
+    worker.next_id().unwrap();
+    clock.step(-500);
+    assert!(worker.next_id().is_err());
+
+    let text = worker.metrics_snapshot().render_prometheus();
+    assert_well_formed(&text);
+    assert_eq!(sample(&text, "snowflake_ids_generated_total"), 101.0);
+    assert_eq!(sample(&text, "snowflake_clock_backward_events_total"), 2.0);
+    assert_eq!(sample(&text, "snowflake_clock_backward_errors_total"), 1.0);
+    assert!(sample(&text, "snowflake_clock_backward_max_ms") >= 500.0);
+    assert_eq!(sample(&text, "snowflake_worker_id"), worker.get_worker_id() as f64);
+}
+
+#[test]
+fn global_render_reflects_generated_ids() {
+    get_next_ids(10).unwrap();
+    let text = render_prometheus();
+    assert_well_formed(&text);
+    assert!(sample(&text, "snowflake_ids_generated_total") >= 10.0);
+    assert!(sample(&text, "snowflake_lock_acquisitions_total") >= 1.0);
+}
@@ -0,0 +1,114 @@
+//! 对外ID混淆测试
+
+use std::collections::HashSet;
+
+use snowflake::encoding::DecodeError;
+use snowflake::get_next_id;
+use snowflake::obfuscation::{ObfuscationKey, Obfuscator, RevealError, PUBLIC_ID_WIDTH};
+
+fn key(version: u8, secret: &str) -> ObfuscationKey {
+    ObfuscationKey::new(version, secret.as_bytes()).unwrap()
+}
+
+/// 确定性的伪随机数 (xorshift64)
+fn sample_values(count: usize) -> Vec<u64> {
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut values = vec![0, 1, u32::MAX as u64, 1 << 32, u64::MAX - 1, u64::MAX];
+    for _ in 0..count {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        values.push(state);
+    }
+    values
+}
+
+#[test]
+fn permutation_is_a_bijection() {
+    let key = key(1, "secret");
+
+    // 可逆 (单射)，在有限集合u64上即为双射
+    for id in sample_values(100_000) {
+        assert_eq!(key.unpermute(key.permute(id)), id);
+        assert_eq!(key.permute(key.unpermute(id)), id);
+    }
+
+    // 连续的输入 (同一毫秒内的序列号) 不产生冲突，也看不出连续性
+    let base = get_next_id().unwrap();
+    let outputs: HashSet<u64> = (0..1_000_000).map(|i| key.permute(base + i)).collect();
+    assert_eq!(outputs.len(), 1_000_000);
+    let adjacent = (0..1000)
+        .filter(|&i| key.permute(base + i + 1).abs_diff(key.permute(base + i)) < 1 << 32)
+        .count();
+    assert!(adjacent < 10, "相邻ID的公开值过于接近: {}", adjacent);
+}
+
+#[test]
+fn different_keys_give_different_permutations() {
+    let a = key(1, "secret-a");
+    let b = key(1, "secret-b");
+    let c = key(2, "secret-a");
+    let id = get_next_id().unwrap();
+    assert_ne!(a.permute(id), b.permute(id));
+    assert_ne!(a.permute(id), c.permute(id));
+    assert_eq!(a.permute(id), key(1, "secret-a").permute(id));
+}
+
+#[test]
+fn public_ids_round_trip_and_embed_version() {
+    let obfuscator = Obfuscator::new(key(7, "current"), Vec::new()).unwrap();
+    for _ in 0..1000 {
+        let id = get_next_id().unwrap();
+        let public = obfuscator.obfuscate(id);
+        assert_eq!(public.len(), PUBLIC_ID_WIDTH);
+        assert!(public.starts_with('7'));
+        assert!(!public.contains(&id.to_string()));
+        assert_eq!(obfuscator.reveal(&public), Ok(id));
+    }
+}
+
+#[test]
+fn old_public_ids_survive_key_rotation() {
+    let mut obfuscator = Obfuscator::new(key(1, "v1"), Vec::new()).unwrap();
+    let id = get_next_id().unwrap();
+    let old_public = obfuscator.obfuscate(id);
+
+    obfuscator.rotate(key(2, "v2")).unwrap();
+    assert_eq!(obfuscator.current_version(), 2);
+    let new_public = obfuscator.obfuscate(id);
+    assert_ne!(old_public, new_public);
+    assert!(new_public.starts_with('2'));
+
+    assert_eq!(obfuscator.reveal(&old_public), Ok(id));
+    assert_eq!(obfuscator.reveal(&new_public), Ok(id));
+
+    // 删除历史密钥后，旧的公开ID无法还原
+    let without_v1 = Obfuscator::new(key(2, "v2"), Vec::new()).unwrap();
+    assert_eq!(without_v1.reveal(&old_public), Err(RevealError::UnknownKeyVersion(1)));
+}
+
+#[test]
+fn rejects_invalid_configuration_and_input() {
+    assert!(ObfuscationKey::new(62, b"secret").is_err());
+    assert!(ObfuscationKey::new(0, b"").is_err());
+    assert!(Obfuscator::new(key(1, "a"), vec![key(1, "b")]).is_err());
+    let mut obfuscator = Obfuscator::new(key(1, "a"), vec![key(0, "b")]).unwrap();
+    assert!(obfuscator.rotate(key(0, "c")).is_err());
+
+    assert_eq!(
+        obfuscator.reveal("1abc"),
+        Err(RevealError::Decode(DecodeError::InvalidLength { expected: 12, actual: 4 }))
+    );
+    assert_eq!(
+        obfuscator.reveal("-00000000000"),
+        Err(RevealError::Decode(DecodeError::InvalidChar { ch: '-', position: 0 }))
+    );
+    assert_eq!(
+        obfuscator.reveal("100000_00000"),
+        Err(RevealError::Decode(DecodeError::InvalidChar { ch: '_', position: 6 }))
+    );
+    assert_eq!(
+        obfuscator.reveal("1zzzzzzzzzzz"),
+        Err(RevealError::Decode(DecodeError::Overflow))
+    );
+}
@@ -0,0 +1,99 @@
+//! 预取客户端端到端测试 (本机回环地址)
+
+use std::collections::HashSet;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use snowflake::client::{PrefetchClient, PrefetchConfig};
+use snowflake::{proto, SnowflakeError};
+
+/// 在随机端口启动二进制协议服务端，返回监听地址
+fn start_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || proto::serve(listener, 10_000));
+    addr
+}
+
+/// 一个已关闭的本地端口 (连接会被立即拒绝)
+fn dead_endpoint() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+#[test]
+fn falls_back_to_next_endpoint_and_refills() {
+    let client = Arc::new(
+        PrefetchClient::new(PrefetchConfig {
+            endpoints: vec![dead_endpoint(), start_server()],
+            batch_size: 100,
+            low_water_mark: 20,
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+
+    // 多个线程一共取走多批ID，期间需要多次后台补充
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let client = Arc::clone(&client);
+            thread::spawn(move || (0..500).map(|_| client.next_id().unwrap()).collect::<Vec<_>>())
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    for handle in handles {
+        let ids = handle.join().unwrap();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        for id in ids {
+            assert!(seen.insert(id), "重复ID: {}", id);
+        }
+    }
+    assert_eq!(seen.len(), 2000);
+}
+
+#[test]
+fn zero_low_water_mark_refills_when_empty() {
+    let client = PrefetchClient::new(PrefetchConfig {
+        endpoints: vec![start_server()],
+        batch_size: 10,
+        low_water_mark: 0,
+        timeout: Duration::from_secs(2),
+        ..Default::default()
+    })
+    .unwrap();
+
+    // 每取空一批才补充下一批
+    let ids: Vec<u64> = (0..35).map(|_| client.next_id().unwrap()).collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    assert!(client.buffered() <= 10);
+}
+
+#[test]
+fn reports_error_when_all_endpoints_are_down() {
+    let client = PrefetchClient::new(PrefetchConfig {
+        endpoints: vec![dead_endpoint(), dead_endpoint()],
+        timeout: Duration::from_millis(500),
+        ..Default::default()
+    })
+    .unwrap();
+
+    match client.next_id() {
+        Err(SnowflakeError::NetworkError(_)) => {}
+        other => panic!("期望网络错误, 实际: {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_invalid_config() {
+    assert!(PrefetchClient::new(PrefetchConfig::default()).is_err());
+    assert!(PrefetchClient::new(PrefetchConfig {
+        endpoints: vec!["127.0.0.1:1".to_string()],
+        batch_size: 10,
+        low_water_mark: 10,
+        ..Default::default()
+    })
+    .is_err());
+}
@@ -0,0 +1,50 @@
+//! 多进程唯一性测试程序
+
+use std::process::Command;
+
+use serde_json::Value;
+
+#[test]
+fn all_strategies_produce_unique_ids() {
+    let output = Command::new(env!("CARGO_BIN_EXE_process_harness"))
+        .args(["--processes", "4", "--ids", "20000", "--json"])
+        .env("RUST_LOG", "off")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["passed"], true);
+    let rounds = report["rounds"].as_array().unwrap();
+    let strategies: Vec<&str> = rounds.iter().map(|r| r["strategy"].as_str().unwrap()).collect();
+    assert_eq!(strategies, vec!["env", "config", "lease"]);
+
+    for round in rounds {
+        assert_eq!(round["total_ids"], 80_000);
+        assert_eq!(round["duplicate_ids"], 0);
+        let children = round["children"].as_array().unwrap();
+        assert_eq!(children.len(), 4);
+        for child in children {
+            assert_eq!(child["strategy"], round["strategy"]);
+        }
+    }
+
+    // 租约目录为空时从0开始依次分配
+    let mut leased: Vec<u64> = rounds[2]["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["worker_id"].as_u64().unwrap())
+        .collect();
+    leased.sort_unstable();
+    assert_eq!(leased, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn rejects_unknown_strategy() {
+    let output = Command::new(env!("CARGO_BIN_EXE_process_harness"))
+        .args(["--strategies", "env,dns"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
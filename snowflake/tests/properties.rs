@@ -0,0 +1,258 @@
+//! 基于性质的测试: 位布局编解码、序列号回绕、配置文件解析
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use proptest::prelude::*;
+use snowflake::encoding::Encoding;
+use snowflake::{
+    parse_worker_id_mapping, AtomicIdWorker, Clock, SequenceExhaustedPolicy, SequenceStart, SnowflakeConfig,
+    SnowflakeError, SnowflakeIdWorker, TWEPOCH_MS,
+};
+
+/// 手动控制的时钟: sleep直接推进时间
+#[derive(Debug)]
+struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    fn new(ms: u64) -> Arc<Self> {
+        Arc::new(Self(AtomicU64::new(ms)))
+    }
+
+    fn advance(&self, ms: u64) {
+        self.0.fetch_add(ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> Result<u64, SnowflakeError> {
+        Ok(self.0.load(Ordering::SeqCst))
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration.as_millis() as u64);
+    }
+}
+
+/// 位布局: Worker ID 1-10位，序列号 1-16位
+fn layout() -> impl Strategy<Value = SnowflakeConfig> {
+    (1u8..=10, 1u8..=16).prop_map(|(worker_id_bits, sequence_bits)| SnowflakeConfig {
+        worker_id_bits,
+        sequence_bits,
+        ..Default::default()
+    })
+}
+
+fn sequence_start() -> impl Strategy<Value = SequenceStart> {
+    prop_oneof![
+        Just(SequenceStart::Zero),
+        Just(SequenceStart::Random),
+        Just(SequenceStart::Rotating),
+    ]
+}
+
+fn encoding() -> impl Strategy<Value = Encoding> {
+    prop_oneof![
+        Just(Encoding::Base62),
+        Just(Encoding::Base32),
+        Just(Encoding::Hex),
+        Just(Encoding::Base32Check),
+    ]
+}
+
+// ============================================================================
+// 位布局
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn decode_inverts_layout(config in layout(), ts in 0u64..1 << 41, worker in any::<u64>(), seq in any::<u64>()) {
+        let ts = ts & ((1u64 << config.timestamp_bits()) - 1);
+        let worker = worker & ((1u64 << config.worker_id_bits) - 1);
+        let seq = seq & ((1u64 << config.sequence_bits) - 1);
+        let id = ts << (config.worker_id_bits + config.sequence_bits) | worker << config.sequence_bits | seq;
+
+        // 最高位始终为0
+        prop_assert!(id <= i64::MAX as u64);
+        let decoded = config.decode(id);
+        prop_assert_eq!(decoded.timestamp_ms, TWEPOCH_MS + ts);
+        prop_assert_eq!(decoded.worker_id, worker);
+        prop_assert_eq!(decoded.sequence, seq);
+
+        // 同一毫秒的所有ID都落在该毫秒的ID边界内
+        let time = Utc.timestamp_millis_opt(decoded.timestamp_ms as i64).unwrap();
+        let (min, max) = (config.min_id_for(&time).unwrap(), config.max_id_for(&time).unwrap());
+        prop_assert!(min <= id && id <= max);
+        prop_assert_eq!(config.decode(min).timestamp_ms, decoded.timestamp_ms);
+        prop_assert_eq!(config.decode(max).timestamp_ms, decoded.timestamp_ms);
+        prop_assert_eq!((config.decode(min).worker_id, config.decode(min).sequence), (0, 0));
+    }
+
+    #[test]
+    fn encodings_round_trip(encoding in encoding(), id in any::<u64>()) {
+        let text = encoding.encode(id);
+        prop_assert_eq!(text.len(), encoding.width());
+        prop_assert_eq!(encoding.decode(&text), Ok(id));
+    }
+
+    #[test]
+    fn encodings_preserve_order(encoding in encoding(), a in any::<u64>(), b in any::<u64>()) {
+        prop_assert_eq!(encoding.encode(a).cmp(&encoding.encode(b)), a.cmp(&b));
+    }
+
+    #[test]
+    fn atomic_worker_ids_decode_to_their_parts(
+        // Worker ID是u8，生成器只接受1-8位 (见 `check_layout`)
+        config in layout().prop_filter("Worker ID最多8位", |config| config.check_layout().is_ok()),
+        worker in any::<u8>(),
+        offset_ms in 0u64..1 << 40,
+        count in 1usize..200,
+    ) {
+        let worker = if config.worker_id_bits < 8 { worker & ((1u8 << config.worker_id_bits) - 1) } else { worker };
+        let now_ms = TWEPOCH_MS + (offset_ms & ((1u64 << config.timestamp_bits()) - 1) >> 1);
+        let clock = ManualClock::new(now_ms);
+        let generator = AtomicIdWorker::with_clock(Some(config.clone()), worker, clock.clone()).unwrap();
+
+        let mut last = 0;
+        for i in 0..count {
+            // 每毫秒2个ID，不超过最小的序列号容量
+            if i % 2 == 0 {
+                clock.advance(1);
+            }
+            let id = generator.next_id().unwrap();
+            prop_assert!(id > last);
+            last = id;
+            let decoded = config.decode(id);
+            prop_assert_eq!(decoded.worker_id, worker as u64);
+            // 时间戳最多领先时钟max_backward_ms毫秒
+            let clock_ms = clock.now_ms().unwrap();
+            prop_assert!(decoded.timestamp_ms <= clock_ms + config.max_backward_ms);
+        }
+    }
+}
+
+// ============================================================================
+// 序列号回绕
+// ============================================================================
+
+proptest! {
+    #[test]
+    fn sequence_wraps_without_duplicates(
+        sequence_bits in 1u8..=6,
+        start in sequence_start(),
+        borrow in any::<bool>(),
+        count in 1usize..600,
+    ) {
+        let config = SnowflakeConfig {
+            sequence_bits,
+            sequence_start: start,
+            // 冻结的时钟下只能借用未来毫秒或通过sleep推进时间
+            sequence_exhausted_policy: if borrow { SequenceExhaustedPolicy::Borrow(u64::MAX) } else { SequenceExhaustedPolicy::Sleep },
+            ..Default::default()
+        };
+        let clock = ManualClock::new(TWEPOCH_MS + 1_000);
+        let mut worker = SnowflakeIdWorker::with_clock(Some(config.clone()), clock).unwrap();
+
+        let capacity = 1u64 << sequence_bits;
+        let mut seen = HashSet::new();
+        let mut per_ms = std::collections::HashMap::new();
+        let mut last_ms = 0;
+        for _ in 0..count {
+            let id = worker.next_id().unwrap();
+            prop_assert!(seen.insert(id), "重复ID {}", id);
+            let decoded = config.decode(id);
+            prop_assert_eq!(decoded.worker_id, worker.get_worker_id() as u64);
+            // 时间戳不回退，每毫秒不超过序列号容量
+            prop_assert!(decoded.timestamp_ms >= last_ms);
+            last_ms = decoded.timestamp_ms;
+            let used = per_ms.entry(decoded.timestamp_ms).or_insert(0u64);
+            *used += 1;
+            prop_assert!(*used <= capacity);
+        }
+        // 用满的毫秒数与统计一致
+        let full = per_ms.values().filter(|&&used| used == capacity).count() as u64;
+        prop_assert!(worker.sequence_stats().exhausted_ticks >= full.saturating_sub(1));
+    }
+
+    #[test]
+    fn error_policy_issues_exactly_capacity_per_ms(sequence_bits in 1u8..=8, start in sequence_start()) {
+        let config = SnowflakeConfig {
+            sequence_bits,
+            sequence_start: start,
+            sequence_exhausted_policy: SequenceExhaustedPolicy::Error,
+            ..Default::default()
+        };
+        let clock = ManualClock::new(TWEPOCH_MS + 1_000);
+        let mut worker = SnowflakeIdWorker::with_clock(Some(config.clone()), clock.clone()).unwrap();
+
+        for _ in 0..3 {
+            let ids: HashSet<u64> = (0..1u64 << sequence_bits).map(|_| worker.next_id().unwrap()).collect();
+            prop_assert_eq!(ids.len() as u64, 1u64 << sequence_bits);
+            // 用尽后同一毫秒内持续报错，不会回绕发出重复ID
+            for _ in 0..3 {
+                prop_assert!(matches!(worker.next_id(), Err(SnowflakeError::SequenceExhausted(_))));
+            }
+            clock.advance(1);
+        }
+    }
+}
+
+// ============================================================================
+// 配置文件解析
+// ============================================================================
+
+/// 不影响解析结果的行: 注释、空行、其他配置项
+fn filler() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "# [^\n]*",
+        Just(String::new()),
+        "[ \t]*",
+        Just("worker_id_bits = 8".to_string()),
+        Just("max_backward_ms = 10".to_string()),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn mapping_combines_datacenter_and_machine(
+        datacenter_id in any::<u8>(),
+        machine_id in any::<u8>(),
+        fillers in prop::collection::vec(filler(), 0..6),
+        machine_first in any::<bool>(),
+        indent in "[ \t]{0,3}",
+        spaces in "[ ]{0,3}",
+        comment in "[^\n]*",
+        quoted in any::<bool>(),
+    ) {
+        let quote = if quoted { "\"" } else { "" };
+        let dc = format!("{}datacenter_id{}={}{}{}{}", indent, spaces, spaces, quote, datacenter_id, quote);
+        // 行尾注释 (可以包含任意文字，包括 `=` 和数字) 不影响解析
+        let m = format!("{}machine_id{}={}{}{}{}{}#{}", indent, spaces, spaces, quote, machine_id, quote, spaces, comment);
+        let mut lines = fillers;
+        let (first, second) = if machine_first { (m, dc) } else { (dc, m) };
+        lines.insert(lines.len() / 2, first);
+        lines.push(second);
+        let content = lines.join("\n");
+
+        prop_assert_eq!(
+            parse_worker_id_mapping(&content),
+            Some(((datacenter_id & 0x03) << 6) | (machine_id & 0x3F))
+        );
+    }
+
+    #[test]
+    fn mapping_requires_both_ids(value in any::<u8>(), key in prop_oneof!["datacenter_id", "machine_id"]) {
+        prop_assert_eq!(parse_worker_id_mapping(&format!("{} = {}", key, value)), None);
+        // 超出u8范围的值视为未配置
+        let content = format!("{} = {}\ndatacenter_id = 256\nmachine_id = 1", key, value);
+        prop_assert_eq!(parse_worker_id_mapping(&content), None);
+    }
+
+    #[test]
+    fn mapping_never_panics(content in "(?s).{0,200}") {
+        let _ = parse_worker_id_mapping(&content);
+    }
+}
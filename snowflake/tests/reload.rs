@@ -0,0 +1,240 @@
+//! 配置文件重新加载
+
+use std::env;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use snowflake::reload::{FileConfig, ReloadReport};
+use snowflake::simulation::SimulatedClock;
+use snowflake::{
+    parse_worker_id_mapping, SequenceExhaustedPolicy, SequenceStart, SnowflakeConfig, SnowflakeError,
+    SnowflakeIdWorker, WorkerIdStrategy, TWEPOCH_MS,
+};
+
+/// 测试会修改Worker ID相关的环境变量，创建生成器时需要互斥
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn parses_repository_config_and_policies() {
+    let file = FileConfig::parse(include_str!("../snowflake.toml")).unwrap();
+    assert_eq!(
+        file,
+        FileConfig {
+            worker_id: Some(69),
+            worker_id_bits: Some(8),
+            sequence_bits: Some(12),
+            max_backward_ms: Some(10),
+            sequence_exhausted_policy: None,
+            sequence_start: None,
+        }
+    );
+
+    let content = "\
+max_backward_ms = 50   # 容忍50ms
+sequence_exhausted_policy = \"borrow:5\"
+sequence_start = rotating
+";
+    let file = FileConfig::parse(content).unwrap();
+    assert_eq!(file.worker_id, None);
+    assert_eq!(file.max_backward_ms, Some(50));
+    assert_eq!(file.sequence_exhausted_policy, Some(SequenceExhaustedPolicy::Borrow(5)));
+    assert_eq!(file.sequence_start, Some(SequenceStart::Rotating));
+
+    // Worker ID映射与其他配置项使用同一套行解析: 行尾注释、引号
+    let content = "datacenter_id = \"1\"  # 西部\nmachine_id = 5  # rack 5\n";
+    assert_eq!(parse_worker_id_mapping(content), Some(69));
+    assert_eq!(FileConfig::parse(content).unwrap().worker_id, Some(69));
+
+    for invalid in ["max_backward_ms = -1", "sequence_exhausted_policy = \"wait\"", "sequence_bits = 12.5"] {
+        assert!(matches!(FileConfig::parse(invalid), Err(SnowflakeError::ConfigError(_))), "{}", invalid);
+    }
+}
+
+#[test]
+fn applies_safe_changes_and_switches_worker_id() {
+    let _env = ENV_LOCK.lock().unwrap();
+    env::remove_var("SNOWFLAKE_WORKER_ID");
+    env::remove_var("SNOWFLAKE_WORKER_LEASE_DIR");
+    let clock = SimulatedClock::new(TWEPOCH_MS + 60_000);
+    // 当前目录的snowflake.toml映射为Worker ID 69
+    let mut worker = SnowflakeIdWorker::with_clock(None, clock.clone()).unwrap();
+    assert_eq!(worker.get_worker_id_strategy(), WorkerIdStrategy::Config);
+
+    // 安全的修改立即生效，位布局的修改被拒绝
+    let report = worker
+        .apply_file_config(&FileConfig {
+            worker_id_bits: Some(10),
+            sequence_bits: Some(12),
+            max_backward_ms: Some(50),
+            sequence_exhausted_policy: Some(SequenceExhaustedPolicy::Error),
+            sequence_start: Some(SequenceStart::Zero),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(report.applied.len(), 2, "{:?}", report);
+    assert_eq!(report.rejected.len(), 1, "{:?}", report);
+    assert!(report.rejected[0].starts_with("worker_id_bits"));
+    assert_eq!(worker.config().max_backward_ms, 50);
+    assert_eq!(worker.config().sequence_exhausted_policy, SequenceExhaustedPolicy::Error);
+    assert_eq!(worker.config().worker_id_bits, 8);
+
+    // 再次加载相同的文件没有变化
+    let unchanged = FileConfig { max_backward_ms: Some(50), ..Default::default() };
+    assert_eq!(worker.apply_file_config(&unchanged).unwrap(), ReloadReport::default());
+
+    // 切换Worker ID: 旧ID所在的毫秒结束后才使用新ID
+    let before = worker.next_id().unwrap();
+    let report = worker
+        .apply_file_config(&FileConfig { worker_id: Some(70), ..Default::default() })
+        .unwrap();
+    assert_eq!(report.worker_id, Some((69, 70)));
+    let after = worker.next_id().unwrap();
+    let (before, after) = (worker.decode(before), worker.decode(after));
+    assert_eq!((before.worker_id, after.worker_id), (69, 70));
+    assert!(after.timestamp_ms > before.timestamp_ms);
+    assert_eq!(after.sequence, 0);
+
+    // 环境变量指定的Worker ID优先于配置文件
+    env::set_var("SNOWFLAKE_WORKER_ID", "3");
+    let mut worker = SnowflakeIdWorker::with_clock(None, clock).unwrap();
+    env::remove_var("SNOWFLAKE_WORKER_ID");
+    let report = worker
+        .apply_file_config(&FileConfig { worker_id: Some(70), ..Default::default() })
+        .unwrap();
+    assert_eq!(report.worker_id, None);
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(worker.get_worker_id(), 3);
+}
+
+#[test]
+fn file_config_overrides_startup_config() {
+    let file = FileConfig {
+        sequence_bits: Some(16),
+        max_backward_ms: Some(50),
+        sequence_start: Some(SequenceStart::Rotating),
+        ..Default::default()
+    };
+    let mut config = SnowflakeConfig::default();
+    file.apply_to(&mut config);
+    assert_eq!((config.worker_id_bits, config.sequence_bits), (8, 16));
+    assert_eq!(config.max_backward_ms, 50);
+    assert_eq!(config.sequence_exhausted_policy, SequenceExhaustedPolicy::Spin);
+    assert_eq!(config.sequence_start, SequenceStart::Rotating);
+
+    // 按文件创建的生成器重新加载同一个文件不会产生修改
+    let _env = ENV_LOCK.lock().unwrap();
+    env::remove_var("SNOWFLAKE_WORKER_ID");
+    env::remove_var("SNOWFLAKE_WORKER_LEASE_DIR");
+    let clock = SimulatedClock::new(TWEPOCH_MS + 60_000);
+    let mut worker = SnowflakeIdWorker::with_clock(Some(config), clock).unwrap();
+    assert_eq!(worker.apply_file_config(&file).unwrap(), ReloadReport::default());
+}
+
+#[test]
+fn worker_id_switch_gives_up_after_clock_step_back() {
+    let _env = ENV_LOCK.lock().unwrap();
+    env::remove_var("SNOWFLAKE_WORKER_ID");
+    env::remove_var("SNOWFLAKE_WORKER_LEASE_DIR");
+    let clock = SimulatedClock::new(TWEPOCH_MS + 60_000);
+    let mut worker = SnowflakeIdWorker::with_clock(None, clock.clone()).unwrap();
+    let last = worker.next_id().unwrap();
+
+    // 时钟回拨5秒: 不会在锁内等待5秒，切换被拒绝，其他修改照常生效
+    clock.step(-5_000);
+    let real_before = clock.real_ms();
+    let report = worker
+        .apply_file_config(&FileConfig { worker_id: Some(70), max_backward_ms: Some(20), ..Default::default() })
+        .unwrap();
+    assert!(clock.real_ms() - real_before < 100);
+    assert_eq!(report.worker_id, None);
+    assert_eq!(report.applied.len(), 1, "{:?}", report);
+    assert_eq!(report.rejected.len(), 1, "{:?}", report);
+    assert!(report.rejected[0].starts_with("worker_id: 69 -> 70"), "{:?}", report);
+    assert_eq!(worker.get_worker_id(), 69);
+    assert_eq!(worker.config().max_backward_ms, 20);
+
+    // 时钟恢复后可以切换
+    clock.step(5_000);
+    let report = worker
+        .apply_file_config(&FileConfig { worker_id: Some(70), ..Default::default() })
+        .unwrap();
+    assert_eq!(report.worker_id, Some((69, 70)));
+    assert!(worker.next_id().unwrap() > last);
+}
+
+#[test]
+fn worker_id_must_fit_the_file_layout() {
+    let _env = ENV_LOCK.lock().unwrap();
+    env::remove_var("SNOWFLAKE_WORKER_LEASE_DIR");
+    env::set_var("SNOWFLAKE_WORKER_ID", "69");
+
+    // 配置文件把Worker ID缩小到5位: 69移位后会进入时间戳部分
+    let mut config = SnowflakeConfig::default();
+    FileConfig { worker_id_bits: Some(5), ..Default::default() }.apply_to(&mut config);
+    let clock = SimulatedClock::new(TWEPOCH_MS + 60_000);
+    let result = SnowflakeIdWorker::with_clock(Some(config.clone()), clock.clone());
+    // 检查配置时仍然报告解析出的Worker ID
+    let resolved = SnowflakeIdWorker::resolve_worker_id(Some(config));
+    let fits = SnowflakeIdWorker::with_clock(
+        Some(SnowflakeConfig { worker_id_bits: 7, ..Default::default() }),
+        clock.clone(),
+    );
+    // 代码传入的无效位布局同样被拒绝
+    let invalid = SnowflakeIdWorker::with_clock(
+        Some(SnowflakeConfig { sequence_bits: 0, ..Default::default() }),
+        clock,
+    );
+    env::remove_var("SNOWFLAKE_WORKER_ID");
+
+    match result {
+        Err(SnowflakeError::ConfigError(msg)) => assert!(msg.contains("69") && msg.contains("5位"), "{}", msg),
+        other => panic!("应返回ConfigError: {:?}", other.map(|w| w.get_worker_id())),
+    }
+    assert_eq!(resolved.unwrap(), (Some(69), WorkerIdStrategy::Env));
+    assert_eq!(fits.unwrap().get_worker_id(), 69);
+    assert!(matches!(invalid, Err(SnowflakeError::ConfigError(_))));
+}
+
+#[test]
+fn leaving_borrow_keeps_the_borrowed_allowance_until_the_clock_catches_up() {
+    let _env = ENV_LOCK.lock().unwrap();
+    env::remove_var("SNOWFLAKE_WORKER_ID");
+    env::remove_var("SNOWFLAKE_WORKER_LEASE_DIR");
+    let clock = SimulatedClock::new(TWEPOCH_MS + 60_000);
+    let config = SnowflakeConfig {
+        sequence_bits: 4,
+        max_backward_ms: 10,
+        sequence_exhausted_policy: SequenceExhaustedPolicy::Borrow(5),
+        ..Default::default()
+    };
+    let mut worker = SnowflakeIdWorker::with_clock(Some(config), clock.clone()).unwrap();
+
+    // 冻结的时钟上每毫秒16个ID: 40个ID借用到当前毫秒之后第2个毫秒
+    clock.freeze(Duration::from_millis(50));
+    let tick = clock.reported_ms();
+    let ids: Vec<u64> = (0..40).map(|_| worker.next_id().unwrap()).collect();
+    let last = *ids.last().unwrap();
+    assert_eq!(worker.decode(last).timestamp_ms, tick + 2);
+    assert!(worker.clock_state().borrowing);
+
+    for policy in [SequenceExhaustedPolicy::Spin, SequenceExhaustedPolicy::Error] {
+        let report = worker
+            .apply_file_config(&FileConfig { sequence_exhausted_policy: Some(policy), ..Default::default() })
+            .unwrap();
+        assert_eq!(report.applied.len(), 1, "{:?}", report);
+
+        // 时钟没有移动: 继续使用借用的毫秒，不算时钟回拨，也不等待
+        let real_before = clock.real_ms();
+        let id = worker.next_id().unwrap();
+        assert!(id > last);
+        assert_eq!(worker.decode(id).timestamp_ms, tick + 2);
+        assert!(clock.real_ms() - real_before < 1);
+        assert_eq!(worker.metrics_snapshot().clock_backward_events, 0, "{:?}", policy);
+    }
+
+    // 时钟追上后恢复正常
+    clock.advance(Duration::from_millis(60));
+    let id = worker.next_id().unwrap();
+    assert!(worker.decode(id).timestamp_ms > tick + 2);
+    assert!(!worker.clock_state().borrowing);
+}
@@ -0,0 +1,155 @@
+//! 号段模式生成器测试
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use snowflake::segment::{SegmentAllocator, SegmentConfig};
+use snowflake::SnowflakeError;
+
+/// 每个测试使用独立的日志文件
+fn journal_path(name: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "snowflake-segment-{}-{}-{}.log",
+        name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn config(step: u64) -> Option<SegmentConfig> {
+    Some(SegmentConfig { step, ..Default::default() })
+}
+
+#[test]
+fn ids_are_dense_and_increasing_per_tag() {
+    let path = journal_path("dense");
+    let allocator = SegmentAllocator::open(&path, config(10)).unwrap();
+    let orders = allocator.generator("order").unwrap();
+    let users = allocator.generator("user").unwrap();
+
+    let order_ids: Vec<u64> = (0..35).map(|_| orders.next_id().unwrap()).collect();
+    let user_ids: Vec<u64> = (0..5).map(|_| users.next_id().unwrap()).collect();
+
+    assert_eq!(order_ids, (1..=35).collect::<Vec<_>>());
+    assert_eq!(user_ids, (1..=5).collect::<Vec<_>>());
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn restart_continues_after_leased_segments() {
+    let path = journal_path("restart");
+    let last = {
+        let allocator = SegmentAllocator::open(&path, config(100)).unwrap();
+        let generator = allocator.generator("order").unwrap();
+        let mut last = 0;
+        for _ in 0..150 {
+            last = generator.next_id().unwrap();
+        }
+        last
+    };
+
+    let allocator = SegmentAllocator::open(&path, config(100)).unwrap();
+    let next = allocator.next_id("order").unwrap();
+    assert!(next > last, "重启后ID回退: {} <= {}", next, last);
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn torn_journal_tail_is_discarded() {
+    let path = journal_path("torn");
+    fs::write(&path, "order 100\norder 2").unwrap();
+
+    // 号段用完才预加载，避免加载线程在读取日志前追加记录
+    let config = SegmentConfig { step: 10, preload_ratio: 1.0 };
+    let allocator = SegmentAllocator::open(&path, Some(config)).unwrap();
+    assert_eq!(allocator.next_id("order").unwrap(), 101);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "order 100\norder 110\n");
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn many_tags_preload_through_one_loader() {
+    let path = journal_path("many-tags");
+    let allocator = SegmentAllocator::open(&path, config(10)).unwrap();
+    let generators: Vec<_> = (0..50).map(|i| allocator.generator(&format!("tag{}", i)).unwrap()).collect();
+
+    // 每个标签都跨过多个号段，预加载请求在同一个加载线程中排队处理
+    for _ in 0..35 {
+        for generator in &generators {
+            generator.next_id().unwrap();
+        }
+    }
+    for generator in &generators {
+        assert_eq!(generator.next_id().unwrap(), 36, "{}", generator.tag());
+    }
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn journal_io_failure_is_an_io_error() {
+    let path = journal_path("missing-dir").join("segments.log");
+    match SegmentAllocator::open(&path, None) {
+        Err(SnowflakeError::IoError(msg)) => assert!(msg.contains("segments.log"), "{}", msg),
+        other => panic!("应返回IoError: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn journal_can_only_be_opened_once() {
+    let path = journal_path("locked");
+    let allocator = SegmentAllocator::open(&path, config(10)).unwrap();
+    let first = allocator.next_id("order").unwrap();
+
+    match SegmentAllocator::open(&path, config(10)) {
+        Err(SnowflakeError::ConfigError(msg)) => assert!(msg.contains("已被其他分配器打开"), "{}", msg),
+        other => panic!("应返回ConfigError: {:?}", other.map(|_| ())),
+    }
+
+    // 关闭后可以重新打开，并从已租用上限之后继续
+    drop(allocator);
+    let allocator = SegmentAllocator::open(&path, config(10)).unwrap();
+    assert!(allocator.next_id("order").unwrap() > first);
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn concurrent_generators_never_repeat() {
+    let path = journal_path("concurrent");
+    let allocator = SegmentAllocator::open(&path, config(50)).unwrap();
+    let generator = allocator.generator("order").unwrap();
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let generator = generator.clone();
+            thread::spawn(move || {
+                let ids: Vec<u64> = (0..1000).map(|_| generator.next_id().unwrap()).collect();
+                assert!(ids.windows(2).all(|w| w[0] < w[1]));
+                ids
+            })
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    for handle in handles {
+        for id in handle.join().unwrap() {
+            assert!(seen.insert(id), "重复ID: {}", id);
+        }
+    }
+    assert_eq!(seen.len(), 8000);
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn rejects_invalid_tags() {
+    let path = journal_path("tags");
+    let allocator = SegmentAllocator::open(&path, None).unwrap();
+    assert!(allocator.generator("").is_err());
+    assert!(allocator.generator("two words").is_err());
+    let _ = fs::remove_file(path);
+}
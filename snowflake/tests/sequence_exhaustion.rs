@@ -0,0 +1,138 @@
+//! 序列号用尽策略测试
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use snowflake::simulation::SimulatedClock;
+use snowflake::{SequenceExhaustedPolicy, SnowflakeConfig, SnowflakeError, SnowflakeIdWorker, TWEPOCH_MS};
+
+const START_MS: u64 = TWEPOCH_MS + 60_000;
+
+/// 每毫秒只有16个序列号，模拟时钟每次读取前进1微秒，很快就会用尽
+fn narrow_worker(policy: SequenceExhaustedPolicy) -> (std::sync::Arc<SimulatedClock>, SnowflakeIdWorker) {
+    let clock = SimulatedClock::new(START_MS);
+    let config = SnowflakeConfig {
+        sequence_bits: 4,
+        max_backward_ms: 10,
+        sequence_exhausted_policy: policy,
+        ..Default::default()
+    };
+    let worker = SnowflakeIdWorker::with_clock(Some(config), clock.clone()).unwrap();
+    (clock, worker)
+}
+
+#[test]
+fn step_back_while_borrowing_is_reported() {
+    let (clock, mut worker) = narrow_worker(SequenceExhaustedPolicy::Borrow(5));
+    let mut last = 0;
+    while !worker.clock_state().borrowing {
+        last = worker.next_id().unwrap();
+    }
+
+    clock.step(-5_000);
+    let real_before = clock.real_ms();
+    let err = worker.next_id().unwrap_err();
+    assert!(matches!(err, SnowflakeError::ClockBackward(_)), "{:?}", err);
+    // 立即报错，不会等待5秒
+    assert!(clock.real_ms() - real_before < 100);
+    assert!(!worker.clock_state().borrowing);
+
+    let metrics = worker.metrics_snapshot();
+    assert_eq!(metrics.clock_backward_errors, 1);
+    assert!(metrics.clock_backward_max_ms >= 4_990, "{}", metrics.clock_backward_max_ms);
+
+    // 时钟恢复后继续递增
+    clock.step(5_000);
+    assert!(worker.next_id().unwrap() > last);
+}
+
+#[test]
+fn small_step_back_within_borrow_limit_keeps_borrowing() {
+    let (clock, mut worker) = narrow_worker(SequenceExhaustedPolicy::Borrow(5));
+    let mut last = 0;
+    while !worker.clock_state().borrowing {
+        last = worker.next_id().unwrap();
+    }
+
+    // 回拨幅度在借用上限内: 沿用借用的毫秒，不算时钟回拨
+    clock.step(-1);
+    let id = worker.next_id().unwrap();
+    assert!(id > last);
+    assert_eq!(worker.metrics_snapshot().clock_backward_events, 0);
+}
+
+/// 生成count个ID，返回ID和每毫秒的ID数
+fn generate(worker: &mut SnowflakeIdWorker, count: usize) -> (Vec<u64>, HashMap<u64, usize>) {
+    let ids: Vec<u64> = (0..count).map(|_| worker.next_id().unwrap()).collect();
+    let mut per_tick = HashMap::new();
+    for &id in &ids {
+        *per_tick.entry(worker.decode(id).timestamp_ms).or_insert(0) += 1;
+    }
+    (ids, per_tick)
+}
+
+#[test]
+fn spin_and_sleep_wait_for_the_next_millisecond() {
+    for policy in [SequenceExhaustedPolicy::Spin, SequenceExhaustedPolicy::Sleep] {
+        let (clock, mut worker) = narrow_worker(policy);
+        // 报告时间停住5ms: 第一毫秒的16个序列号用完后一直等到时钟恢复
+        clock.freeze(Duration::from_millis(5));
+        let (ids, per_tick) = generate(&mut worker, 100);
+        assert!(ids.windows(2).all(|w| w[0] < w[1]), "{:?}", policy);
+        assert!(per_tick.values().all(|&n| n <= 16), "{:?}", policy);
+        // 没有借用: ID的时间不超过时钟
+        assert!(per_tick.keys().all(|&ms| ms <= clock.reported_ms()), "{:?}", policy);
+        assert!(clock.real_ms() >= START_MS + 5);
+
+        let stats = worker.sequence_stats();
+        assert!(stats.exhausted_ticks >= 1 && stats.wait_count >= 1, "{:?}: {:?}", policy, stats);
+        assert_eq!((stats.rejected_count, stats.borrowed_ticks), (0, 0), "{:?}", policy);
+        assert!(!worker.clock_state().borrowing);
+    }
+}
+
+#[test]
+fn error_policy_rejects_until_the_next_millisecond() {
+    let (clock, mut worker) = narrow_worker(SequenceExhaustedPolicy::Error);
+    clock.freeze(Duration::from_millis(5));
+    let (first, _) = generate(&mut worker, 16);
+    for _ in 0..3 {
+        let err = worker.next_id().unwrap_err();
+        assert!(matches!(err, SnowflakeError::SequenceExhausted(_)), "{:?}", err);
+    }
+    let stats = worker.sequence_stats();
+    // 同一毫秒多次报错只算一次用尽
+    assert_eq!((stats.exhausted_ticks, stats.rejected_count, stats.wait_count), (1, 3, 0));
+
+    // 冻结结束后报告时间从停住处继续，再过1ms进入下一毫秒: 从0开始，ID继续递增
+    clock.advance(Duration::from_millis(6));
+    let id = worker.next_id().unwrap();
+    assert!(id > *first.last().unwrap());
+    assert_eq!(worker.decode(id).sequence, 0);
+}
+
+#[test]
+fn borrow_runs_ahead_up_to_the_limit_then_waits() {
+    let (clock, mut worker) = narrow_worker(SequenceExhaustedPolicy::Borrow(5));
+    clock.freeze(Duration::from_millis(20));
+    let tick = clock.reported_ms();
+
+    // 当前毫秒加上借用的5个毫秒，共96个ID不需要等待
+    let (ids, per_tick) = generate(&mut worker, 96);
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(per_tick.len(), 6);
+    assert!(per_tick.values().all(|&n| n == 16));
+    assert_eq!(worker.decode(ids[95]).timestamp_ms, tick + 5);
+    assert!(worker.clock_state().borrowing);
+    assert_eq!(clock.reported_ms(), tick);
+    let stats = worker.sequence_stats();
+    assert_eq!((stats.borrowed_ticks, stats.max_borrow_ahead_ms, stats.wait_count), (5, 5, 0));
+
+    // 超出借用上限: 等时钟前进后再借用下一个毫秒
+    let id = worker.next_id().unwrap();
+    assert!(id > ids[95]);
+    assert_eq!(worker.decode(id).timestamp_ms, tick + 6);
+    assert!(clock.reported_ms() > tick);
+    let stats = worker.sequence_stats();
+    assert_eq!((stats.borrowed_ticks, stats.wait_count), (6, 1));
+}
@@ -0,0 +1,80 @@
+//! 每毫秒序列号的起始值 (SequenceStart)
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use snowflake::simulation::SimulatedClock;
+use snowflake::{SequenceExhaustedPolicy, SequenceStart, SnowflakeConfig, SnowflakeError, SnowflakeIdWorker, TWEPOCH_MS};
+
+const START_MS: u64 = TWEPOCH_MS + 60_000;
+
+fn config(start: SequenceStart, sequence_bits: u8) -> SnowflakeConfig {
+    SnowflakeConfig {
+        sequence_bits,
+        sequence_start: start,
+        sequence_exhausted_policy: SequenceExhaustedPolicy::Error,
+        ..Default::default()
+    }
+}
+
+/// 每次读取时钟前进1ms: 每个ID都是所在毫秒的第一个ID
+fn first_sequences(start: SequenceStart, sequence_bits: u8, count: usize) -> Vec<u64> {
+    let clock = SimulatedClock::with_tick(START_MS, Duration::from_millis(1));
+    let mut worker = SnowflakeIdWorker::with_clock(Some(config(start, sequence_bits)), clock).unwrap();
+    (0..count)
+        .map(|_| {
+            let id = worker.next_id().unwrap();
+            worker.decode(id).sequence
+        })
+        .collect()
+}
+
+#[test]
+fn zero_start_begins_every_tick_at_zero() {
+    assert!(first_sequences(SequenceStart::Zero, 12, 100).iter().all(|&seq| seq == 0));
+}
+
+#[test]
+fn rotating_start_advances_one_per_tick_and_wraps() {
+    let sequences = first_sequences(SequenceStart::Rotating, 3, 10);
+    assert_eq!(sequences, [1, 2, 3, 4, 5, 6, 7, 0, 1, 2]);
+}
+
+#[test]
+fn random_start_spreads_low_traffic_ids_across_shards() {
+    let sequences = first_sequences(SequenceStart::Random, 12, 1000);
+    assert!(sequences.iter().all(|&seq| seq < 4096));
+    let distinct: HashSet<_> = sequences.iter().collect();
+    assert!(distinct.len() > 800, "{}", distinct.len());
+
+    // 按 id % 4 分片: 每个分片都分到接近1/4 (Zero时全部落在0号分片)
+    let mut shards = [0usize; 4];
+    for seq in &sequences {
+        shards[(seq % 4) as usize] += 1;
+    }
+    assert!(shards.iter().all(|&n| n > 150), "{:?}", shards);
+}
+
+#[test]
+fn non_zero_start_keeps_full_capacity_per_tick() {
+    for start in [SequenceStart::Random, SequenceStart::Rotating] {
+        let clock = SimulatedClock::new(START_MS);
+        let mut worker = SnowflakeIdWorker::with_clock(Some(config(start, 4)), clock.clone()).unwrap();
+        // 报告时间停在同一毫秒: 回绕到起始值时才用尽
+        clock.freeze(Duration::from_secs(1));
+        let mut ids = Vec::new();
+        let err = loop {
+            match worker.next_id() {
+                Ok(id) => ids.push(id),
+                Err(e) => break e,
+            }
+        };
+        assert!(matches!(err, SnowflakeError::SequenceExhausted(_)), "{:?}", err);
+        assert_eq!(ids.len(), 16, "{:?}", start);
+
+        let sequences: HashSet<u64> = ids.iter().map(|&id| worker.decode(id).sequence).collect();
+        assert_eq!(sequences, (0..16).collect(), "{:?}", start);
+        let timestamps: HashSet<u64> = ids.iter().map(|&id| worker.decode(id).timestamp_ms).collect();
+        assert_eq!(timestamps.len(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+//! 类型化雪花ID测试
+
+use std::collections::HashSet;
+
+use snowflake::{decode_id, set_global_config, SnowflakeConfig, SnowflakeId};
+
+struct Order;
+struct User;
+
+#[test]
+fn accessors_match_decode() {
+    let id: SnowflakeId<Order> = SnowflakeId::generate().unwrap();
+    let decoded = decode_id(id.get());
+    assert_eq!(id.decode(), decoded);
+    assert_eq!(id.timestamp_ms(), decoded.timestamp_ms);
+    assert_eq!(id.worker_id(), decoded.worker_id);
+    assert_eq!(id.sequence(), decoded.sequence);
+}
+
+#[test]
+fn display_and_from_str_round_trip() {
+    let id = SnowflakeId::<User>::new(1_234_567_890_123_456_789);
+    assert_eq!(id.to_string(), "1234567890123456789");
+    assert_eq!("1234567890123456789".parse::<SnowflakeId<User>>(), Ok(id));
+    assert!("-1".parse::<SnowflakeId>().is_err());
+    assert!("abc".parse::<SnowflakeId>().is_err());
+    assert_eq!(format!("{:?}", id), "SnowflakeId(1234567890123456789)");
+}
+
+#[test]
+fn ordering_and_hashing_follow_value() {
+    let ids: Vec<SnowflakeId<Order>> = (0..100).map(|_| SnowflakeId::generate().unwrap()).collect();
+    let mut sorted = ids.clone();
+    sorted.sort();
+    assert_eq!(ids, sorted);
+
+    let set: HashSet<_> = ids.iter().copied().collect();
+    assert_eq!(set.len(), ids.len());
+
+    let raw: u64 = ids[0].into();
+    assert_eq!(ids[0].cast::<User>().get(), raw);
+}
+
+#[test]
+fn decode_follows_the_running_worker() {
+    let id = SnowflakeId::<Order>::generate().unwrap();
+    let before = id.decode();
+
+    // 全局Worker创建后再修改全局配置不会影响它，解析仍按它实际使用的位布局
+    set_global_config(SnowflakeConfig {
+        sequence_bits: 16,
+        ..Default::default()
+    });
+    assert_eq!(id.decode(), before);
+    assert_eq!(decode_id(id.get()), before);
+    set_global_config(SnowflakeConfig::default());
+}
+
+#[cfg(feature = "serde")]
+mod serde_support {
+    use serde::{Deserialize, Serialize};
+    use snowflake::SnowflakeId;
+
+    use super::{Order, User};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct OrderDto {
+        id: SnowflakeId<Order>,
+        #[serde(with = "snowflake::id::as_number")]
+        user_id: SnowflakeId<User>,
+    }
+
+    #[test]
+    fn serializes_as_string_or_number() {
+        let dto = OrderDto {
+            id: SnowflakeId::new(u64::MAX),
+            user_id: SnowflakeId::new(42),
+        };
+        let json = serde_json::to_string(&dto).unwrap();
+        assert_eq!(json, r#"{"id":"18446744073709551615","user_id":42}"#);
+        assert_eq!(serde_json::from_str::<OrderDto>(&json).unwrap(), dto);
+    }
+
+    #[test]
+    fn deserializes_from_string_or_number() {
+        let dto: OrderDto = serde_json::from_str(r#"{"id":7,"user_id":"8"}"#).unwrap();
+        assert_eq!(dto.id.get(), 7);
+        assert_eq!(dto.user_id.get(), 8);
+
+        assert!(serde_json::from_str::<SnowflakeId>("-1").is_err());
+        assert!(serde_json::from_str::<SnowflakeId>(r#""12a""#).is_err());
+        assert!(serde_json::from_str::<SnowflakeId>("1.5").is_err());
+    }
+
+    #[test]
+    fn binary_formats_use_u64() {
+        let dto = OrderDto {
+            id: SnowflakeId::new(u64::MAX),
+            user_id: SnowflakeId::new(42),
+        };
+        // bincode不是自描述格式，不支持deserialize_any
+        let bytes = bincode::serialize(&dto).unwrap();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(bincode::deserialize::<OrderDto>(&bytes).unwrap(), dto);
+    }
+}
@@ -0,0 +1,57 @@
+//! 压力测试程序的命令行参数和JSON报告
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use serde_json::Value;
+
+#[test]
+fn writes_verified_json_report() {
+    let report = env::temp_dir().join(format!("snowflake-stress-report-{}.json", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_stress_test"))
+        .args(["--tests", "concurrency,memory", "--modes", "global,batch,worker"])
+        .args(["--threads", "2,4", "--ids-per-thread", "20000", "--batch-size", "64"])
+        .args(["--memory-threads", "3", "--memory-ids-per-thread", "10000", "--pause-ms", "0"])
+        .arg("--report")
+        .arg(&report)
+        .env("SNOWFLAKE_WORKER_ID", "7")
+        .env("RUST_LOG", "off")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let json: Value = serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    fs::remove_file(&report).unwrap();
+    assert_eq!(json["passed"], true);
+
+    let results = json["results"].as_array().unwrap();
+    // 每种模式: 2轮并发测试 + 1轮吞吐测试
+    assert_eq!(results.len(), 9);
+    for result in results {
+        let threads = result["threads"].as_u64().unwrap();
+        let expected = if result["test"] == "memory" { threads * 10_000 } else { threads * 20_000 };
+        assert_eq!(result["total_ids"].as_u64().unwrap(), expected, "{}", result);
+        assert_eq!(result["unique_ids"], result["total_ids"]);
+        assert_eq!(result["verification"]["checked"], result["total_ids"]);
+        assert_eq!(result["duplicates"], 0);
+
+        let latency = &result["latency_ns"];
+        assert!(latency["p50"].as_u64().unwrap() <= latency["p99"].as_u64().unwrap());
+        assert!(latency["p999"].as_u64().unwrap() <= latency["max"].as_u64().unwrap());
+        assert!(result["lock_wait"]["acquisitions"].as_u64().unwrap() > 0);
+    }
+}
+
+#[test]
+fn rejects_invalid_options() {
+    for args in [&["--threads", "0"][..], &["--modes", "fast"], &["--ids-per-thread"], &["--bogus", "1"]] {
+        let status = Command::new(env!("CARGO_BIN_EXE_stress_test"))
+            .args(args)
+            .env("RUST_LOG", "off")
+            .output()
+            .unwrap()
+            .status;
+        assert_eq!(status.code(), Some(2), "{:?}", args);
+    }
+}